@@ -0,0 +1,1083 @@
+use crate::config::{
+    self, ActiveSeasonalEvent, Config, CustomBackground, PreviousBackground, RotationConfig,
+    SeasonalEvent, Target,
+};
+use crate::helpers::{self, Error, ErrorCode, Platform};
+use crate::launch_args::LaunchArgs;
+use crate::platforms::{self, FilePreview, LauncherPlatform, battle_net, steam};
+use crate::{
+    backgrounds, custom_backgrounds, discovery, history, launch_options, rotation, search, seasonal,
+};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Operations shared by the Tauri commands and the CLI. They read and write the
+// OverBuddy configuration in an explicit data directory instead of using the app handle.
+
+/// The result of an operation that changes launch arguments.
+pub enum Applied {
+    Saved(Config),
+    /// Nothing was written, see [`platforms::Preview`].
+    Preview(Config, Vec<FilePreview>),
+}
+
+impl Applied {
+    pub fn config(&self) -> &Config {
+        match self {
+            Applied::Saved(config) | Applied::Preview(config, _) => config,
+        }
+    }
+
+    pub fn into_config(self) -> Config {
+        match self {
+            Applied::Saved(config) | Applied::Preview(config, _) => config,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        match self {
+            Applied::Saved(config) => Ok(serde_json::to_string(config)?),
+            Applied::Preview(config, files) => Ok(serde_json::to_string(&platforms::Preview {
+                config,
+                files,
+            })?),
+        }
+    }
+}
+
+pub enum SteamSetup {
+    Confirmed(Box<Config>),
+    /// No Steam account has Overwatch installed. Fatal if Battle.net is not enabled either.
+    NoOverwatch {
+        fatal: bool,
+    },
+}
+
+pub fn query_backgrounds(
+    data_dir: &Path,
+    query: &search::BackgroundQuery,
+) -> Result<search::QueryResult, Error> {
+    let config = config::read_config_from(data_dir)?;
+
+    Ok(search::query(
+        &backgrounds::get_backgrounds(),
+        query,
+        &history::recent(&config),
+    ))
+}
+
+/// Read the configuration and update it with the state of the launchers, without saving
+/// it.
+pub fn read_launch_config(data_dir: &Path) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    if !config.battle_net.enabled && !config.steam.enabled {
+        config.is_setup = false;
+    }
+
+    if config.is_setup {
+        // TODO: Show user a warning if the backup file exists
+
+        config.shared = platforms::read_shared_state(&mut config)?;
+
+        if let Some(custom) = config.shared.background.custom.as_deref() {
+            if config.shared.background.current.is_none()
+                || config.shared.background.current.as_deref() != Some(custom)
+            {
+                config.shared.background.custom = None;
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+pub fn get_launch_config(data_dir: &Path) -> Result<Config, Error> {
+    let config = read_launch_config(data_dir)?;
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Report the launch arguments and state of every target. Nothing is written, and
+/// unreadable launcher files are reported instead of failing.
+pub fn get_status(data_dir: &Path) -> Result<platforms::StatusReport, Error> {
+    let config = config::read_config_from(data_dir)?;
+
+    Ok(platforms::get_status(&config))
+}
+
+/// Get the launch options of every target with warnings about their flags, and the
+/// catalog of known flags.
+pub fn get_launch_options(data_dir: &Path) -> Result<launch_options::LaunchOptions, Error> {
+    let config = config::read_config_from(data_dir)?;
+
+    let targets = platforms::get_status(&config)
+        .targets
+        .into_iter()
+        .map(|status| {
+            let launch_args = LaunchArgs::parse(status.launch_args.clone().unwrap_or_default());
+            launch_options::TargetLaunchOptions {
+                target: status.state.target,
+                name: status.state.name,
+                args: launch_args
+                    .args()
+                    .iter()
+                    .map(|arg| arg.value.clone())
+                    .collect(),
+                warnings: launch_options::check(&launch_args),
+                launch_args: status.launch_args,
+                error: status.error,
+            }
+        })
+        .collect();
+
+    Ok(launch_options::LaunchOptions {
+        targets,
+        catalog: launch_options::CATALOG,
+    })
+}
+
+/// Add a launch option to the given targets, or to every target if `None`. The value is
+/// replaced where the flag is already set.
+pub fn set_launch_option(
+    data_dir: &Path,
+    flag: &str,
+    value: Option<&str>,
+    targets: Option<&[Target]>,
+    preview: bool,
+) -> Result<Applied, Error> {
+    launch_options::validate(flag, value)?;
+    let config = config::read_config_from(data_dir)?;
+    validate_targets(&config, targets)?;
+
+    let transform = |launch_args: Option<&str>| launch_options::set(launch_args, flag, value);
+    let preview = if preview {
+        Some(platforms::preview_launch_args(
+            &config, targets, &transform,
+        )?)
+    } else {
+        platforms::apply_launch_args(&config, targets, "set launch option", &transform)?;
+        None
+    };
+
+    save_config(data_dir, config, preview)
+}
+
+/// Remove a launch option from the given targets, or from every target if `None`.
+pub fn remove_launch_option(
+    data_dir: &Path,
+    flag: &str,
+    targets: Option<&[Target]>,
+    preview: bool,
+) -> Result<Applied, Error> {
+    launch_options::validate_flag(flag)?;
+    let config = config::read_config_from(data_dir)?;
+    validate_targets(&config, targets)?;
+
+    let transform = |launch_args: Option<&str>| launch_options::remove(launch_args, flag);
+    let preview = if preview {
+        Some(platforms::preview_launch_args(
+            &config, targets, &transform,
+        )?)
+    } else {
+        platforms::apply_launch_args(&config, targets, "remove launch option", &transform)?;
+        None
+    };
+
+    save_config(data_dir, config, preview)
+}
+
+pub fn setup(data_dir: &Path, platforms: &[&str], is_initialized: bool) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    if platforms.contains(&"BattleNet") {
+        // Check if Battle.net is installed
+        if config.battle_net.install.is_none() {
+            // Let the user pick when there are several installations, e.g. in Wine prefixes
+            let candidates = discovery::find_battle_net_candidates()
+                .into_iter()
+                .filter(|candidate| candidate.install.is_some())
+                .count();
+            if candidates > 1 {
+                return Err(Error::new(
+                    ErrorCode::BattleNetPrefix,
+                    "Found more than one Battle.net installation",
+                )
+                .with_platform(Platform::BattleNet)
+                .with_platforms(platforms));
+            }
+
+            battle_net::BattleNet.discover(&mut config)?;
+        }
+        if config.battle_net.install.is_none() {
+            return Err(Error::new(
+                ErrorCode::BattleNetInstall,
+                "Failed to find your Battle.net installation",
+            )
+            .with_platform(Platform::BattleNet)
+            .with_platforms(platforms));
+        }
+
+        // Check if Battle.net config exists
+        let battle_net_config = match &config.battle_net.config {
+            Some(battle_net_config) => battle_net_config.clone(),
+            None => {
+                let path = env::var_os("appdata").map(|path| Path::new(&path).join("Battle.net"));
+
+                if let Some(resource_path) = path {
+                    // Check if Battle.net AppData directory exists
+                    if let Ok(entries) = fs::read_dir(&resource_path) {
+                        // Check if Battle.net.config exists in the directory
+                        if let Some(target_entry) =
+                            entries.filter_map(|entry| entry.ok()).find(|entry| {
+                                entry.file_name().to_string_lossy() == battle_net::CONFIG_FILE
+                            })
+                        {
+                            let display_path = helpers::display_path_string(&target_entry.path())?;
+                            config.battle_net.config = Some(display_path.clone());
+                            display_path
+                        } else {
+                            let display_path = helpers::display_path_string(&resource_path)?;
+                            return Err(Error::new(
+                                ErrorCode::BattleNetConfig,
+                                format!(
+                                    "Failed to find [[{}]] file at [[{}]]",
+                                    battle_net::CONFIG_FILE,
+                                    display_path
+                                ),
+                            )
+                            .with_platform(Platform::BattleNet)
+                            .with_path(&resource_path)
+                            .with_platforms(platforms));
+                        }
+                    } else {
+                        let display_path = helpers::display_path_string(&resource_path)?;
+                        return Err(Error::new(
+                            ErrorCode::BattleNetConfig,
+                            format!(
+                                "Failed to read [[{}]] file at [[{}]]",
+                                battle_net::CONFIG_FILE,
+                                display_path
+                            ),
+                        )
+                        .with_platform(Platform::BattleNet)
+                        .with_path(&resource_path)
+                        .with_platforms(platforms));
+                    }
+                } else {
+                    return Err(Error::new(
+                        ErrorCode::BattleNetConfig,
+                        "Failed to find the Battle.net AppData directory",
+                    )
+                    .with_platform(Platform::BattleNet)
+                    .with_platforms(platforms));
+                }
+            }
+        };
+
+        // Read and parse Battle.net.config file
+        let file = match std::fs::File::open(&battle_net_config) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorCode::BattleNetConfig,
+                    format!(
+                        "Failed to open the [[{}]] file at [[{}]]: {}",
+                        battle_net::CONFIG_FILE,
+                        battle_net_config,
+                        e
+                    ),
+                )
+                .with_platform(Platform::BattleNet)
+                .with_path(&battle_net_config)
+                .with_io(&e)
+                .with_platforms(platforms));
+            }
+        };
+        let mut json: serde_json::Value = match serde_json::from_reader(file) {
+            Ok(json) => json,
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorCode::BattleNetConfig,
+                    format!(
+                        "Failed to read [[{}]] file at [[{}]]: {}",
+                        battle_net::CONFIG_FILE,
+                        battle_net_config,
+                        e
+                    ),
+                )
+                .with_platform(Platform::BattleNet)
+                .with_path(&battle_net_config)
+                .with_platforms(platforms));
+            }
+        };
+
+        // Check Overwatch installation on Battle.net
+        if let None = json
+            .get_mut("Games")
+            .and_then(|games| games.get_mut("prometheus"))
+        {
+            return Err(Error::new(
+                ErrorCode::NoOverwatch,
+                "Unable to find an Overwatch installation on Battle.net",
+            )
+            .with_platform(Platform::BattleNet));
+        };
+
+        let mut battle_net_was_closed = false;
+
+        // Check and create DefaultStartupScreen if it doesn't exist
+        if let Some(client_config) = json.get_mut("Client") {
+            if client_config.get("DefaultStartupScreen").is_none() {
+                client_config
+                    .as_object_mut()
+                    .unwrap()
+                    .insert("DefaultStartupScreen".to_string(), serde_json::json!("1"));
+
+                battle_net_was_closed = battle_net::close_app();
+            } else {
+                let startup_screen = client_config["DefaultStartupScreen"]
+                    .as_str()
+                    .map(|s| s.to_string());
+
+                if startup_screen.is_none() || startup_screen.unwrap() == "0" {
+                    client_config
+                        .as_object_mut()
+                        .unwrap()
+                        .insert("DefaultStartupScreen".to_string(), serde_json::json!("1"));
+
+                    battle_net_was_closed = battle_net::close_app();
+                }
+            }
+        }
+
+        // Update config
+        let battle_net_states = battle_net::update_config(&config)?;
+        config.shared = platforms::merge_states(&config, &battle_net_states);
+        config.targets = battle_net_states;
+
+        // Cleanup: Reopen Battle.net if it was closed
+        if battle_net_was_closed {
+            helpers::safe_json_write(battle_net_config, &json)?;
+            battle_net::open_app(&config)?;
+        }
+
+        // Enable Battle.net
+        config.battle_net.enabled = true;
+    } else {
+        platforms::reset_platform(&battle_net::BattleNet, &config)?;
+
+        // Disable Battle.net
+        config.battle_net.enabled = false;
+    }
+
+    if platforms.contains(&"Steam") {
+        // Check if Steam is installed
+        if config.steam.install.is_none() {
+            steam::Steam.discover(&mut config)?;
+        }
+        if config.steam.install.is_none() {
+            return Err(Error::new(
+                ErrorCode::SteamInstall,
+                "Failed to find your Steam installation",
+            )
+            .with_platform(Platform::Steam)
+            .with_platforms(platforms));
+        }
+        let steam_install = config.steam.install.clone().unwrap();
+
+        // Check if Steam localconfig exists
+        let steam_path = Path::new(&steam_install).parent().ok_or_else(|| {
+            Error::new(
+                ErrorCode::SteamInstall,
+                "Failed to read the parent directory of your Steam installation",
+            )
+            .with_platform(Platform::Steam)
+            .with_platforms(platforms)
+        })?;
+
+        let userdata_path = steam_path.join("userdata");
+        if !userdata_path.exists() || !userdata_path.is_dir() {
+            return Err(Error::new(
+                ErrorCode::SteamAccount,
+                format!(
+                    "Failed to read your Steam [[userdata]] folder, located at [[{}]]",
+                    userdata_path.to_string_lossy()
+                ),
+            )
+            .with_platform(Platform::Steam)
+            .with_path(&userdata_path)
+            .with_platforms(platforms));
+        }
+
+        config.steam.configs = Some(steam::get_configs(&config)?);
+        if config.steam.configs.is_none() || config.steam.configs.as_ref().unwrap().is_empty() {
+            return Err(Error::new(
+                ErrorCode::SteamAccount,
+                format!(
+                    "Failed to find any accounts in your Steam [[userdata]] folder, located at [[{}]]",
+                    userdata_path.to_string_lossy()
+                ),
+            )
+            .with_platform(Platform::Steam)
+            .with_path(&userdata_path)
+            .with_platforms(platforms));
+        }
+
+        // Enable Steam
+        if !config.steam.enabled {
+            config.steam.in_setup = true;
+        }
+        config.steam.enabled = true;
+    } else {
+        platforms::reset_platform(&steam::Steam, &config)?;
+
+        // Disable Steam
+        config.steam.profiles = None;
+        config.steam.configs = None;
+        config.steam.overwatch = None;
+        config.steam.in_setup = false;
+        config.steam.enabled = false;
+    }
+
+    // Check if no platforms were enabled
+    if !config.battle_net.enabled && !config.steam.enabled {
+        config.is_setup = false;
+        if !is_initialized {
+            return Err(Error::new(
+                ErrorCode::NotSetup,
+                format!(
+                    "Failed to setup one of your requested platforms: [[{}]]",
+                    platforms.join("]], [[").replace("BattleNet", "Battle.net")
+                ),
+            ));
+        }
+
+        config.shared.background.current = None;
+        config.shared.background.is_outdated = false;
+        config.shared.background.retired = None;
+        config.shared.additional.console_enabled = false;
+    } else {
+        config.is_setup = true;
+    }
+
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Read the Steam accounts and finish the Steam setup.
+pub fn confirm_steam_setup(data_dir: &Path) -> Result<SteamSetup, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    let steam_states = steam::update_config(&mut config)?;
+
+    // Check that at least one account has Overwatch
+    if let Some(profiles) = &config.steam.profiles {
+        let steam_has_overwatch = profiles.iter().any(|profile| profile.has_overwatch);
+        if !steam_has_overwatch {
+            return Ok(SteamSetup::NoOverwatch {
+                fatal: !config.battle_net.enabled,
+            });
+        }
+    }
+
+    config
+        .targets
+        .retain(|state| state.target == Target::BattleNet && config.battle_net.enabled);
+    config.targets.extend(steam_states);
+    config.shared = platforms::merge_states(&config, &config.targets);
+
+    config.steam.in_setup = false;
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(SteamSetup::Confirmed(Box::new(config)))
+}
+
+/// Disable Steam after its setup was cancelled.
+pub fn undo_steam_setup(data_dir: &Path) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    config.steam.profiles = None;
+    config.steam.configs = None;
+    config.steam.overwatch = None;
+    config.steam.in_setup = false;
+    config.steam.enabled = false;
+
+    if !config.battle_net.enabled {
+        config.is_setup = false;
+    }
+
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Include or exclude a Steam account. The launch arguments OverBuddy applied to an
+/// account are removed when it is excluded, afterwards it is left alone.
+pub fn set_steam_account_included(
+    data_dir: &Path,
+    id: &str,
+    included: bool,
+) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    let Some(profile) = config
+        .steam
+        .profiles
+        .iter()
+        .flatten()
+        .find(|profile| profile.id == id)
+    else {
+        return Err(Error::new(
+            ErrorCode::SteamAccount,
+            format!("Failed to find a Steam account with id [[{}]]", id),
+        )
+        .with_platform(Platform::Steam));
+    };
+
+    let target = Target::Steam(id.to_string());
+    if !included && profile.included && platforms::targets(&config).contains(&target) {
+        platforms::apply_launch_args(
+            &config,
+            Some(&[target]),
+            "exclude the Steam account",
+            &|launch_args| platforms::reset_launch_args(&config, launch_args),
+        )?;
+    }
+
+    for profile in config.steam.profiles.iter_mut().flatten() {
+        if profile.id == id {
+            profile.included = included;
+            profile.is_new = false;
+        }
+    }
+    config.shared = platforms::read_shared_state(&mut config)?;
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Clear the new mark of every Steam account once the user has seen them.
+pub fn mark_steam_accounts_seen(data_dir: &Path) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    for profile in config.steam.profiles.iter_mut().flatten() {
+        profile.is_new = false;
+    }
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Set whether Steam accounts that are not known yet are included.
+pub fn set_include_new_steam_accounts(data_dir: &Path, include: bool) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    config.steam.include_new_accounts = include;
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Save the configuration, or return it along with the planned file changes when previewing.
+fn save_config(
+    data_dir: &Path,
+    config: Config,
+    preview: Option<Vec<FilePreview>>,
+) -> Result<Applied, Error> {
+    match preview {
+        Some(files) => Ok(Applied::Preview(config, files)),
+        None => {
+            config::write_config_to(data_dir, &config)?;
+            Ok(Applied::Saved(config))
+        }
+    }
+}
+
+/// Check that the given targets exist.
+fn validate_targets(config: &Config, targets: Option<&[Target]>) -> Result<(), Error> {
+    let Some(targets) = targets else {
+        return Ok(());
+    };
+
+    let available = platforms::targets(config);
+    match targets.iter().find(|target| !available.contains(target)) {
+        Some(target) => Err(Error::new(
+            ErrorCode::InvalidArgument,
+            format!("Failed to find the target [[{}]]", target),
+        )
+        .with_hint("Targets are Battle.net and the Steam accounts that have Overwatch.")),
+        None => Ok(()),
+    }
+}
+
+/// Refuse an action that applies one background to every target while the targets have
+/// different backgrounds, so their backgrounds are not overwritten.
+fn ensure_same_background(config: &Config, action: &str) -> Result<(), Error> {
+    let mut backgrounds = config.targets.iter().map(|state| &state.background.current);
+    match backgrounds.next() {
+        Some(first) if backgrounds.any(|background| background != first) => Err(Error::new(
+            ErrorCode::InvalidArgument,
+            format!("Cannot {} while targets have different backgrounds", action),
+        )
+        .with_hint("Apply one background to every target first.")),
+        _ => Ok(()),
+    }
+}
+
+/// Apply a background to the given targets, or to every target if `None`.
+pub fn set_background(
+    data_dir: &Path,
+    id: &str,
+    is_custom: bool,
+    targets: Option<&[Target]>,
+    preview: bool,
+) -> Result<Applied, Error> {
+    apply_background(data_dir, id, is_custom, targets, preview, true)
+}
+
+/// Apply a background, adding it to the history if `record` is set. Backgrounds the app
+/// applies on its own, like seasonal ones, are not the user's choice and are not recorded.
+fn apply_background(
+    data_dir: &Path,
+    id: &str,
+    is_custom: bool,
+    targets: Option<&[Target]>,
+    preview: bool,
+    record: bool,
+) -> Result<Applied, Error> {
+    let id = &backgrounds::normalize_id(id)?;
+    let mut config = config::read_config_from(data_dir)?;
+    validate_targets(&config, targets)?;
+
+    let transform =
+        |launch_args: Option<&str>| helpers::generate_background_launch_args(launch_args, Some(id));
+    let preview = if preview {
+        Some(platforms::preview_launch_args(
+            &config, targets, &transform,
+        )?)
+    } else {
+        platforms::apply_launch_args(&config, targets, "apply background", &transform)?;
+        None
+    };
+
+    // Ids from the custom background library are custom even when applied from elsewhere
+    let is_custom = is_custom || custom_backgrounds::find(&config, id).is_some();
+    if preview.is_none() {
+        if is_custom {
+            custom_backgrounds::remember(&mut config, id);
+        }
+        if record {
+            history::record(&mut config, id, is_custom);
+        }
+    }
+
+    let background = || config::BackgroundConfig {
+        current: Some(id.to_string()),
+        is_outdated: false,
+        custom: is_custom.then(|| id.to_string()),
+        retired: None,
+    };
+    for state in &mut config.targets {
+        if platforms::is_selected(targets, &state.target) {
+            state.background = background();
+        }
+    }
+    if targets.is_some() {
+        config.shared.background.custom = background().custom;
+        config.shared = platforms::merge_states(&config, &config.targets);
+    } else {
+        config.shared.background = background();
+    }
+
+    save_config(data_dir, config, preview)
+}
+
+pub fn save_custom_background(
+    data_dir: &Path,
+    background: CustomBackground,
+    image: Option<&Path>,
+) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    custom_backgrounds::save(&mut config, data_dir, background, image)?;
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+pub fn delete_custom_background(data_dir: &Path, id: &str) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    custom_backgrounds::delete(&mut config, data_dir, id)?;
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Add or remove a background from the favorites.
+pub fn set_favorite(data_dir: &Path, id: &str, favorite: bool) -> Result<Config, Error> {
+    let id = backgrounds::normalize_id(id)?;
+    let mut config = config::read_config_from(data_dir)?;
+
+    config.favorites.retain(|favorite| *favorite != id);
+    if favorite {
+        config.favorites.push(id);
+    }
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Save the rotation settings. The last rotation time is kept, and enabling the rotation
+/// starts its interval, so the first background is not applied right away.
+pub fn set_rotation(data_dir: &Path, mut rotation: RotationConfig) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    rotation.no_repeat = rotation.no_repeat.min(history::RECENT_LIMIT);
+    rotation.last_rotated = if rotation.enabled && !config.rotation.enabled {
+        Some(helpers::now())
+    } else {
+        config.rotation.last_rotated
+    };
+    config.rotation = rotation;
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Apply the next background of the rotation to every target.
+pub fn rotate_background(data_dir: &Path) -> Result<Config, Error> {
+    let config = config::read_config_from(data_dir)?;
+    ensure_same_background(&config, "rotate the background")?;
+    let Some(id) = rotation::pick_next(&config) else {
+        return Err(Error::new(
+            ErrorCode::InvalidArgument,
+            "There are no other backgrounds to rotate to",
+        )
+        .with_hint("Add favorites or choose other tags for the rotation."));
+    };
+
+    let mut config = set_background(data_dir, &id, false, None, false)?.into_config();
+    config.rotation.last_rotated = Some(helpers::now());
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Enable or disable the seasonal calendar, and replace its events if given.
+pub fn set_seasonal_calendar(
+    data_dir: &Path,
+    enabled: bool,
+    events: Option<Vec<SeasonalEvent>>,
+) -> Result<Config, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    if let Some(mut events) = events {
+        seasonal::validate(&config, &mut events)?;
+        config.seasonal.events = events;
+    }
+    config.seasonal.enabled = enabled;
+    config::write_config_to(data_dir, &config)?;
+
+    Ok(config)
+}
+
+/// Apply the background of the seasonal event that runs today, and restore the previous
+/// background of every target once it ends. Returns the saved configuration if anything
+/// changed.
+///
+/// The previous background of a target is only restored if the user did not change its
+/// background during the event. The event state is saved even if applying fails, so a broken
+/// launcher is not restarted over and over.
+pub fn apply_seasonal_calendar(data_dir: &Path) -> Result<Option<Config>, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+    let event = seasonal::current_event(&config, seasonal::today()).cloned();
+
+    let is_unchanged = match (&config.seasonal.active, &event) {
+        (Some(active), Some(event)) => {
+            active.name == event.name && active.background == event.background
+        }
+        (None, None) => true,
+        _ => false,
+    };
+    if is_unchanged {
+        return Ok(None);
+    }
+
+    let mut result = Ok(());
+    if let Some(active) = config.seasonal.active.take() {
+        // Group the targets that still show the event background by what they had before
+        let mut restores: Vec<(Option<&str>, bool, Vec<Target>)> = vec![];
+        for previous in &active.previous {
+            let is_unchanged = config.targets.iter().any(|state| {
+                state.target == previous.target
+                    && state.background.current.as_deref() == Some(active.background.as_str())
+            });
+            if !is_unchanged {
+                continue;
+            }
+
+            let background = previous.background.as_deref();
+            match restores
+                .iter_mut()
+                .find(|(id, is_custom, _)| *id == background && *is_custom == previous.is_custom)
+            {
+                Some((_, _, targets)) => targets.push(previous.target.clone()),
+                None => restores.push((
+                    background,
+                    previous.is_custom,
+                    vec![previous.target.clone()],
+                )),
+            }
+        }
+
+        for (background, is_custom, targets) in restores {
+            let restored = match background {
+                Some(id) => apply_background(data_dir, id, is_custom, Some(&targets), false, false),
+                None => reset_background(data_dir, Some(&targets), false),
+            };
+            match restored {
+                Ok(applied) => config = applied.into_config(),
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+        config.seasonal.active = None;
+    }
+
+    if let Some(event) = event {
+        let previous: Vec<PreviousBackground> = config
+            .targets
+            .iter()
+            .map(|state| PreviousBackground {
+                target: state.target.clone(),
+                background: state.background.current.clone(),
+                is_custom: state.background.custom.is_some(),
+            })
+            .collect();
+        let targets: Vec<Target> = previous
+            .iter()
+            .filter(|previous| previous.background.as_deref() != Some(event.background.as_str()))
+            .map(|previous| previous.target.clone())
+            .collect();
+        if result.is_ok() && !targets.is_empty() {
+            let applied = apply_background(
+                data_dir,
+                &event.background,
+                false,
+                Some(&targets),
+                false,
+                false,
+            );
+            match applied {
+                Ok(applied) => config = applied.into_config(),
+                Err(error) => result = Err(error),
+            }
+        }
+        config.seasonal.active = Some(ActiveSeasonalEvent {
+            name: event.name,
+            background: event.background,
+            previous,
+        });
+    }
+
+    config::write_config_to(data_dir, &config)?;
+    result.map(|_| Some(config))
+}
+
+pub fn get_history(data_dir: &Path) -> Result<history::History, Error> {
+    let config = config::read_config_from(data_dir)?;
+
+    Ok(history::get_history(&config))
+}
+
+/// Apply the background that was applied before the current one to every target.
+pub fn revert_background(data_dir: &Path, preview: bool) -> Result<Applied, Error> {
+    let config = config::read_config_from(data_dir)?;
+    ensure_same_background(&config, "revert the background")?;
+    let Some(previous) = history::previous(&config) else {
+        return Err(Error::new(
+            ErrorCode::InvalidArgument,
+            "There is no previous background to revert to",
+        ));
+    };
+
+    if backgrounds::find_background_by_id(&previous.id).is_none()
+        && custom_backgrounds::find(&config, &previous.id).is_none()
+    {
+        let mut error = Error::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "The previous background [[{}]] can no longer be applied",
+                previous.name
+            ),
+        );
+        if let Some(replacement) = backgrounds::find_retired_background(&previous.id)
+            .and_then(|retired| retired.replacement)
+            .and_then(|replacement| backgrounds::find_background_by_id(&replacement))
+        {
+            error = error.with_hint(format!("Try {} instead.", replacement.name));
+        }
+        return Err(error);
+    }
+
+    set_background(data_dir, &previous.id, previous.is_custom, None, preview)
+}
+
+/// Remove the background from the given targets, or from every target if `None`.
+pub fn reset_background(
+    data_dir: &Path,
+    targets: Option<&[Target]>,
+    preview: bool,
+) -> Result<Applied, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+    validate_targets(&config, targets)?;
+
+    let transform =
+        |launch_args: Option<&str>| helpers::generate_background_launch_args(launch_args, None);
+    let preview = if preview {
+        Some(platforms::preview_launch_args(
+            &config, targets, &transform,
+        )?)
+    } else {
+        platforms::apply_launch_args(&config, targets, "reset background", &transform)?;
+        None
+    };
+
+    let reset = |background: &mut config::BackgroundConfig| {
+        background.current = None;
+        background.is_outdated = false;
+        background.retired = None;
+        background.custom = None;
+    };
+    for state in &mut config.targets {
+        if platforms::is_selected(targets, &state.target) {
+            reset(&mut state.background);
+        }
+    }
+    if targets.is_some() {
+        config.shared = platforms::merge_states(&config, &config.targets);
+    } else {
+        reset(&mut config.shared.background);
+    }
+
+    save_config(data_dir, config, preview)
+}
+
+pub fn set_debug_console(
+    data_dir: &Path,
+    enable_console: bool,
+    preview: bool,
+) -> Result<Applied, Error> {
+    let mut config = config::read_config_from(data_dir)?;
+
+    let transform = |launch_args: Option<&str>| {
+        helpers::generate_console_launch_args(launch_args, enable_console)
+    };
+    let preview = if preview {
+        Some(platforms::preview_launch_args(&config, None, &transform)?)
+    } else {
+        platforms::apply_launch_args(&config, None, "apply debug console", &transform)?;
+        None
+    };
+
+    for state in &mut config.targets {
+        state.additional.console_enabled = enable_console;
+    }
+    config.shared.additional.console_enabled = enable_console;
+
+    save_config(data_dir, config, preview)
+}
+
+pub fn reset(data_dir: &Path, preview: bool) -> Result<Applied, Error> {
+    let config = config::read_config_from(data_dir);
+
+    let preview = match config {
+        Ok(config) if preview => Some(platforms::preview_reset(&config)?),
+        Ok(config) => {
+            platforms::reset_platforms(&config)?;
+            None
+        }
+        Err(_) if preview => Some(vec![]),
+        Err(_) => None,
+    };
+
+    save_config(data_dir, config::get_default_config(), preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SeasonalEvent;
+    use std::path::PathBuf;
+
+    /// Create a data directory with Battle.net set up and showing `0x0800000000000E77`.
+    fn data_dir(name: &str) -> PathBuf {
+        let data_dir =
+            std::env::temp_dir().join(format!("overbuddy-actions-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&data_dir).ok();
+        fs::create_dir_all(&data_dir).unwrap();
+
+        let battle_net_config = data_dir.join("Battle.net.config");
+        fs::write(
+            &battle_net_config,
+            r#"{"Games":{"prometheus":{"AdditionalLaunchArguments":"--lobbyMap=0x0800000000000E77"}}}"#,
+        )
+        .unwrap();
+
+        let mut config = config::get_default_config();
+        config.is_setup = true;
+        config.battle_net.enabled = true;
+        config.battle_net.config = Some(battle_net_config.to_string_lossy().to_string());
+        config::write_config_to(&data_dir, &config).unwrap();
+        get_launch_config(&data_dir).unwrap();
+
+        data_dir
+    }
+
+    fn launch_args(data_dir: &Path) -> String {
+        fs::read_to_string(data_dir.join("Battle.net.config")).unwrap()
+    }
+
+    #[test]
+    fn applies_seasonal_backgrounds_without_recording_them() {
+        let data_dir = data_dir("seasonal");
+        let mut config = config::read_config_from(&data_dir).unwrap();
+        config.seasonal.enabled = true;
+        config.seasonal.events = vec![SeasonalEvent {
+            name: "Every Day".to_string(),
+            start: "01-01".to_string(),
+            end: "12-31".to_string(),
+            background: "0x08000000000013F0".to_string(),
+        }];
+        config::write_config_to(&data_dir, &config).unwrap();
+
+        let config = apply_seasonal_calendar(&data_dir).unwrap().unwrap();
+        assert!(launch_args(&data_dir).contains("--lobbyMap=0x08000000000013F0"));
+        assert_eq!(config.seasonal.active.unwrap().name, "Every Day");
+        assert!(config.history.is_empty());
+        assert!(apply_seasonal_calendar(&data_dir).unwrap().is_none());
+
+        // The previous background is restored once the event ends
+        let mut config = config::read_config_from(&data_dir).unwrap();
+        config.seasonal.events.clear();
+        config::write_config_to(&data_dir, &config).unwrap();
+
+        let config = apply_seasonal_calendar(&data_dir).unwrap().unwrap();
+        assert!(launch_args(&data_dir).contains("--lobbyMap=0x0800000000000E77"));
+        assert!(config.seasonal.active.is_none());
+        assert!(config.history.is_empty());
+
+        // Backgrounds the user applies are still recorded
+        let config = set_background(&data_dir, "0x13F0", false, None, false)
+            .unwrap()
+            .into_config();
+        assert_eq!(config.history.len(), 1);
+    }
+}
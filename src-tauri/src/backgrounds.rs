@@ -1,797 +1,777 @@
-use serde::Serialize;
+use crate::config::{self, CatalogConfig};
+use crate::helpers::{Error, ErrorCode};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
-#[derive(Serialize)]
+/// Catalog bundled with the app, see `catalog/backgrounds.schema.json` for its format.
+const BUNDLED_CATALOG: &str = include_str!("../catalog/backgrounds.json");
+/// User override file in the app data directory, merged on top of the bundled catalog.
+pub const OVERRIDE_FILE: &str = "backgrounds.json";
+/// Newest catalog format this version of OverBuddy can read.
+const SCHEMA_VERSION: u32 = 1;
+
+/// Last catalog downloaded from the manifest, along with its signature.
+const CACHE_FILE: &str = "backgrounds.cache.json";
+const CACHE_SIGNATURE_FILE: &str = "backgrounds.cache.json.sig";
+/// Catalogs are signed with the same minisign key as app updates, see `tauri.conf.json`.
+const DEFAULT_PUBLIC_KEY: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IG1pbmlzaWduIHB1YmxpYyBrZXk6IEI2OUE5NTMyM0M5ODUzNwpSV1EzaGNralU2bHBDOXM0TzcxSFU3aG9CZlVnV3Znc3lWZ0xianFuT0d4WGJBQklGclNabWhZcQo=";
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(10);
+const UPDATE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Largest manifest or catalog that is downloaded.
+const MAX_DOWNLOAD_SIZE: usize = 4 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Background {
-    pub id: &'static str,
-    pub image: &'static str,
-    pub name: &'static str,
-    pub description: &'static str,
-    pub tags: &'static [&'static str],
+    pub id: String,
+    pub image: String,
+    pub name: String,
+    pub description: String,
+    /// Heroes shown in the background.
+    #[serde(default)]
+    pub heroes: Vec<String>,
+    /// In-game event the background was released for.
+    #[serde(default)]
+    pub event: Option<String>,
+    /// Season the background was released in.
+    #[serde(default)]
+    pub season: Option<u32>,
+    /// Release date as `YYYY-MM-DD`.
+    #[serde(default)]
+    pub release_date: Option<String>,
+    #[serde(default)]
+    pub animated: bool,
+    /// Music track that plays in the menu.
+    #[serde(default)]
+    pub music: Option<String>,
+    /// The menu plays no music.
+    #[serde(default)]
+    pub silent: bool,
+    /// Partner of a collaboration event.
+    #[serde(default)]
+    pub collaboration: Option<String>,
+    /// Tags from the catalog's vocabulary.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
     pub new: bool,
+    #[serde(default, skip_serializing_if = "BackgroundStatus::is_available")]
+    pub status: BackgroundStatus,
+    /// Season the background was removed or broke in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retired_season: Option<u32>,
+    /// Id of an available background to suggest instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BackgroundStatus {
+    #[default]
+    Available,
+    /// No longer part of the game.
+    Removed,
+    /// Still in the game, but no longer displays correctly.
+    Broken,
+}
+
+impl BackgroundStatus {
+    pub fn is_available(&self) -> bool {
+        *self == BackgroundStatus::Available
+    }
+}
+
+/// A background that is applied but was removed from the game or broke.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct RetiredBackground {
+    pub id: String,
+    pub name: String,
+    pub status: BackgroundStatus,
+    pub season: Option<u32>,
+    pub replacement: Option<String>,
+}
+
+/// Announces the newest catalog. Published alongside the catalog file.
+#[derive(Deserialize)]
+struct Manifest {
+    version: u32,
+    url: String,
+    /// Signature of the catalog file, as written by `tauri signer sign`.
+    signature: String,
+}
+
+pub enum UpdateStatus {
+    Updated(u32),
+    UpToDate(u32),
+    Disabled,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Catalog {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub version: u32,
+    /// Vocabulary of the tags backgrounds can use.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub backgrounds: Vec<Background>,
 }
 
-const BACKGROUNDS: &[Background] = &[
-    Background {
-        id: "0x0800000000000864",
-        image: "overwatch_league.jpg",
-        name: "Overwatch League",
-        description: "Overwatch League Promo",
-        tags: &["Overwatch League", "Silent"],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000000E77",
-        image: "heroes.jpg",
-        name: "Heroes",
-        description: "2022 Alpha Test",
-        tags: &["Animated"],
-        new: false,
-    },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000000D6C",
-    //     image: "sojourn.jpg",
-    //     name: "Sojourn",
-    //     description: "2022 PvP Beta",
-    //     tags: &[],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000000EF3",
-    //     image: "kiriko.jpg",
-    //     name: "Kiriko",
-    //     description: "Kiriko Release (Season 1)",
-    //     tags: vec!["Song: BOW by MFS"],
-    // },
-    Background {
-        id: "0x0800000000000EFB",
-        image: "zero_hour.jpg",
-        name: "Zero Hour",
-        description: "Overwatch 2 Launch",
-        tags: &[],
-        new: false,
-    },
-    // NOTE: Broken after Season 13 mid-season patch
-    // Background {
-    //     id: "0x0800000000000F11",
-    //     image: "cyber_demon_genji_green.jpg",
-    //     name: "Cyber Demon Genji (Green)",
-    //     description: "Season 1 Mythic Skin",
-    //     tags: &["Animated"],
-    //     new: false
-    // },
-    // Background {
-    //     id: "0x0800000000000EFA",
-    //     image: "cyber_demon_genji_red.jpg",
-    //     name: "Cyber Demon Genji (Red)",
-    //     description: "Season 1 Mythic Skin",
-    //     tags: &["Animated"],
-    //     new: false
-    // },
-    // Background {
-    //     id: "0x0800000000000F12",
-    //     image: "cyber_demon_genji_pink.jpg",
-    //     name: "Cyber Demon Genji (Pink)",
-    //     description: "Season 1 Mythic Skin",
-    //     tags: &["Animated"],
-    //     new: false
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000000F8F",
-    //     image: "ramattra.jpg",
-    //     name: "Ramattra",
-    //     description: "Ramattra Release (Season 2)",
-    //     tags: vec![],
-    // },
-    // Background {
-    //     id: "0x0800000000000f31",
-    //     image: "shambali_monastery.jpg",
-    //     name: "Shambali Monastery",
-    //     description: "Shambali Monastery Release (Season 2)",
-    //     tags: vec!["No Hero"],
-    // },
-    // Background {
-    //     id: "0x0800000000000F4A",
-    //     image: "zeus_junker_queen.jpg",
-    //     name: "Zeus Junker Queen",
-    //     description: "Season 2 Mythic Skin",
-    //     tags: vec![],
-    // },
-    Background {
-        id: "0x0800000000000D77",
-        image: "winter_wonderland_2022.jpg",
-        name: "Festive Junkrat & Roadhog",
-        description: "Winter Wonderland 2022 (Season 2)",
-        tags: &[],
-        new: false,
-    },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000000DAD",
-    //     image: "mei_lunar_2023.jpg",
-    //     name: "Festive Mei",
-    //     description: "Lunar New Year 2023 (Season 2)",
-    //     tags: &[],
-    //     new: false,
-    // },
-    // Background {
-    //     id: "0x0800000000000710",
-    //     image: "dva_lunar_2023.jpg",
-    //     name: "Palanquin D.Va",
-    //     description: "Lunar New Year 2023 (Season 2)",
-    //     tags: &[],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x080000000000103D",
-    //     image: "antarctic_peninsula.jpg",
-    //     name: "Antarctic Peninsula",
-    //     description: "Antarctic Peninsula Release (Season 3)",
-    //     tags: vec!["No Hero"],
-    // },
-    // Background {
-    //     id: "0x0800000000001003",
-    //     image: "kiriko_amaterasu.jpg",
-    //     name: "Amaterasu Kiriko",
-    //     description: "Season 3 Mythic Skin",
-    //     tags: vec![],
-    // },
-    // Background {
-    //     id: "0x0800000000001045",
-    //     image: "kiriko_amaterasu_art.jpg",
-    //     name: "Amaterasu Kiriko Variations",
-    //     description: "Season 3 Mythic Skin",
-    //     tags: vec!["Art"],
-    // },
-    Background {
-        id: "0x0800000000000B6B",
-        image: "cupid_hanzo.jpg",
-        name: "Cupid Hanzo",
-        description: "Ultimate Valentine 2023 (Season 3)",
-        tags: &["Animated"],
-        new: false,
-    },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000000EED",
-    //     image: "one_punch_man_doomfist.jpg",
-    //     name: "Saitama Doomfist",
-    //     description: "One Punch Man Event (Season 3)",
-    //     tags: vec!["Collaboration", "No Music"],
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000001060",
-    //     image: "lifeweaver.jpg",
-    //     name: "Lifeweaver",
-    //     description: "Lifeweaver Release (Season 4)",
-    //     tags: vec![],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001032",
-    //     image: "galactic_emperor_sigma.jpg",
-    //     name: "Galactic Emperor Sigma",
-    //     description: "Sigma Mythic Skin (Season 4)",
-    //     tags: &["Mythic Skin"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000001132",
-    //     image: "starwatch_art.jpg",
-    //     name: "Starwatch",
-    //     description: "Starwatch Event (Season 4)",
-    //     tags: vec!["Art"],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001026",
-    //     image: "zero_hour_owl.jpg",
-    //     name: "Overwatch League Zero Hour",
-    //     description: "Overwatch League Promo (Season 4)",
-    //     tags: &["Overwatch League"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000001133",
-    //     image: "questwatch_art.jpg",
-    //     name: "Questwatch",
-    //     description: "Questwatch Event (Season 5)",
-    //     tags: vec!["Art"],
-    // },
-    Background {
-        id: "0x0800000000000BCE",
-        image: "summer_games_2023.jpg",
-        name: "Tropical Doomfist",
-        description: "Summer Games 2023 (Season 5)",
-        tags: &[],
-        new: false,
-    },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x080000000000112B",
-    //     image: "illari.jpg",
-    //     name: "Illari",
-    //     description: "Illari Release (Season 6)",
-    //     tags: vec![],
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x08000000000010F2",
-    //     image: "ana_a_7000_wargod.jpg",
-    //     name: "A-7000 Wargod Ana",
-    //     description: "Season 6 Mythic Skin",
-    //     tags: vec![],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001104",
-    //     image: "gothenburg_mothership.jpg",
-    //     name: "Gothenburg Mothership",
-    //     description: "Invasion PvE Event (Season 6)",
-    //     tags: &["No Music"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000001150",
-    //     image: "onryo_hanzo.jpg",
-    //     name: "Onryo Hanzo",
-    //     description: "Season 7 Mythic Skin",
-    //     tags: vec![],
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x08000000000010AC",
-    //     image: "onryo_hanzo_art.jpg",
-    //     name: "Onryo Hanzo Art",
-    //     description: "Season 7 Mythic Skin",
-    //     tags: vec!["Art"],
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x080000000000115C",
-    //     image: "lilith_moira.jpg",
-    //     name: "Lilith Moira",
-    //     description: "Halloween Terror 2023 (Season 7)",
-    //     tags: vec![],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000000817",
-    //     image: "overwatch_world_cup.jpg",
-    //     name: "Overwatch World Cup",
-    //     description: "Overwatch World Cup 2023 (Season 7)",
-    //     tags: &["World Cup"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000001173",
-    //     image: "le_sserafim_collab.jpg",
-    //     name: "LE SSERAFIM",
-    //     description: "LE SSERAFIM Event (Season 7)",
-    //     tags: &["Collaboration", "Song: Perfect Night by LE SSERAFIM"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x080000000000118A",
-    //     image: "mauga.jpg",
-    //     name: "Mauga",
-    //     description: "Mauga Release (Season 8)",
-    //     tags: vec![],
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x0800000000001197",
-    //     image: "grand_beast_orisa.jpg",
-    //     name: "Grand Beast Orisa",
-    //     description: "Season 8 Mythic Skin",
-    //     tags: vec![],
-    // },
-    // NOTE: Removed in Season 9 rebase
-    // Background {
-    //     id: "0x08000000000011B4",
-    //     image: "winter_wonderland_2023.jpg",
-    //     name: "Festive Mercy, B.O.B. and Genji",
-    //     description: "Winter Wonderland 2023 (Season 8)",
-    //     tags: vec!["Art"],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001200",
-    //     image: "ancient_caller_moira.jpg",
-    //     name: "Ancient Caller Moira",
-    //     description: "Moira Mythic Skin (Season 9)",
-    //     tags: &["Mythic Skin", "Art"],
-    //     new: false,
-    // },
-    Background {
-        id: "0x0800000000001202",
-        image: "cowboby_bebop.jpg",
-        name: "Cowboy Bebop",
-        description: "Cowboy Bebop Event (Season 9)",
-        tags: &["Collaboration", "Art"],
-        new: false,
-    },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x080000000000121A",
-    //     image: "venture.jpg",
-    //     name: "Venture",
-    //     description: "Venture Release (Season 10)",
-    //     tags: &["Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x080000000000121E",
-    //     image: "mirrorwatch.jpg",
-    //     name: "Mirrorwatch",
-    //     description: "Mirrorwatch Event (Season 10)",
-    //     tags: &[],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000010AC",
-    //     image: "mirrorwatch_art.jpg",
-    //     name: "Mirrorwatch Art",
-    //     description: "Mirrorwatch Event (Season 10)",
-    //     tags: &["Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001219",
-    //     image: "porsche.jpg",
-    //     name: "Porsche D.Va",
-    //     description: "Porsche Event (Season 10)",
-    //     tags: &["Collaboration", "Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 12 (wasn't released on OverBuddy)
-    // Background {
-    //     id: "0x080000000000123F",
-    //     image: "calamity_empress_ashe.jpg",
-    //     name: "Calamity Empress Ashe",
-    //     description: "Season 11 Mythic Skin",
-    //     tags: &["Art"],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000008BA",
-    //     image: "rose_gold_mercy.jpg",
-    //     name: "Rose Gold Mercy",
-    //     description: "Breast Cancer Charity Event (Season 11)",
-    //     tags: &["Charity"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 12 (wasn't released on OverBuddy)
-    // Background {
-    //     id: "0x0800000000001243",
-    //     image: "transformers.jpg",
-    //     name: "Optimus Prime Reinhardt",
-    //     description: "Transformers Event (Season 11)",
-    //     tags: &["Collaboration", "Art"],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001249",
-    //     image: "summer_games_2024.jpg",
-    //     name: "Lifeguard Lifeweaver & Kiriko",
-    //     description: "Summer Games 2024 (Season 11)",
-    //     tags: &[],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x080000000000124A",
-    //     image: "reinhardt_bound_demon_mythic.jpg",
-    //     name: "Reinhardt Bound Demon",
-    //     description: "Reinhardt Mythic Weapon (Season 11)",
-    //     tags: &["Mythic Weapon"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 13
-    // Background {
-    //     id: "0x0800000000001276",
-    //     image: "juno.jpg",
-    //     name: "Juno",
-    //     description: "Juno Release (Season 12)",
-    //     tags: &["Art"],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001277",
-    //     image: "anubis_reaper.jpg",
-    //     name: "Anubis Reaper",
-    //     description: "Reaper Mythic Skin (Season 12)",
-    //     tags: &["Mythic Skin", "Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001293",
-    //     image: "world_of_warcraft.jpg",
-    //     name: "Sylvanas Widowmaker",
-    //     description: "World of Warcraft Event (Season 12)",
-    //     tags: &["Collaboration", "Art"],
-    //     new: false,
-    // },
-    Background {
-        id: "0x0800000000001289",
-        image: "ana_midnight_sun_mythic.jpg",
-        name: "Midnight Sun",
-        description: "Ana Mythic Weapon (Season 12)",
-        tags: &["Mythic Weapon"],
-        new: false,
-    },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000012A5",
-    //     image: "spellbinder_widowmaker.jpg",
-    //     name: "Spellbinder Widowmaker",
-    //     description: "Widowmaker Mythic Skin (Season 13)",
-    //     tags: &["Mythic Skin", "Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000012A3",
-    //     image: "owcs_finals_2024.jpg",
-    //     name: "OWCS Finals 2024",
-    //     description: "Overwatch Championship Series Finals 2024",
-    //     tags: &["Overwatch Champions Series", "Art", "Crowd Sounds"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 14
-    // Background {
-    //     id: "0x08000000000012A4",
-    //     image: "my_hero_academia.jpg",
-    //     name: "My Hero Academia",
-    //     description: "My Hero Academia Event (Season 13)",
-    //     tags: &["Collaboration", "Art"],
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000012C2",
-    //     image: "overwatch_classic.jpg",
-    //     name: "Overwatch Classic",
-    //     description: "Overwatch Classic Event (Season 13)",
-    //     tags: &["Overwatch 1.0 Patch", "Original Theme Song"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000012BE",
-    //     image: "soldier_76_deliverance.jpg",
-    //     name: "Soldier: 76 Deliverance",
-    //     description: "Soldier: 76 Mythic Weapon (Season 13)",
-    //     tags: &["Mythic Weapon"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000012C6",
-    //     image: "hazard.jpg",
-    //     name: "Hazard",
-    //     description: "Hazard Release (Season 14)",
-    //     tags: &["Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 15
-    // Background {
-    //     id: "0x08000000000012C7",
-    //     image: "thor_reinhardt.jpg",
-    //     name: "Thor Reinhardt",
-    //     description: "Season 14 Mythic Skin",
-    //     tags: &["Animated Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000012EA",
-    //     image: "welcome_home_china.jpg",
-    //     name: "Welcome Home, China",
-    //     description: "Chinese (NetEase) beta re-release of Overwatch (Season 14)",
-    //     tags: &["Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000012CA",
-    //     image: "avatar_the_last_airbender.jpg",
-    //     name: "Avatar: The Last Airbender",
-    //     description: "Avatar: The Last Airbender Event (Season 14)",
-    //     tags: &["Collaboration", "Animated Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x08000000000012CB",
-    //     image: "ashe_lead_rose.jpg",
-    //     name: "Ashe Lead Rose",
-    //     description: "Ashe Mythic Weapon (Season 14)",
-    //     tags: &["Mythic Weapon"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001300",
-    //     image: "celestial_skins.jpg",
-    //     name: "Celestial Skins",
-    //     description: "Celestial Skin Bundle (Season 14)",
-    //     tags: &[],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001316",
-    //     image: "honor_and_glory.jpg",
-    //     name: "Honor and Glory",
-    //     description: "Season 15 Release Art",
-    //     tags: &["Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001317",
-    //     image: "party_in_china.jpg",
-    //     name: "Party in China",
-    //     description: "Chinese (NetEase) re-release of Overwatch (Season 15)",
-    //     tags: &["Animated Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001331",
-    //     image: "le_sserafim_homecoming.jpg",
-    //     name: "LE SSERAFIM",
-    //     description: "LE SSERAFIM Homecoming Event (Season 15)",
-    //     tags: &[
-    //         "Collaboration",
-    //         "Art",
-    //         "Song: So Cynical (Badum) by LE SSERAFIM",
-    //     ],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x080000000000132D",
-    //     image: "widowmaker_dame_chance.jpg",
-    //     name: "Widowmaker Dame Chance",
-    //     description: "Widowmaker Mythic Weapon (Season 15)",
-    //     tags: &["Mythic Weapon"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x080000000000132F",
-    //     image: "owcs_juno.jpg",
-    //     name: "Ember Juno",
-    //     description: "2025 Champions Clash Hangzhou Crowdfunding Skin",
-    //     tags: &["Overwatch Champions Series"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001346",
-    //     image: "stadium.jpg",
-    //     name: "Stadium",
-    //     description: "Stadium Release (Season 16)",
-    //     tags: &["Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001347",
-    //     image: "freja.jpg",
-    //     name: "Freja",
-    //     description: "Freja Release (Season 16)",
-    //     tags: &["Animated Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001348",
-    //     image: "gundam_wing.jpg",
-    //     name: "Gundam Wing",
-    //     description: "Gundam Wing Event (Season 16)",
-    //     tags: &["Collaboration", "Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001353",
-    //     image: "street_fighter_6.jpg",
-    //     name: "Street Fighter 6",
-    //     description: "Street Fighter 6 Event (Season 16)",
-    //     tags: &["Collaboration", "Animated Art"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x080000000000134A",
-    //     image: "mercy_merciful_magitech.jpg",
-    //     name: "Mercy Merciful Magitech",
-    //     description: "Mercy Mythic Weapon (Season 16)",
-    //     tags: &["Mythic Weapon"],
-    //     new: false,
-    // },
-    // NOTE: Removed in Season 17 rebase
-    // Background {
-    //     id: "0x0800000000001345",
-    //     image: "heart_of_hope_juno.jpg",
-    //     name: "Heart of Hope Juno",
-    //     description: "Juno Mythic Skin (Season 16)",
-    //     tags: &["Mythic Skin", "Animated Art"],
-    //     new: false,
-    // },
-    Background {
-        id: "0x0800000000001378",
-        image: "reaper_steel_death.jpg",
-        name: "Steel Death",
-        description: "Reaper Mythic Weapon (Season 17)",
-        tags: &["Mythic Weapon", "Art"],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000001379",
-        image: "gi_joe.jpg",
-        name: "G.I. Joe",
-        description: "G.I. Joe Event (Season 17)",
-        tags: &["Collaboration", "Art"],
-        new: false,
-    },
-    Background {
-        id: "0x080000000000138A",
-        image: "radiant_angel.jpg",
-        name: "Radiant Angel Mercy",
-        description: "OWCS 2025 Midseason Championship Crowdfunding Skin",
-        tags: &["Overwatch Champions Series"],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000001389",
-        image: "nerf.jpg",
-        name: "NERF",
-        description: "NERF Collaboration Event (Season 17)",
-        tags: &["Collaboration", "Art"],
-        new: false,
-    },
-    Background {
-        id: "0x080000000000139D",
-        image: "wuyang.jpg",
-        name: "Wuyang",
-        description: "Wuyang Release (Season 18)",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x080000000000139F",
-        image: "ultraviolet_sentinel_blazing_sunsetter.jpg",
-        name: "Season 18 Mythics",
-        description: "Ultraviolet Sentinel and Blazing Sunsetter (Season 18)",
-        tags: &["Mythic Skin", "Mythic Weapon"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013A2",
-        image: "ultraviolet_sentinel_blazing_sunsetter_art.jpg",
-        name: "Season 18 Mythic Art",
-        description: "Ultraviolet Sentinel and Blazing Sunsetter (Season 18)",
-        tags: &["Mythic Skin", "Mythic Weapon", "Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013AC",
-        image: "phantom_thieves.jpg",
-        name: "Phantom Thieves",
-        description: "Persona 5 Event (Season 18)",
-        tags: &["Collaboration", "Art", "Song: Phantom by ATLUS Sound Team"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013AD",
-        image: "anniversary_2025.jpg",
-        name: "Anniversary",
-        description: "Overwatch 2 Anniversary Celebration (Season 18)",
-        tags: &["Art", "Song: Overture by Sam Cardon"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013B7",
-        image: "divine_druid_spirit_keeper.jpg",
-        name: "Season 19 Mythics",
-        description: "Divine Druid and Spirit Keeper (Season 19)",
-        tags: &["Mythic Skin", "Mythic Weapon"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013C4",
-        image: "stadium_gadgets.jpg",
-        name: "Stadium Gadgets",
-        description: "Gadgets Launch in Stadium (Season 19)",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013C5",
-        image: "haunted_masquerade.jpg",
-        name: "Haunted Masquerade",
-        description: "Halloween Event (Season 19)",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013C2",
-        image: "one_punch_man.jpg",
-        name: "One-Punch Man",
-        description: "One-Punch Man Event (Season 19)",
-        tags: &["Collaboration", "Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013DC",
-        image: "cyber_fuel.jpg",
-        name: "Cyber Fuel Junkrat",
-        description: "Junkrat Mythic Skin (Season 19)",
-        tags: &["Mythic Skin"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013CA",
-        image: "scarlet_flare.jpg",
-        name: "Scarlet Flare",
-        description: "OWCS 2025 Word Finals Crowdfunding Bundle",
-        tags: &["Overwatch Champions Series", "Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000013E9",
-        image: "vendetta.jpg",
-        name: "Vendetta",
-        description: "Vendetta Release (Season 20)",
-        tags: &[],
-        new: true,
-    },
-    Background {
-        id: "0x08000000000013F1",
-        image: "divine_desperado_capsule_cannon.jpg",
-        name: "Season 20 Mythics",
-        description: "Divine Desperado and Capsule Cannon (Season 20)",
-        tags: &["Mythic Skin", "Mythic Weapon"],
-        new: true,
-    },
-    Background {
-        id: "0x08000000000013F0",
-        image: "winter_wonderland_2025.jpg",
-        name: "Winter Wonderland",
-        description: "Winter Wonderland 2025 (Season 20)",
-        tags: &["Art"],
-        new: true,
-    },
-    Background {
-        id: "0x0800000000001407",
-        image: "magma_titan.jpg",
-        name: "Magma Titan Doomfist",
-        description: "Doomfist Mythic Skin (Season 20)",
-        tags: &["Mythic Skin"],
-        new: true,
-    },
-];
-
-pub fn get_backgrounds() -> &'static [Background] {
-    BACKGROUNDS
+static CATALOG: LazyLock<RwLock<Arc<Catalog>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(bundled_catalog())));
+static LAST_UPDATE_CHECK: Mutex<Option<Instant>> = Mutex::new(None);
+
+impl Catalog {
+    /// Merge another catalog on top of this one. Backgrounds with a matching id are
+    /// replaced, new ones are appended.
+    fn merge(&mut self, other: Catalog) {
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        for background in other.backgrounds {
+            match self
+                .backgrounds
+                .iter_mut()
+                .find(|existing| existing.id == background.id)
+            {
+                Some(existing) => *existing = background,
+                None => self.backgrounds.push(background),
+            }
+        }
+    }
 }
 
-pub fn find_background_by_id(id: &str) -> Option<&'static Background> {
-    BACKGROUNDS.iter().find(|&bg| bg.id == id)
+/// Parse and validate a catalog. Tags must be declared in its own vocabulary or in
+/// `known_tags`, the vocabulary of the catalog it will be merged into.
+fn parse_catalog(contents: &str, path: &Path, known_tags: &[String]) -> Result<Catalog, Error> {
+    let invalid = |message: String| {
+        Error::new(
+            ErrorCode::Catalog,
+            format!("Background catalog [[{}]] is invalid", path.display()),
+        )
+        .with_path(path)
+        .with_detail(message)
+    };
+
+    let catalog: Catalog =
+        serde_json::from_str(contents).map_err(|err| invalid(err.to_string()))?;
+
+    if catalog.schema_version == 0 || catalog.schema_version > SCHEMA_VERSION {
+        return Err(invalid(format!(
+            "Unsupported schema version {}, expected at most {}",
+            catalog.schema_version, SCHEMA_VERSION
+        ))
+        .with_hint("Update OverBuddy to read this catalog."));
+    }
+
+    let mut ids = HashSet::new();
+    for background in &catalog.backgrounds {
+        if !is_valid_id(&background.id) {
+            return Err(invalid(format!(
+                "Background id [[{}]] is not a lobbyMap id like 0x0800000000000E77",
+                background.id
+            )));
+        }
+        if !ids.insert(background.id.as_str()) {
+            return Err(invalid(format!(
+                "Background id [[{}]] appears more than once",
+                background.id
+            )));
+        }
+        if background.name.trim().is_empty() || background.image.trim().is_empty() {
+            return Err(invalid(format!(
+                "Background [[{}]] is missing a name or image",
+                background.id
+            )));
+        }
+        if let Some(replacement) = &background.replacement
+            && (background.status.is_available() || !is_valid_id(replacement))
+        {
+            return Err(invalid(format!(
+                "Background [[{}]] has an invalid replacement [[{}]]",
+                background.id, replacement
+            ))
+            .with_hint("Only removed or broken backgrounds can have a replacement."));
+        }
+        if let Some(date) = &background.release_date
+            && !is_valid_date(date)
+        {
+            return Err(invalid(format!(
+                "Background [[{}]] has an invalid release date [[{}]], expected YYYY-MM-DD",
+                background.id, date
+            )));
+        }
+        if background.season == Some(0) || background.retired_season == Some(0) {
+            return Err(invalid(format!(
+                "Background [[{}]] has an invalid season [[0]]",
+                background.id
+            )));
+        }
+        if let Some(tag) = background
+            .tags
+            .iter()
+            .find(|tag| !catalog.tags.contains(tag) && !known_tags.contains(tag))
+        {
+            return Err(invalid(format!(
+                "Background [[{}]] uses unknown tag [[{}]]",
+                background.id, tag
+            ))
+            .with_hint("Add the tag to the catalog's tags list first."));
+        }
+    }
+
+    Ok(catalog)
+}
+
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    let number = |part: &str, len: usize| {
+        (part.len() == len && part.chars().all(|c| c.is_ascii_digit()))
+            .then(|| part.parse::<u32>().ok())
+            .flatten()
+    };
+
+    number(year, 4).is_some()
+        && number(month, 2).is_some_and(|month| (1..=12).contains(&month))
+        && number(day, 2).is_some_and(|day| (1..=31).contains(&day))
+}
+
+/// Parse a background id the way users write it and return its canonical form.
+///
+/// Accepts the canonical `0x0800000000000E77`, the short `E77` listed by DataTool, with or
+/// without `0x` and in any case. Only an id of 16 digits is read as a full id, anything
+/// shorter is the index, so `0x08A` and `08A` are both `0x080000000000008A`.
+pub fn normalize_id(input: &str) -> Result<String, Error> {
+    let invalid = || {
+        Error::new(
+            ErrorCode::InvalidArgument,
+            format!("[[{}]] is not a valid background id", input),
+        )
+        .with_hint("Background ids are hexadecimal, like E77 or 0x0800000000000E77.")
+    };
+
+    let input = input.trim();
+    let hex = match input.get(..2) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("0x") => &input[2..],
+        _ => input,
+    };
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    // Full ids start with the lobbyMap type, short ids only contain the index
+    let hex = hex.to_ascii_uppercase();
+    let index = if hex.len() == 16 {
+        hex.strip_prefix("08").ok_or_else(invalid)?
+    } else {
+        &hex
+    };
+    let index = index.trim_start_matches('0');
+    if index.is_empty() || index.len() > 14 {
+        return Err(invalid());
+    }
+
+    Ok(format!("0x08{:0>14}", index))
+}
+
+/// Check that an id has the canonical form of a lobbyMap id, e.g. `0x0800000000000E77`.
+pub fn is_valid_id(id: &str) -> bool {
+    id.strip_prefix("0x08").is_some_and(|rest| {
+        rest.len() == 14
+            && rest
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, 'A'..='F'))
+    })
+}
+
+fn bundled_catalog() -> Catalog {
+    parse_catalog(BUNDLED_CATALOG, Path::new("catalog/backgrounds.json"), &[])
+        .expect("Bundled background catalog is invalid")
+}
+
+fn read_settings(data_dir: &Path) -> CatalogConfig {
+    config::read_config_from(data_dir)
+        .map(|config| config.catalog)
+        .unwrap_or_default()
+}
+
+fn verify_signature(contents: &[u8], signature: &str, public_key: &str) -> Result<(), Error> {
+    let decode = |value: &str| {
+        base64::engine::general_purpose::STANDARD
+            .decode(value.trim())
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    };
+    let invalid = |message: &str| {
+        Error::new(ErrorCode::Catalog, message.to_string())
+            .with_hint("The catalog may have been tampered with, the current one stays in use.")
+    };
+
+    let public_key = decode(public_key)
+        .and_then(|key| minisign_verify::PublicKey::decode(&key).ok())
+        .ok_or_else(|| invalid("Failed to decode the background catalog public key"))?;
+    let signature = decode(signature)
+        .and_then(|signature| minisign_verify::Signature::decode(&signature).ok())
+        .ok_or_else(|| invalid("Failed to decode the background catalog signature"))?;
+
+    public_key
+        .verify(contents, &signature, false)
+        .map_err(|_| invalid("Background catalog signature does not match"))
+}
+
+/// Read the catalog from the last update, if its signature is still valid.
+fn read_cache(data_dir: &Path, settings: &CatalogConfig) -> Option<Catalog> {
+    let path = data_dir.join(CACHE_FILE);
+    let contents = fs::read_to_string(&path).ok()?;
+    let signature = fs::read_to_string(data_dir.join(CACHE_SIGNATURE_FILE)).ok()?;
+
+    verify_signature(contents.as_bytes(), &signature, public_key(settings)).ok()?;
+    parse_catalog(&contents, &path, &[]).ok()
+}
+
+/// The bundled catalog, or the cached one if it is newer.
+fn base_catalog(data_dir: &Path, settings: &CatalogConfig) -> Catalog {
+    let bundled = bundled_catalog();
+    match read_cache(data_dir, settings) {
+        Some(cached) if cached.version > bundled.version => cached,
+        _ => bundled,
+    }
+}
+
+fn public_key(settings: &CatalogConfig) -> &str {
+    settings.public_key.as_deref().unwrap_or(DEFAULT_PUBLIC_KEY)
+}
+
+/// Load the newest catalog (cached or bundled) and merge the override file from the data
+/// directory on top. If the override is invalid, it is skipped and the error is returned.
+pub fn load(data_dir: &Path) -> Result<(), Error> {
+    let mut catalog = base_catalog(data_dir, &read_settings(data_dir));
+
+    let override_path = data_dir.join(OVERRIDE_FILE);
+    let result = match fs::read_to_string(&override_path) {
+        Ok(contents) => {
+            parse_catalog(&contents, &override_path, &catalog.tags).map(|user_catalog| {
+                catalog.merge(user_catalog);
+            })
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(Error::new(
+            ErrorCode::Catalog,
+            format!(
+                "Failed to read background catalog [[{}]]",
+                override_path.display()
+            ),
+        )
+        .with_path(&override_path)
+        .with_io(&err)),
+    };
+
+    *CATALOG.write().unwrap() = Arc::new(catalog);
+    result
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, Error> {
+    let network_error = |err: reqwest::Error| {
+        Error::new(
+            ErrorCode::Network,
+            format!("Failed to download [[{}]]", url),
+        )
+        .with_detail(err.to_string())
+        .with_hint("Check your internet connection, the current backgrounds stay in use.")
+    };
+
+    let too_large = || {
+        Error::new(
+            ErrorCode::Network,
+            format!(
+                "Download of [[{}]] is larger than {} MB",
+                url,
+                MAX_DOWNLOAD_SIZE / 1024 / 1024
+            ),
+        )
+        .with_hint("The current backgrounds stay in use.")
+    };
+
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(network_error)?;
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_DOWNLOAD_SIZE as u64)
+    {
+        return Err(too_large());
+    }
+
+    // The announced length may be missing or wrong, so count while reading
+    let mut contents = vec![];
+    while let Some(chunk) = response.chunk().await.map_err(network_error)? {
+        if contents.len() + chunk.len() > MAX_DOWNLOAD_SIZE {
+            return Err(too_large());
+        }
+        contents.extend_from_slice(&chunk);
+    }
+
+    Ok(contents)
+}
+
+/// Download the catalog announced by the manifest if it is newer than the current one,
+/// verify its signature and store it in the data directory.
+///
+/// Nothing is downloaded until a manifest URL is configured.
+pub async fn update(data_dir: &Path) -> Result<UpdateStatus, Error> {
+    let settings = read_settings(data_dir);
+    let Some(manifest_url) = settings
+        .manifest_url
+        .as_deref()
+        .filter(|_| settings.updates_enabled)
+    else {
+        return Ok(UpdateStatus::Disabled);
+    };
+    let current_version = base_catalog(data_dir, &settings).version;
+
+    let client = reqwest::Client::builder()
+        .timeout(UPDATE_TIMEOUT)
+        .build()
+        .map_err(|err| Error::new(ErrorCode::Network, err.to_string()))?;
+
+    let manifest: Manifest =
+        serde_json::from_slice(&fetch(&client, manifest_url).await?).map_err(|err| {
+            Error::new(
+                ErrorCode::Catalog,
+                format!(
+                    "Background catalog manifest [[{}]] is invalid",
+                    manifest_url
+                ),
+            )
+            .with_detail(err.to_string())
+        })?;
+    if manifest.version <= current_version {
+        return Ok(UpdateStatus::UpToDate(current_version));
+    }
+
+    let contents = fetch(&client, &manifest.url).await?;
+    verify_signature(&contents, &manifest.signature, public_key(&settings))?;
+    let contents = String::from_utf8(contents).map_err(|err| {
+        Error::new(
+            ErrorCode::Catalog,
+            format!("Background catalog [[{}]] is invalid", manifest.url),
+        )
+        .with_detail(err.to_string())
+    })?;
+    let catalog = parse_catalog(&contents, Path::new(&manifest.url), &[])?;
+    if catalog.version != manifest.version {
+        return Err(Error::new(
+            ErrorCode::Catalog,
+            format!(
+                "Background catalog [[{}]] has version {}, but the manifest announced {}",
+                manifest.url, catalog.version, manifest.version
+            ),
+        ));
+    }
+
+    // Write the signature first so that an interrupted write fails verification
+    for (file, contents) in [
+        (CACHE_SIGNATURE_FILE, manifest.signature.as_str()),
+        (CACHE_FILE, contents.as_str()),
+    ] {
+        let path = data_dir.join(file);
+        fs::write(&path, contents).map_err(|err| {
+            Error::new(
+                ErrorCode::Io,
+                format!(
+                    "Failed to save background catalog to [[{}]]",
+                    path.display()
+                ),
+            )
+            .with_path(&path)
+            .with_io(&err)
+        })?;
+    }
+
+    Ok(UpdateStatus::Updated(catalog.version))
+}
+
+/// Check for a catalog update unless one was checked recently. Failures are ignored,
+/// as the cached or bundled catalog keeps working offline.
+pub async fn update_if_due(data_dir: &Path) {
+    {
+        let mut last_check = LAST_UPDATE_CHECK.lock().unwrap();
+        if last_check.is_some_and(|last_check| last_check.elapsed() < UPDATE_INTERVAL) {
+            return;
+        }
+        *last_check = Some(Instant::now());
+    }
+
+    update(data_dir).await.ok();
+}
+
+pub fn get_catalog() -> Arc<Catalog> {
+    CATALOG.read().unwrap().clone()
+}
+
+/// Get all backgrounds that can be applied.
+pub fn get_backgrounds() -> Vec<Background> {
+    get_catalog()
+        .backgrounds
+        .iter()
+        .filter(|background| background.status.is_available())
+        .cloned()
+        .collect()
+}
+
+/// Find a background that can be applied, ignoring the case of the id.
+pub fn find_background_by_id(id: &str) -> Option<Background> {
+    get_catalog()
+        .backgrounds
+        .iter()
+        .find(|background| {
+            background.id.eq_ignore_ascii_case(id) && background.status.is_available()
+        })
+        .cloned()
+}
+
+/// Find a background that was removed or broke. The replacement is only suggested if it
+/// can still be applied.
+pub fn find_retired_background(id: &str) -> Option<RetiredBackground> {
+    let catalog = get_catalog();
+    let background = catalog.backgrounds.iter().find(|background| {
+        background.id.eq_ignore_ascii_case(id) && !background.status.is_available()
+    })?;
+
+    Some(RetiredBackground {
+        id: background.id.clone(),
+        name: background.name.clone(),
+        status: background.status,
+        season: background.retired_season,
+        replacement: background
+            .replacement
+            .clone()
+            .filter(|replacement| find_background_by_id(replacement).is_some()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::thread;
+
+    #[test]
+    fn normalizes_full_and_short_ids() {
+        for input in [
+            "0x0800000000000E77",
+            "0x0800000000000e77",
+            "0X0800000000000E77",
+            "0800000000000E77",
+            " 0x0800000000000E77 ",
+            "0xE77",
+            "E77",
+            "e77",
+            "0x0E77",
+            "00000E77",
+        ] {
+            assert_eq!(
+                normalize_id(input).unwrap(),
+                "0x0800000000000E77",
+                "{}",
+                input
+            );
+        }
+
+        // Short ids are never read as full ids, even when they start with 08
+        assert_eq!(normalize_id("0x08A").unwrap(), "0x080000000000008A");
+        assert_eq!(normalize_id("08A").unwrap(), "0x080000000000008A");
+        assert_eq!(normalize_id("0x0800").unwrap(), "0x0800000000000800");
+        assert_eq!(
+            normalize_id("FFFFFFFFFFFFFF").unwrap(),
+            "0x08FFFFFFFFFFFFFF"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_ids() {
+        for input in [
+            "",
+            "  ",
+            "0x",
+            "0",
+            "0x0",
+            "0x0800000000000000",
+            "0x0900000000000E77",
+            "0x1FFFFFFFFFFFFFFF",
+            "100000000000000",
+            "0x080000000000000E77",
+            "E77G",
+            "0xE 77",
+            "-E77",
+            "0x0x0800000000000E77",
+        ] {
+            let error = normalize_id(input).err().unwrap();
+            assert_eq!(error.code, ErrorCode::InvalidArgument, "{}", input);
+        }
+    }
+
+    /// Catalog signed with the test key below.
+    const CATALOG: &str = r#"{"schema_version":1,"version":100,"tags":[],"backgrounds":[{"id":"0x0800000000000E77","image":"heroes.jpg","name":"Heroes","description":"2022 Alpha Test"}]}"#;
+    const PUBLIC_KEY: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IG1pbmlzaWduIHB1YmxpYyBrZXk6IHRlc3QKUldUdDBwMEpnRHFZd3Vpc29wbFgwREM3MzRzY2d1ekhOcUo1NkRCRE4wclFJWDJ3dGl0VEhTSEYK";
+    const SIGNATURE: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IHNpZ25hdHVyZSBmcm9tIG1pbmlzaWduIHNlY3JldCBrZXkKUlVUdDBwMEpnRHFZd3BqUXRXbEo5Y01EY0hZbi9PaFM5V2lmc2Q0VXVNbXpwdWVhR0xIRVZSK2tIVFRTUXZRTElXNnVISVVGSlR0OExZMyt3dzNpWmQxN1dKLzM4bjV4QXdzPQp0cnVzdGVkIGNvbW1lbnQ6IHRlc3QgY2F0YWxvZwozRi9sMk1XVlJKbGwzK01QdE1PclBaKzl2bS93UzlwSEQ1TjgwbHgxR2huVlBBSHBjOGR0ME8yc2UwcnAyNzY4bXAyV29jYXQ0TTlyTFIyYU40N1dDUT09Cg==";
+
+    /// Serve fixed responses by path on a local port. `routes` gets the base URL of the
+    /// server, so responses can link to each other. Returns the base URL.
+    fn serve(routes: impl FnOnce(&str) -> Vec<(&'static str, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let routes = routes(&base);
+
+        thread::spawn(move || {
+            for mut stream in listener.incoming().filter_map(Result::ok) {
+                // Read up to the end of the request headers
+                let mut request = vec![];
+                let mut buffer = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(length) => request.extend_from_slice(&buffer[..length]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+                let (status, body) = match routes.iter().find(|(route, _)| *route == path) {
+                    Some((_, body)) => ("200 OK", body.as_slice()),
+                    None => ("404 Not Found", &[][..]),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                )
+                .ok();
+                stream.write_all(body).ok();
+            }
+        });
+
+        base
+    }
+
+    /// Serve a manifest that announces `version` and a catalog with the given contents.
+    fn serve_catalog(version: u32, catalog: &str) -> String {
+        let catalog = catalog.as_bytes().to_vec();
+        let base = serve(|base| {
+            let manifest = serde_json::json!({
+                "version": version,
+                "url": format!("{}/backgrounds.json", base),
+                "signature": SIGNATURE,
+            });
+            vec![
+                ("/manifest.json", manifest.to_string().into_bytes()),
+                ("/backgrounds.json", catalog),
+            ]
+        });
+
+        format!("{}/manifest.json", base)
+    }
+
+    /// Create an empty data directory whose configuration uses the given manifest.
+    fn data_dir(name: &str, manifest_url: Option<String>) -> PathBuf {
+        let data_dir = std::env::temp_dir().join(format!(
+            "overbuddy-backgrounds-{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::remove_dir_all(&data_dir).ok();
+        fs::create_dir_all(&data_dir).unwrap();
+
+        let mut config = config::get_default_config();
+        config.catalog.manifest_url = manifest_url;
+        config.catalog.public_key = Some(PUBLIC_KEY.to_string());
+        config::write_config_to(&data_dir, &config).unwrap();
+
+        data_dir
+    }
+
+    fn update(data_dir: &Path) -> Result<UpdateStatus, Error> {
+        tauri::async_runtime::block_on(super::update(data_dir))
+    }
+
+    #[test]
+    fn is_disabled_without_manifest_url() {
+        let data_dir = data_dir("disabled", None);
+        assert!(matches!(update(&data_dir), Ok(UpdateStatus::Disabled)));
+    }
+
+    #[test]
+    fn downloads_and_caches_signed_catalog() {
+        let data_dir = data_dir("update", Some(serve_catalog(100, CATALOG)));
+
+        assert!(matches!(update(&data_dir), Ok(UpdateStatus::Updated(100))));
+        assert_eq!(
+            fs::read_to_string(data_dir.join(CACHE_FILE)).unwrap(),
+            CATALOG
+        );
+        let settings = read_settings(&data_dir);
+        assert_eq!(base_catalog(&data_dir, &settings).version, 100);
+
+        // The cached catalog is now the current one
+        assert!(matches!(update(&data_dir), Ok(UpdateStatus::UpToDate(100))));
+    }
+
+    #[test]
+    fn skips_catalogs_that_are_not_newer() {
+        let data_dir = data_dir("up-to-date", Some(serve_catalog(1, CATALOG)));
+
+        let bundled = bundled_catalog().version;
+        assert!(
+            matches!(update(&data_dir), Ok(UpdateStatus::UpToDate(version)) if version == bundled)
+        );
+        assert!(!data_dir.join(CACHE_FILE).exists());
+    }
+
+    #[test]
+    fn rejects_catalogs_with_invalid_signature() {
+        let tampered = CATALOG.replace("Heroes", "Villains");
+        let data_dir = data_dir("tampered", Some(serve_catalog(100, &tampered)));
+
+        let error = update(&data_dir).err().unwrap();
+        assert_eq!(error.code, ErrorCode::Catalog);
+        assert!(!data_dir.join(CACHE_FILE).exists());
+    }
+
+    #[test]
+    fn rejects_downloads_that_are_too_large() {
+        let base = serve(|_| vec![("/manifest.json", vec![b' '; MAX_DOWNLOAD_SIZE + 1])]);
+        let data_dir = data_dir("too-large", Some(format!("{}/manifest.json", base)));
+
+        let error = update(&data_dir).err().unwrap();
+        assert_eq!(error.code, ErrorCode::Network);
+        assert!(error.to_string().contains("larger than"));
+    }
+
+    #[test]
+    fn reports_missing_manifest() {
+        let base = serve(|_| vec![]);
+        let data_dir = data_dir("missing", Some(format!("{}/manifest.json", base)));
+
+        let error = update(&data_dir).err().unwrap();
+        assert_eq!(error.code, ErrorCode::Network);
+    }
 }
@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    tauri_app_lib::run_cli()
+}
@@ -0,0 +1,796 @@
+use crate::actions::{self, Applied, SteamSetup};
+use crate::backgrounds::{self, BackgroundStatus, RetiredBackground, UpdateStatus};
+use crate::config::{
+    self, BackgroundConfig, Config, CustomBackground, RotationConfig, RotationInterval,
+    RotationSource, Target,
+};
+use crate::helpers::{self, Error, ErrorCode};
+use crate::history::History;
+use crate::launch_options::{self, LaunchOptions, ValueType};
+use crate::platforms::StatusReport;
+use crate::{custom_backgrounds, search};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+// Headless command line interface, built as the `overbuddy-cli` binary.
+
+const USAGE: &str = "Usage: overbuddy-cli --data-dir <path> [--dry-run] <command>
+
+Commands:
+  status [--targets]             Show the enabled platforms and applied settings
+                                 --targets shows the launch arguments of every target
+  list-backgrounds               List the available backgrounds
+  search <text>                  Search the available backgrounds by name and description
+  update-backgrounds             Download the newest signed background catalog
+  set <id> [--custom] [--target <target>]...
+                                 Apply a background, --custom allows ids outside the list
+                                 Ids may be short and in any case, e.g. e77
+                                 Targets are battlenet or steam:<account id>, default all
+  favorite <id> <on|off>         Add or remove a background from the favorites
+  history                        Show the favorites and recently applied backgrounds
+  revert                         Apply the background that was applied before the current one
+  rotate                         Apply the next background of the rotation
+  rotation <daily|weekly|launch|off> [--favorites|--tags <tags>]
+                                 Rotate backgrounds while the app is running
+  seasonal <on|off|apply>        Follow the seasonal calendar, or apply it right away
+  list-custom                    List the saved custom backgrounds
+  save-custom <id> [name]        Save a custom background to the library
+  delete-custom <id>             Remove a custom background from the library
+  reset                          Remove all launch arguments and reset OverBuddy
+  console <on|off>               Enable or disable the debug console
+  list-options                   Show the launch options of every target with warnings
+  list-flags                     List the known Overwatch flags
+  set-option <flag> [value] [--target <target>]...
+                                 Add a launch option, or change its value
+                                 Only flags that start with - can be edited
+  remove-option <flag> [--target <target>]...
+                                 Remove a launch option
+  setup [--steam] [--battlenet]  Set up the given platforms
+  steam-account <id> <on|off>    Include or exclude a Steam account
+  steam-new-accounts <on|off>    Include Steam accounts that are not known yet
+
+Options:
+  --data-dir <path>              Directory that contains the OverBuddy configuration
+  --dry-run                      Show the changes to launcher files without writing them
+                                 Supported by set, revert, reset, console, set-option
+                                 and remove-option";
+
+/// Commands that write without being able to preview the change first.
+const NO_DRY_RUN_COMMANDS: [&str; 10] = [
+    "update-backgrounds",
+    "favorite",
+    "rotate",
+    "rotation",
+    "seasonal",
+    "save-custom",
+    "delete-custom",
+    "setup",
+    "steam-account",
+    "steam-new-accounts",
+];
+
+#[derive(Debug, PartialEq)]
+enum Parsed {
+    Help,
+    Run(Args),
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    data_dir: PathBuf,
+    dry_run: bool,
+    command: Vec<String>,
+}
+
+/// Run the CLI with the given arguments, excluding the program name.
+pub fn run(args: Vec<String>) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Parsed::Help) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Parsed::Run(args)) => args,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match execute(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            for hint in &error.hints {
+                eprintln!("Hint: {}", helpers::render_plain(hint));
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Parsed, String> {
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(Parsed::Help);
+    }
+
+    let mut data_dir = None;
+    let mut dry_run = false;
+    let mut command = vec![];
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--data-dir" => {
+                let path = args.next().ok_or("Missing a path after --data-dir")?;
+                data_dir = Some(PathBuf::from(path));
+            }
+            "--dry-run" => dry_run = true,
+            _ => command.push(arg),
+        }
+    }
+
+    let data_dir = data_dir.ok_or("Missing the --data-dir option")?;
+    if command.is_empty() {
+        return Err("Missing a command".to_string());
+    }
+
+    Ok(Parsed::Run(Args {
+        data_dir,
+        dry_run,
+        command,
+    }))
+}
+
+fn execute(args: &Args) -> Result<(), Error> {
+    let data_dir = args.data_dir.as_path();
+    let command: Vec<&str> = args.command.iter().map(String::as_str).collect();
+
+    if let Some(name) = command
+        .first()
+        .filter(|name| args.dry_run && NO_DRY_RUN_COMMANDS.contains(name))
+    {
+        return Err(Error::new(
+            ErrorCode::InvalidArgument,
+            format!("[[{}]] does not support --dry-run", name),
+        )
+        .with_hint("Run the command without --dry-run to apply it."));
+    }
+
+    if let Err(error) = backgrounds::load(data_dir) {
+        eprintln!("Warning: {}\nUsing the bundled backgrounds instead.", error);
+    }
+
+    match command.as_slice() {
+        ["status"] => print_status(&actions::read_launch_config(data_dir)?),
+        ["status", "--targets"] => print_targets(
+            &config::read_config_from(data_dir)?,
+            &actions::get_status(data_dir)?,
+        ),
+        ["list-backgrounds"] => {
+            for background in backgrounds::get_backgrounds() {
+                println!("{}\t{}", background.id, background.name);
+            }
+        }
+        ["search", text @ ..] if !text.is_empty() => {
+            let query = search::BackgroundQuery {
+                text: Some(text.join(" ")),
+                ..Default::default()
+            };
+            for result in actions::query_backgrounds(data_dir, &query)?.results {
+                println!("{}\t{}", result.background.id, result.background.name);
+            }
+        }
+        ["update-backgrounds"] => {
+            match tauri::async_runtime::block_on(backgrounds::update(data_dir))? {
+                UpdateStatus::Updated(version) => {
+                    println!("Updated the background catalog to version {}", version)
+                }
+                UpdateStatus::UpToDate(version) => {
+                    println!("Background catalog version {} is up to date", version)
+                }
+                UpdateStatus::Disabled => println!(
+                    "Background catalog updates are disabled or no manifest_url is configured"
+                ),
+            }
+        }
+        ["set", options @ ..] => {
+            let mut id = None;
+            let mut is_custom = false;
+            let mut targets = vec![];
+            let mut options = options.iter();
+            while let Some(option) = options.next() {
+                match *option {
+                    "--custom" => is_custom = true,
+                    "--target" => {
+                        let target = options.next().ok_or_else(|| usage_error(&args.command))?;
+                        targets.push(parse_target(target)?);
+                    }
+                    _ if id.is_none() => id = Some(*option),
+                    _ => return Err(usage_error(&args.command)),
+                }
+            }
+            let id = &backgrounds::normalize_id(id.ok_or_else(|| usage_error(&args.command))?)?;
+            if !is_custom
+                && backgrounds::find_background_by_id(id).is_none()
+                && custom_backgrounds::find(&config::read_config_from(data_dir)?, id).is_none()
+            {
+                return Err(Error::new(
+                    ErrorCode::InvalidArgument,
+                    format!("Failed to find a background with id [[{}]]", id),
+                )
+                .with_hint("Run list-backgrounds to see the available ids, or pass --custom."));
+            }
+            print_applied(actions::set_background(
+                data_dir,
+                id,
+                is_custom,
+                (!targets.is_empty()).then_some(targets.as_slice()),
+                args.dry_run,
+            )?);
+        }
+        ["favorite", id, state @ ("on" | "off")] => {
+            actions::set_favorite(data_dir, id, *state == "on")?;
+            let id = backgrounds::normalize_id(id)?;
+            if *state == "on" {
+                println!("Added {} to the favorites", id);
+            } else {
+                println!("Removed {} from the favorites", id);
+            }
+        }
+        ["history"] => print_history(&actions::get_history(data_dir)?),
+        ["revert"] => print_applied(actions::revert_background(data_dir, args.dry_run)?),
+        ["rotate"] => {
+            let config = actions::rotate_background(data_dir)?;
+            println!(
+                "Applied background {}",
+                config.shared.background.current.unwrap_or_default()
+            );
+        }
+        ["rotation", interval, source @ ..] => {
+            let mut rotation = RotationConfig {
+                enabled: *interval != "off",
+                ..config::read_config_from(data_dir)?.rotation
+            };
+            rotation.interval = match *interval {
+                "daily" => RotationInterval::Daily,
+                "weekly" => RotationInterval::Weekly,
+                "launch" => RotationInterval::EveryLaunch,
+                "off" => rotation.interval,
+                _ => return Err(usage_error(&args.command)),
+            };
+            match source {
+                [] => {}
+                ["--favorites"] => rotation.source = RotationSource::Favorites,
+                ["--tags", tags @ ..] if !tags.is_empty() => {
+                    rotation.source = RotationSource::Tags;
+                    rotation.tags = tags.iter().map(ToString::to_string).collect();
+                }
+                _ => return Err(usage_error(&args.command)),
+            }
+            actions::set_rotation(data_dir, rotation)?;
+            println!("Saved the rotation settings");
+        }
+        ["seasonal", state @ ("on" | "off")] => {
+            actions::set_seasonal_calendar(data_dir, *state == "on", None)?;
+            println!("Saved the seasonal calendar settings");
+        }
+        ["seasonal", "apply"] => match actions::apply_seasonal_calendar(data_dir)? {
+            Some(config) => match config.seasonal.active {
+                Some(active) => println!("Applied the {} background", active.name),
+                None => println!("Restored the background from before the event"),
+            },
+            None => println!("The seasonal calendar is up to date"),
+        },
+        ["list-custom"] => {
+            for background in config::read_config_from(data_dir)?.custom_backgrounds {
+                println!("{}\t{}", background.id, background.name);
+            }
+        }
+        ["save-custom", id, name @ ..] => {
+            let background = CustomBackground {
+                id: id.to_string(),
+                name: name.join(" "),
+                notes: None,
+                image: None,
+                tags: vec![],
+            };
+            actions::save_custom_background(data_dir, background, None)?;
+            println!("Saved custom background {}", id);
+        }
+        ["delete-custom", id] => {
+            actions::delete_custom_background(data_dir, id)?;
+            println!("Deleted custom background {}", id);
+        }
+        ["reset"] => print_applied(actions::reset(data_dir, args.dry_run)?),
+        ["console", state @ ("on" | "off")] => print_applied(actions::set_debug_console(
+            data_dir,
+            *state == "on",
+            args.dry_run,
+        )?),
+        ["steam-account", id, state @ ("on" | "off")] => {
+            actions::set_steam_account_included(data_dir, id, *state == "on")?;
+            if *state == "on" {
+                println!("Included Steam account {}", id);
+            } else {
+                println!("Excluded Steam account {}", id);
+            }
+        }
+        ["steam-new-accounts", state @ ("on" | "off")] => {
+            actions::set_include_new_steam_accounts(data_dir, *state == "on")?;
+            println!("Saved the Steam account settings");
+        }
+        ["setup", flags @ ..] if !flags.is_empty() => {
+            let mut platforms = vec![];
+            for flag in flags {
+                match *flag {
+                    "--battlenet" => platforms.push("BattleNet"),
+                    "--steam" => platforms.push("Steam"),
+                    _ => return Err(usage_error(&args.command)),
+                }
+            }
+            setup(args, &platforms)?;
+        }
+        ["list-options"] => print_launch_options(&actions::get_launch_options(data_dir)?),
+        ["list-flags"] => {
+            for known in launch_options::CATALOG {
+                let value = match known.value {
+                    ValueType::None => "",
+                    ValueType::Integer => "=<number>",
+                    ValueType::Background => "=<background id>",
+                };
+                let managed = if known.managed {
+                    ", managed by OverBuddy"
+                } else {
+                    ""
+                };
+                println!("{}{}\t{}{}", known.flag, value, known.description, managed);
+            }
+        }
+        ["set-option", flag, options @ ..] => {
+            let mut value = None;
+            let mut targets = vec![];
+            let mut options = options.iter();
+            while let Some(option) = options.next() {
+                match *option {
+                    "--target" => {
+                        let target = options.next().ok_or_else(|| usage_error(&args.command))?;
+                        targets.push(parse_target(target)?);
+                    }
+                    _ if value.is_none() => value = Some(*option),
+                    _ => return Err(usage_error(&args.command)),
+                }
+            }
+            print_applied(actions::set_launch_option(
+                data_dir,
+                flag,
+                value,
+                (!targets.is_empty()).then_some(targets.as_slice()),
+                args.dry_run,
+            )?);
+            if !args.dry_run {
+                print_launch_options(&actions::get_launch_options(data_dir)?);
+            }
+        }
+        ["remove-option", flag, options @ ..] => {
+            let mut targets = vec![];
+            let mut options = options.iter();
+            while let Some(option) = options.next() {
+                match *option {
+                    "--target" => {
+                        let target = options.next().ok_or_else(|| usage_error(&args.command))?;
+                        targets.push(parse_target(target)?);
+                    }
+                    _ => return Err(usage_error(&args.command)),
+                }
+            }
+            print_applied(actions::remove_launch_option(
+                data_dir,
+                flag,
+                (!targets.is_empty()).then_some(targets.as_slice()),
+                args.dry_run,
+            )?);
+        }
+        _ => return Err(usage_error(&args.command)),
+    }
+
+    Ok(())
+}
+
+fn usage_error(command: &[String]) -> Error {
+    Error::new(
+        ErrorCode::InvalidArgument,
+        format!("Unknown command [[{}]]", command.join(" ")),
+    )
+    .with_hint("Run overbuddy-cli --help to see the available commands.")
+}
+
+/// Parse a target given as `battlenet` or `steam:<account id>`.
+fn parse_target(target: &str) -> Result<Target, Error> {
+    match target.split_once(':') {
+        None if target.eq_ignore_ascii_case("battlenet") => Ok(Target::BattleNet),
+        Some((platform, account))
+            if platform.eq_ignore_ascii_case("steam") && !account.is_empty() =>
+        {
+            Ok(Target::Steam(account.to_string()))
+        }
+        _ => Err(Error::new(
+            ErrorCode::InvalidArgument,
+            format!("Unknown target [[{}]]", target),
+        )
+        .with_hint("Targets are battlenet or steam:<account id>, see status for the account ids.")),
+    }
+}
+
+fn setup(args: &Args, platforms: &[&str]) -> Result<(), Error> {
+    let data_dir = args.data_dir.as_path();
+    let mut config = actions::setup(data_dir, platforms, false)?;
+
+    // The app lets the user review their Steam accounts first, the CLI confirms them directly
+    if config.steam.in_setup {
+        config = match actions::confirm_steam_setup(data_dir)? {
+            SteamSetup::Confirmed(config) => *config,
+            SteamSetup::NoOverwatch { fatal } => {
+                let config = actions::undo_steam_setup(data_dir)?;
+                let error = Error::new(
+                    ErrorCode::NoOverwatch,
+                    "Unable to find an Overwatch installation on any Steam account",
+                );
+                if fatal {
+                    return Err(error);
+                }
+                eprintln!("Warning: {}, Steam was not set up", error);
+                config
+            }
+        };
+    }
+
+    print_status(&config);
+    Ok(())
+}
+
+fn print_applied(applied: Applied) {
+    if let Applied::Preview(_, files) = &applied {
+        for file in files {
+            print!("{}", file.diff);
+        }
+        if files.is_empty() {
+            println!("No launcher files would change.");
+        }
+        println!("Dry run, nothing was written.");
+        return;
+    }
+
+    print_status(applied.config());
+}
+
+fn print_retired(label: &str, retired: &RetiredBackground) {
+    let status = match retired.status {
+        BackgroundStatus::Broken => "broken",
+        _ => "removed",
+    };
+    let season = retired
+        .season
+        .map(|season| format!(" in Season {}", season))
+        .unwrap_or_default();
+    println!(
+        "{}: {} ({}), {}{}",
+        label, retired.name, retired.id, status, season
+    );
+
+    match retired
+        .replacement
+        .as_deref()
+        .and_then(backgrounds::find_background_by_id)
+    {
+        Some(replacement) => println!(
+            "Suggested replacement: {} (set {})",
+            replacement.name, replacement.id
+        ),
+        None => println!("Run reset to restore the default background."),
+    }
+}
+
+fn print_history(history: &History) {
+    let unavailable = |available: bool| if available { "" } else { " (unavailable)" };
+
+    println!("Favorites:");
+    for favorite in &history.favorites {
+        println!(
+            "  {}\t{}{}",
+            favorite.id,
+            favorite.name,
+            unavailable(favorite.available)
+        );
+    }
+
+    println!("Most applied:");
+    for background in history.backgrounds.iter().take(10) {
+        println!(
+            "  {}\t{}\t{} times{}",
+            background.id,
+            background.name,
+            background.apply_count,
+            unavailable(background.available)
+        );
+    }
+
+    println!("Recently applied:");
+    for item in history.entries.iter().take(10) {
+        println!(
+            "  {}\t{}\t{}{}",
+            item.entry.applied_at,
+            item.entry.id,
+            item.entry.name,
+            unavailable(item.available)
+        );
+    }
+}
+
+fn print_background(config: &Config, label: &str, background: &BackgroundConfig) {
+    match background.current.as_deref() {
+        Some(id) => match backgrounds::find_background_by_id(id) {
+            Some(found) => println!("{}: {} ({})", label, found.name, id),
+            None => match custom_backgrounds::find(config, id) {
+                Some(custom) if custom.name != id => {
+                    println!("{}: {} ({}, custom)", label, custom.name, id)
+                }
+                _ => println!("{}: {} (custom)", label, id),
+            },
+        },
+        None => match &background.retired {
+            Some(retired) => print_retired(label, retired),
+            None if background.is_outdated => println!("{}: outdated", label),
+            None => println!("{}: default", label),
+        },
+    }
+}
+
+fn print_targets(config: &Config, report: &StatusReport) {
+    for status in &report.targets {
+        println!("{} ({})", status.state.name, status.state.target);
+        println!("  File: {}", status.path);
+        if let Some(modified) = status.modified {
+            println!("  Modified: {}", modified);
+        }
+        if let Some(error) = &status.error {
+            println!("  Error: {}", error);
+            continue;
+        }
+        println!(
+            "  Launch arguments: {}",
+            status.launch_args.as_deref().unwrap_or_default()
+        );
+        print_background(config, "  Background", &status.state.background);
+        println!(
+            "  Debug console: {}",
+            if status.state.additional.console_enabled {
+                "on"
+            } else {
+                "off"
+            }
+        );
+    }
+
+    let summary = &report.summary.background;
+    if summary.current.is_none()
+        && report
+            .targets
+            .iter()
+            .any(|status| status.state.background.current.is_some())
+    {
+        println!("Summary: the targets have different backgrounds");
+    } else {
+        print_background(config, "Summary", summary);
+    }
+}
+
+fn print_launch_options(launch_options: &LaunchOptions) {
+    for target in &launch_options.targets {
+        println!("{} ({})", target.name, target.target);
+        if let Some(error) = &target.error {
+            println!("  Error: {}", error);
+            continue;
+        }
+        for arg in &target.args {
+            println!("  {}", arg);
+        }
+        for warning in &target.warnings {
+            println!("  Warning: {}", warning.message);
+        }
+    }
+}
+
+fn print_status(config: &Config) {
+    let platforms: Vec<&str> = [
+        (config.battle_net.enabled, "Battle.net"),
+        (config.steam.enabled, "Steam"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, name)| enabled.then_some(name))
+    .collect();
+
+    if !config.is_setup || platforms.is_empty() {
+        println!("Not set up. Run setup --battlenet and/or --steam first.");
+        return;
+    }
+    println!("Platforms: {}", platforms.join(", "));
+
+    // Targets with different backgrounds are listed separately
+    let is_shared = config
+        .targets
+        .iter()
+        .all(|state| state.background.current == config.shared.background.current);
+    if is_shared {
+        print_background(config, "Background", &config.shared.background);
+    } else {
+        for state in &config.targets {
+            let label = format!("Background on {}", state.name);
+            print_background(config, &label, &state.background);
+        }
+    }
+    println!("Catalog version: {}", backgrounds::get_catalog().version);
+    if let Some(active) = &config.seasonal.active {
+        println!("Seasonal event: {}", active.name);
+    }
+    if config.rotation.enabled {
+        println!(
+            "Rotation: {}, from {}",
+            match config.rotation.interval {
+                RotationInterval::Daily => "daily",
+                RotationInterval::Weekly => "weekly",
+                RotationInterval::EveryLaunch => "every launch",
+            },
+            match config.rotation.source {
+                RotationSource::All => "all backgrounds".to_string(),
+                RotationSource::Favorites => "favorites".to_string(),
+                RotationSource::Tags => config.rotation.tags.join(", "),
+            }
+        );
+    }
+    println!(
+        "Debug console: {}",
+        if config.shared.additional.console_enabled {
+            "on"
+        } else {
+            "off"
+        }
+    );
+
+    if let Some(profiles) = &config.steam.profiles {
+        for profile in profiles {
+            println!(
+                "Steam account: {} ({}){}{}{}",
+                profile.name,
+                profile.id,
+                if profile.has_overwatch {
+                    ""
+                } else {
+                    ", without Overwatch"
+                },
+                if profile.included { "" } else { ", excluded" },
+                if profile.is_new { ", new" } else { "" }
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn parse(args: &[&str]) -> Result<Parsed, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    fn run_args(data_dir: &Path, dry_run: bool, command: &[&str]) -> Parsed {
+        Parsed::Run(Args {
+            data_dir: data_dir.to_path_buf(),
+            dry_run,
+            command: command.iter().map(|arg| arg.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn parses_options_in_any_position() {
+        let data_dir = Path::new("/data");
+        for args in [
+            &["--data-dir", "/data", "--dry-run", "set", "e77"][..],
+            &["--dry-run", "set", "--data-dir", "/data", "e77"],
+            &["set", "e77", "--dry-run", "--data-dir", "/data"],
+        ] {
+            assert_eq!(parse(args), Ok(run_args(data_dir, true, &["set", "e77"])));
+        }
+        assert_eq!(
+            parse(&["status", "--data-dir", "/data"]),
+            Ok(run_args(data_dir, false, &["status"]))
+        );
+    }
+
+    #[test]
+    fn rejects_missing_options() {
+        assert!(parse(&["status"]).is_err());
+        assert!(parse(&["status", "--data-dir"]).is_err());
+        assert!(parse(&["--data-dir", "/data"]).is_err());
+        assert!(parse(&[]).is_err());
+    }
+
+    #[test]
+    fn parses_help_anywhere() {
+        assert_eq!(parse(&["-h"]), Ok(Parsed::Help));
+        assert_eq!(
+            parse(&["--data-dir", "/data", "set", "--help"]),
+            Ok(Parsed::Help)
+        );
+    }
+
+    /// Create a data directory with Battle.net set up, whose saved state is out of date.
+    fn data_dir(name: &str) -> PathBuf {
+        let data_dir =
+            std::env::temp_dir().join(format!("overbuddy-cli-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&data_dir).ok();
+        fs::create_dir_all(&data_dir).unwrap();
+
+        let battle_net_config = data_dir.join("Battle.net.config");
+        fs::write(
+            &battle_net_config,
+            r#"{"Games":{"prometheus":{"AdditionalLaunchArguments":"--lobbyMap=0x0800000000000E77"}}}"#,
+        )
+        .unwrap();
+
+        let mut config = config::get_default_config();
+        config.is_setup = true;
+        config.battle_net.enabled = true;
+        config.battle_net.config = Some(battle_net_config.to_string_lossy().to_string());
+        config::write_config_to(&data_dir, &config).unwrap();
+
+        data_dir
+    }
+
+    fn execute_command(data_dir: &Path, dry_run: bool, command: &[&str]) -> Result<(), Error> {
+        match run_args(data_dir, dry_run, command) {
+            Parsed::Run(args) => execute(&args),
+            Parsed::Help => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn rejects_dry_run_for_commands_that_cannot_preview() {
+        let data_dir = data_dir("dry-run");
+        for command in NO_DRY_RUN_COMMANDS {
+            let error = execute_command(&data_dir, true, &[command]).err().unwrap();
+            assert_eq!(error.code, ErrorCode::InvalidArgument, "{}", command);
+        }
+    }
+
+    #[test]
+    fn read_only_commands_write_nothing() {
+        let data_dir = data_dir("read-only");
+        let config_file = data_dir.join("data.json");
+        let before = fs::read(&config_file).unwrap();
+        let launcher_before = fs::read(data_dir.join("Battle.net.config")).unwrap();
+
+        for command in [
+            &["status"][..],
+            &["status", "--targets"],
+            &["list-backgrounds"],
+            &["search", "heroes"],
+            &["history"],
+            &["list-custom"],
+            &["list-options"],
+            &["list-flags"],
+        ] {
+            execute_command(&data_dir, false, command).unwrap();
+            assert_eq!(fs::read(&config_file).unwrap(), before, "{:?}", command);
+        }
+
+        // Previews leave the configuration and the launcher files alone
+        execute_command(&data_dir, true, &["set", "efb"]).unwrap();
+        assert_eq!(fs::read(&config_file).unwrap(), before);
+        assert_eq!(
+            fs::read(data_dir.join("Battle.net.config")).unwrap(),
+            launcher_before
+        );
+    }
+}
@@ -1,28 +1,22 @@
-use crate::helpers::Error;
+use crate::backgrounds::RetiredBackground;
+use crate::discovery::WineRunner;
+use crate::helpers::{Error, ErrorCode, Platform};
+use crate::seasonal;
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 
-#[derive(serde::Serialize)]
-pub enum ErrorKey {
-    NoOverwatch,
-    BattleNetInstall,
-    BattleNetConfig,
-    SteamInstall,
-    SteamAccount,
-}
-#[derive(serde::Serialize)]
-pub struct SetupError {
-    pub error_key: ErrorKey,
-    pub message: String,
-    pub platforms: Option<Vec<String>>,
-}
-
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct BattleNetConfig {
     pub enabled: bool,
     pub config: Option<String>,
     pub install: Option<String>,
+    pub prefix: Option<String>,
+    /// Runner of the Wine frontend the prefix belongs to.
+    #[serde(default)]
+    pub runner: Option<WineRunner>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -34,9 +28,34 @@ pub struct SteamLocalconfig {
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct SteamProfile {
     pub id: String,
+    pub steam_id: Option<String>,
     pub name: String,
+    pub account_name: Option<String>,
     pub avatar: Option<String>,
+    #[serde(default)]
+    pub most_recent: bool,
     pub has_overwatch: bool,
+    #[serde(default)]
+    pub is_last_owner: bool,
+    /// Whether OverBuddy manages the launch arguments of the account.
+    #[serde(default = "default_included")]
+    pub included: bool,
+    /// Set for accounts found after the Steam setup, until the user includes or excludes them.
+    #[serde(default)]
+    pub is_new: bool,
+}
+
+fn default_included() -> bool {
+    true
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct SteamAppInstall {
+    pub library: String,
+    pub install_dir: String,
+    pub build_id: Option<String>,
+    pub branch: String,
+    pub last_owner: Option<String>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -46,6 +65,10 @@ pub struct SteamConfig {
     pub install: Option<String>,
     pub configs: Option<Vec<SteamLocalconfig>>,
     pub profiles: Option<Vec<SteamProfile>>,
+    pub overwatch: Option<SteamAppInstall>,
+    /// Whether accounts found after the Steam setup are included.
+    #[serde(default = "default_included")]
+    pub include_new_accounts: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -53,6 +76,23 @@ pub struct BackgroundConfig {
     pub current: Option<String>,
     pub is_outdated: bool,
     pub custom: Option<String>,
+    /// Set if the applied background was removed from the game or broke.
+    #[serde(default)]
+    pub retired: Option<RetiredBackground>,
+}
+
+/// A background from the user's library of custom lobbyMap ids.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct CustomBackground {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// URL of the preview image the user supplied, served by the custom background protocol.
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -60,18 +100,202 @@ pub struct AdditionalConfig {
     pub console_enabled: bool,
 }
 
+/// The state of every target merged into one. The background is only set if all targets
+/// agree on it.
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct SharedConfig {
     pub background: BackgroundConfig,
     pub additional: AdditionalConfig,
 }
 
+/// A launch configuration of Overwatch that launch arguments are applied to.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(tag = "platform", content = "account")]
+pub enum Target {
+    BattleNet,
+    /// A Steam account, by the id of its `userdata` folder.
+    Steam(String),
+}
+
+impl Target {
+    pub fn platform(&self) -> Platform {
+        match self {
+            Target::BattleNet => Platform::BattleNet,
+            Target::Steam(_) => Platform::Steam,
+        }
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::BattleNet => write!(f, "Battle.net"),
+            Target::Steam(account) => write!(f, "Steam account {}", account),
+        }
+    }
+}
+
+/// The launch argument state of a single target.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TargetConfig {
+    pub target: Target,
+    /// Name of the target shown to the user, e.g. the Steam account name.
+    pub name: String,
+    pub background: BackgroundConfig,
+    pub additional: AdditionalConfig,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CatalogConfig {
+    /// Check the manifest for newer background catalogs.
+    pub updates_enabled: bool,
+    /// Manifest to check for newer catalogs. Updates are off until one is set.
+    pub manifest_url: Option<String>,
+    /// Public key the catalog must be signed with instead of the default one.
+    pub public_key: Option<String>,
+}
+
+impl Default for CatalogConfig {
+    fn default() -> Self {
+        CatalogConfig {
+            updates_enabled: true,
+            manifest_url: None,
+            public_key: None,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationInterval {
+    Daily,
+    Weekly,
+    /// Every time Overwatch exits, so the next launch shows a new background.
+    EveryLaunch,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationSource {
+    /// Every available background in the catalog.
+    All,
+    Favorites,
+    /// Backgrounds with any of the rotation tags.
+    Tags,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RotationConfig {
+    pub enabled: bool,
+    pub interval: RotationInterval,
+    pub source: RotationSource,
+    pub tags: Vec<String>,
+    /// Number of recently applied backgrounds that are not picked again.
+    pub no_repeat: usize,
+    /// Unix time in seconds of the last rotation.
+    pub last_rotated: Option<u64>,
+}
+
+impl Default for RotationConfig {
+    fn default() -> Self {
+        RotationConfig {
+            enabled: false,
+            interval: RotationInterval::Daily,
+            source: RotationSource::All,
+            tags: vec![],
+            no_repeat: 5,
+            last_rotated: None,
+        }
+    }
+}
+
+/// A background in the history. The name is saved along with the id, so entries can be
+/// shown after the background is removed from the catalog.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub name: String,
+    /// Unix time in seconds.
+    pub applied_at: u64,
+    #[serde(default)]
+    pub is_custom: bool,
+}
+
+/// An event of the seasonal calendar, repeated every year.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct SeasonalEvent {
+    pub name: String,
+    /// First day of the event as `MM-DD`.
+    pub start: String,
+    /// Last day of the event as `MM-DD`, before `start` if the event spans the new year.
+    pub end: String,
+    pub background: String,
+}
+
+/// The background a target had before a seasonal event was applied.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct PreviousBackground {
+    pub target: Target,
+    /// Background to restore when the event ends, `None` for the default background.
+    pub background: Option<String>,
+    pub is_custom: bool,
+}
+
+/// The seasonal event whose background is applied.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct ActiveSeasonalEvent {
+    pub name: String,
+    pub background: String,
+    /// Backgrounds of every target before the event.
+    pub previous: Vec<PreviousBackground>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SeasonalConfig {
+    pub enabled: bool,
+    pub events: Vec<SeasonalEvent>,
+    pub active: Option<ActiveSeasonalEvent>,
+}
+
+impl Default for SeasonalConfig {
+    fn default() -> Self {
+        SeasonalConfig {
+            enabled: false,
+            events: seasonal::default_calendar(),
+            active: None,
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Config {
     pub is_setup: bool,
     pub battle_net: BattleNetConfig,
     pub steam: SteamConfig,
     pub shared: SharedConfig,
+    /// State of each target, read from the launchers along with `shared`.
+    #[serde(default)]
+    pub targets: Vec<TargetConfig>,
+    #[serde(default)]
+    pub catalog: CatalogConfig,
+    /// Applied backgrounds, most recent first.
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    /// Number of times each background was applied, by id.
+    #[serde(default)]
+    pub apply_counts: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub custom_backgrounds: Vec<CustomBackground>,
+    /// Ids of the user's favorite backgrounds.
+    #[serde(default)]
+    pub favorites: Vec<String>,
+    #[serde(default)]
+    pub rotation: RotationConfig,
+    #[serde(default)]
+    pub seasonal: SeasonalConfig,
 }
 
 pub fn get_default_config() -> Config {
@@ -81,6 +305,8 @@ pub fn get_default_config() -> Config {
             enabled: false,
             config: None,
             install: None,
+            prefix: None,
+            runner: None,
         },
         steam: SteamConfig {
             enabled: false,
@@ -88,17 +314,28 @@ pub fn get_default_config() -> Config {
             install: None,
             configs: None,
             profiles: None,
+            overwatch: None,
+            include_new_accounts: true,
         },
         shared: SharedConfig {
             background: BackgroundConfig {
                 current: None,
                 is_outdated: false,
                 custom: None,
+                retired: None,
             },
             additional: AdditionalConfig {
                 console_enabled: false,
             },
         },
+        targets: vec![],
+        catalog: CatalogConfig::default(),
+        history: vec![],
+        apply_counts: BTreeMap::new(),
+        custom_backgrounds: vec![],
+        favorites: vec![],
+        rotation: RotationConfig::default(),
+        seasonal: SeasonalConfig::default(),
     }
 }
 
@@ -122,32 +359,46 @@ fn merge(a: &mut Value, b: Value) {
     *a = b;
 }
 
+/// Get the directory the OverBuddy configuration is stored in.
+pub fn data_dir(handle: &AppHandle) -> PathBuf {
+    handle.path().app_local_data_dir().unwrap()
+}
+
 pub fn read_config(handle: &AppHandle) -> Result<Config, Error> {
-    let app_local_data_dir = handle.path().app_local_data_dir().unwrap();
+    read_config_from(&data_dir(handle))
+}
 
+pub fn read_config_from(data_dir: &Path) -> Result<Config, Error> {
     // Ensure directory exists
-    if !app_local_data_dir.exists() {
-        match fs::create_dir_all(&app_local_data_dir) {
+    if !data_dir.exists() {
+        match fs::create_dir_all(data_dir) {
             Ok(_) => {}
-            Err(_) => {
-                return Err(Error::Custom(format!(
-                    "Failed to read local data at [[{}]]",
-                    app_local_data_dir.to_str().unwrap()
-                )));
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorCode::Config,
+                    format!("Failed to read local data at [[{}]]", data_dir.display()),
+                )
+                .with_path(data_dir)
+                .with_io(&e));
             }
         }
     }
 
     // Ensure file exists
-    let config_file_path = app_local_data_dir.join(CONFIG_FILE);
+    let config_file_path = data_dir.join(CONFIG_FILE);
     if !config_file_path.exists() {
         match fs::File::create(&config_file_path) {
             Ok(_) => {}
-            Err(_) => {
-                return Err(Error::Custom(format!(
-                    "Failed to create configuration file [[{}]]",
-                    config_file_path.to_str().unwrap()
-                )));
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorCode::Config,
+                    format!(
+                        "Failed to create configuration file [[{}]]",
+                        config_file_path.display()
+                    ),
+                )
+                .with_path(&config_file_path)
+                .with_io(&e));
             }
         }
     }
@@ -163,10 +414,15 @@ pub fn read_config(handle: &AppHandle) -> Result<Config, Error> {
                     let mut config: Value = serde_json::to_value(get_default_config()).unwrap();
                     merge(&mut config, json);
                     serde_json::from_value(config).map_err(|_| {
-                        Error::Custom(format!(
-                            "Failed to parse configuration file [[{}]]",
-                            config_file_path.to_str().unwrap()
-                        ))
+                        Error::new(
+                            ErrorCode::Config,
+                            format!(
+                                "Failed to parse configuration file [[{}]]",
+                                config_file_path.display()
+                            ),
+                        )
+                        .with_path(&config_file_path)
+                        .with_hint("Resetting OverBuddy will restore the default configuration.")
                     })?
                 }
             }
@@ -178,31 +434,40 @@ pub fn read_config(handle: &AppHandle) -> Result<Config, Error> {
 }
 
 pub fn write_config(handle: &AppHandle, config: &Config) -> Result<(), Error> {
-    let app_local_data_dir = handle.path().app_local_data_dir().unwrap();
+    write_config_to(&data_dir(handle), config)
+}
 
+pub fn write_config_to(data_dir: &Path, config: &Config) -> Result<(), Error> {
     // Ensure directory exists
-    if !app_local_data_dir.exists() {
-        match fs::create_dir_all(&app_local_data_dir) {
+    if !data_dir.exists() {
+        match fs::create_dir_all(data_dir) {
             Ok(_) => {}
-            Err(_) => {
-                return Err(Error::Custom(format!(
-                    "Failed to write local data at [[{}]]",
-                    app_local_data_dir.to_str().unwrap()
-                )));
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorCode::Config,
+                    format!("Failed to write local data at [[{}]]", data_dir.display()),
+                )
+                .with_path(data_dir)
+                .with_io(&e));
             }
         }
     }
 
     // Ensure file exists
-    let config_file_path = app_local_data_dir.join(CONFIG_FILE);
+    let config_file_path = data_dir.join(CONFIG_FILE);
     if !config_file_path.exists() {
         match fs::File::create(&config_file_path) {
             Ok(_) => {}
-            Err(_) => {
-                return Err(Error::Custom(format!(
-                    "Failed to create configuration file at [[{}]]",
-                    config_file_path.to_str().unwrap()
-                )));
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorCode::Config,
+                    format!(
+                        "Failed to create configuration file at [[{}]]",
+                        config_file_path.display()
+                    ),
+                )
+                .with_path(&config_file_path)
+                .with_io(&e));
             }
         }
     }
@@ -211,16 +476,21 @@ pub fn write_config(handle: &AppHandle, config: &Config) -> Result<(), Error> {
     let serialized_config = match serde_json::to_string(&config) {
         Ok(json) => json,
         Err(_) => {
-            return Err(Error::Custom("Failed to serialize config".into()));
+            return Err(Error::new(ErrorCode::Config, "Failed to serialize config"));
         }
     };
     match fs::write(&config_file_path, &serialized_config) {
         Ok(_) => {}
-        Err(_) => {
-            return Err(Error::Custom(format!(
-                "Failed to write configuration file at [[{}]]",
-                config_file_path.to_str().unwrap()
-            )));
+        Err(e) => {
+            return Err(Error::new(
+                ErrorCode::Config,
+                format!(
+                    "Failed to write configuration file at [[{}]]",
+                    config_file_path.display()
+                ),
+            )
+            .with_path(&config_file_path)
+            .with_io(&e));
         }
     }
 
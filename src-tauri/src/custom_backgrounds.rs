@@ -0,0 +1,317 @@
+use crate::backgrounds;
+use crate::config::{Config, CustomBackground};
+use crate::helpers::{Error, ErrorCode};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The user's library of custom lobbyMap ids, with preview images stored in the data directory.
+
+/// URI scheme the UI loads preview images of custom backgrounds through.
+pub const IMAGE_PROTOCOL: &str = "custom-background";
+const IMAGE_DIR: &str = "custom_backgrounds";
+const IMAGE_TYPES: [(&str, &str); 5] = [
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("webp", "image/webp"),
+    ("gif", "image/gif"),
+];
+
+pub fn find<'a>(config: &'a Config, id: &str) -> Option<&'a CustomBackground> {
+    config
+        .custom_backgrounds
+        .iter()
+        .find(|background| background.id.eq_ignore_ascii_case(id))
+}
+
+/// Add a background to the library or replace the one with the same id. `image` is a file
+/// that is copied as the preview, `None` keeps the current preview.
+pub fn save(
+    config: &mut Config,
+    data_dir: &Path,
+    mut background: CustomBackground,
+    image: Option<&Path>,
+) -> Result<(), Error> {
+    background.id = backgrounds::normalize_id(&background.id)?;
+    background.name = background.name.trim().to_string();
+    if background.name.is_empty() {
+        background.name = background.id.clone();
+    }
+    let mut tags = HashSet::new();
+    background.tags = background
+        .tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty() && tags.insert(tag.clone()))
+        .collect();
+
+    let existing = config
+        .custom_backgrounds
+        .iter()
+        .position(|custom| custom.id.eq_ignore_ascii_case(&background.id));
+    background.image = match image {
+        Some(image) => Some(store_image(data_dir, &background.id, image)?),
+        None => existing.and_then(|index| config.custom_backgrounds[index].image.clone()),
+    };
+
+    match existing {
+        Some(index) => config.custom_backgrounds[index] = background,
+        None => config.custom_backgrounds.push(background),
+    }
+
+    Ok(())
+}
+
+/// Add an id to the library unless it is already saved, named after the id itself.
+pub fn remember(config: &mut Config, id: &str) {
+    if find(config, id).is_none() {
+        config.custom_backgrounds.push(CustomBackground {
+            id: id.to_string(),
+            name: id.to_string(),
+            notes: None,
+            image: None,
+            tags: vec![],
+        });
+    }
+}
+
+/// Remove a background and its preview image from the library.
+pub fn delete(config: &mut Config, data_dir: &Path, id: &str) -> Result<(), Error> {
+    let Some(id) = find(config, id).map(|background| background.id.clone()) else {
+        return Err(Error::new(
+            ErrorCode::InvalidArgument,
+            format!("Failed to find a custom background with id [[{}]]", id),
+        ));
+    };
+
+    remove_images(data_dir, &id, None);
+    config
+        .custom_backgrounds
+        .retain(|background| background.id != id);
+
+    Ok(())
+}
+
+/// Build the URL the UI uses to load a preview image through [`IMAGE_PROTOCOL`].
+///
+/// The URL changes whenever the image is replaced, so the webview does not show a cached one.
+fn image_url(file_name: &str) -> String {
+    let version = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or_default();
+    if cfg!(windows) {
+        format!(
+            "http://{}.localhost/{}?v={}",
+            IMAGE_PROTOCOL, file_name, version
+        )
+    } else {
+        format!("{}://localhost/{}?v={}", IMAGE_PROTOCOL, file_name, version)
+    }
+}
+
+/// Get the path and content type of a preview image file name, e.g. `0x0800000000000E77.png`.
+fn image_path(data_dir: &Path, file_name: &str) -> Option<(PathBuf, &'static str)> {
+    // Only accept ids with a known extension to avoid reading anything outside the directory
+    let (id, extension) = file_name.rsplit_once('.')?;
+    let (_, content_type) = IMAGE_TYPES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(extension))?;
+    if !backgrounds::is_valid_id(id) {
+        return None;
+    }
+
+    Some((
+        data_dir
+            .join(IMAGE_DIR)
+            .join(format!("{}.{}", id, extension.to_ascii_lowercase())),
+        content_type,
+    ))
+}
+
+/// Copy a preview image into the data directory and return its URL.
+fn store_image(data_dir: &Path, id: &str, source: &Path) -> Result<String, Error> {
+    let extension = source
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let file_name = format!("{}.{}", id, extension);
+    let Some((path, _)) = image_path(data_dir, &file_name) else {
+        return Err(Error::new(
+            ErrorCode::InvalidArgument,
+            format!("[[{}]] is not a supported preview image", source.display()),
+        )
+        .with_path(source)
+        .with_hint("Use a PNG, JPEG, WebP or GIF image."));
+    };
+
+    let copy = || -> std::io::Result<()> {
+        fs::create_dir_all(data_dir.join(IMAGE_DIR))?;
+        fs::write(&path, fs::read(source)?)?;
+        // Only drop the previous preview once the new one is in place
+        remove_images(data_dir, id, Some(&extension));
+        Ok(())
+    };
+    copy().map_err(|e| {
+        Error::new(
+            ErrorCode::Io,
+            format!("Failed to copy the preview image [[{}]]", source.display()),
+        )
+        .with_path(source)
+        .with_io(&e)
+    })?;
+
+    Ok(image_url(&file_name))
+}
+
+/// Remove the preview images of a background, except the one with the `keep` extension.
+fn remove_images(data_dir: &Path, id: &str, keep: Option<&str>) {
+    for (extension, _) in IMAGE_TYPES {
+        if keep == Some(extension) {
+            continue;
+        }
+        fs::remove_file(
+            data_dir
+                .join(IMAGE_DIR)
+                .join(format!("{}.{}", id, extension)),
+        )
+        .ok();
+    }
+}
+
+/// Read a preview image along with its content type.
+pub fn read_image(data_dir: &Path, file_name: &str) -> Option<(Vec<u8>, &'static str)> {
+    let (path, content_type) = image_path(data_dir, file_name)?;
+
+    fs::read(path).ok().map(|image| (image, content_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+
+    fn data_dir(name: &str) -> PathBuf {
+        let data_dir = std::env::temp_dir().join(format!(
+            "overbuddy-custom-backgrounds-{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::remove_dir_all(&data_dir).ok();
+        fs::create_dir_all(&data_dir).unwrap();
+        data_dir
+    }
+
+    fn custom(id: &str, name: &str, tags: &[&str]) -> CustomBackground {
+        CustomBackground {
+            id: id.to_string(),
+            name: name.to_string(),
+            notes: None,
+            image: None,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    fn images(data_dir: &Path) -> Vec<String> {
+        let mut images: Vec<String> = fs::read_dir(data_dir.join(IMAGE_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        images.sort();
+        images
+    }
+
+    #[test]
+    fn normalizes_saved_backgrounds() {
+        let data_dir = data_dir("normalize");
+        let mut config = config::get_default_config();
+
+        let tags = [" night ", "night", "", "  ", "Night", "snow"];
+        save(&mut config, &data_dir, custom("e77", "  ", &tags), None).unwrap();
+        let background = &config.custom_backgrounds[0];
+        assert_eq!(background.id, "0x0800000000000E77");
+        assert_eq!(background.name, "0x0800000000000E77");
+        assert_eq!(background.tags, ["night", "Night", "snow"]);
+
+        // Saving the same id again replaces the background
+        save(
+            &mut config,
+            &data_dir,
+            custom("0xE77", " Night ", &[]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(config.custom_backgrounds.len(), 1);
+        assert_eq!(config.custom_backgrounds[0].name, "Night");
+    }
+
+    #[test]
+    fn replaces_preview_images() {
+        let data_dir = data_dir("images");
+        let mut config = config::get_default_config();
+        let png = data_dir.join("preview.PNG");
+        let jpg = data_dir.join("preview.jpg");
+        fs::write(&png, "png").unwrap();
+        fs::write(&jpg, "jpg").unwrap();
+
+        save(&mut config, &data_dir, custom("e77", "", &[]), Some(&png)).unwrap();
+        assert_eq!(images(&data_dir), ["0x0800000000000E77.png"]);
+        assert!(config.custom_backgrounds[0].image.is_some());
+
+        // Saving without an image keeps the preview
+        save(&mut config, &data_dir, custom("e77", "", &[]), None).unwrap();
+        assert!(config.custom_backgrounds[0].image.is_some());
+
+        save(&mut config, &data_dir, custom("e77", "", &[]), Some(&jpg)).unwrap();
+        assert_eq!(images(&data_dir), ["0x0800000000000E77.jpg"]);
+        let (image, content_type) = read_image(&data_dir, "0x0800000000000E77.jpg").unwrap();
+        assert_eq!(image, b"jpg");
+        assert_eq!(content_type, "image/jpeg");
+
+        delete(&mut config, &data_dir, "0x0800000000000e77").unwrap();
+        assert!(config.custom_backgrounds.is_empty());
+        assert!(images(&data_dir).is_empty());
+    }
+
+    #[test]
+    fn keeps_preview_when_copy_fails() {
+        let data_dir = data_dir("copy-fails");
+        let mut config = config::get_default_config();
+        let png = data_dir.join("preview.png");
+        fs::write(&png, "png").unwrap();
+        save(&mut config, &data_dir, custom("e77", "", &[]), Some(&png)).unwrap();
+
+        let missing = data_dir.join("missing.jpg");
+        let error = save(
+            &mut config,
+            &data_dir,
+            custom("e77", "", &[]),
+            Some(&missing),
+        );
+        assert_eq!(error.err().unwrap().code, ErrorCode::Io);
+        assert_eq!(images(&data_dir), ["0x0800000000000E77.png"]);
+
+        let text = data_dir.join("preview.txt");
+        let error = save(&mut config, &data_dir, custom("e77", "", &[]), Some(&text));
+        assert_eq!(error.err().unwrap().code, ErrorCode::InvalidArgument);
+    }
+
+    #[test]
+    fn reads_only_images_of_valid_ids() {
+        let data_dir = data_dir("read");
+        fs::create_dir_all(data_dir.join(IMAGE_DIR)).unwrap();
+        fs::write(
+            data_dir.join(IMAGE_DIR).join("0x0800000000000E77.png"),
+            "png",
+        )
+        .unwrap();
+
+        assert!(read_image(&data_dir, "0x0800000000000E77.PNG").is_some());
+        assert!(read_image(&data_dir, "0x0800000000000E77.txt").is_none());
+        assert!(read_image(&data_dir, "../0x0800000000000E77.png").is_none());
+        assert!(read_image(&data_dir, "0x0800000000000E77").is_none());
+    }
+}
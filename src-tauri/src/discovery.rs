@@ -0,0 +1,601 @@
+use crate::platforms::battle_net;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+pub enum PrefixSource {
+    Native,
+    Wine,
+    Lutris,
+    Bottles,
+    Heroic,
+}
+
+/// The Wine build a Wine frontend runs a prefix with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WineRunner {
+    /// A Wine binary, e.g. one of the Wine builds Lutris or Heroic download.
+    Wine { path: String },
+    /// A Proton script, as used by Heroic. Proton prefixes live in a `pfx` directory.
+    Proton { path: String },
+    /// A bottle, started through `bottles-cli` so Bottles picks its runner.
+    Bottles { bottle: String, flatpak: bool },
+}
+
+#[derive(Serialize, Clone)]
+pub struct BattleNetCandidate {
+    pub source: PrefixSource,
+    pub prefix: Option<String>,
+    pub runner: Option<WineRunner>,
+    pub install: Option<String>,
+    pub config: Option<String>,
+}
+
+static LAUNCHER_PATH: [&str; 2] = ["Battle.net", "Battle.net Launcher.exe"];
+
+/// Find every Battle.net installation on this machine, including those in Wine prefixes.
+///
+/// Candidates without a launcher or config are still returned so the user can resolve them.
+pub fn find_battle_net_candidates() -> Vec<BattleNetCandidate> {
+    let mut candidates = vec![];
+
+    if let Some(candidate) = find_native_battle_net() {
+        candidates.push(candidate);
+    }
+
+    if cfg!(windows) {
+        return candidates;
+    }
+    let Some(home) = env::var_os("HOME").map(PathBuf::from) else {
+        return candidates;
+    };
+    let wine_prefix = env::var_os("WINEPREFIX").map(PathBuf::from);
+    candidates.extend(find_prefix_battle_nets(&home, wine_prefix));
+
+    candidates
+}
+
+/// Find the Battle.net installations in the Wine prefixes of a home directory.
+fn find_prefix_battle_nets(home: &Path, wine_prefix: Option<PathBuf>) -> Vec<BattleNetCandidate> {
+    get_wine_prefixes(home, wine_prefix)
+        .into_iter()
+        .filter_map(|(source, prefix)| find_prefix_battle_net(home, source, &prefix))
+        .collect()
+}
+
+/// Find a Battle.net candidate by its prefix, or the native installation if no prefix is given.
+pub fn find_battle_net_candidate(prefix: Option<&str>) -> Option<BattleNetCandidate> {
+    find_battle_net_candidates()
+        .into_iter()
+        .find(|candidate| candidate.prefix.as_deref() == prefix)
+}
+
+/// Get the Wine prefix that contains a path, if any.
+pub fn get_prefix_from_path(path: &str) -> Option<String> {
+    Path::new(path)
+        .ancestors()
+        .find(|ancestor| ancestor.file_name().is_some_and(|name| name == "drive_c"))
+        .and_then(Path::parent)
+        .map(|prefix| prefix.to_string_lossy().to_string())
+}
+
+fn find_native_battle_net() -> Option<BattleNetCandidate> {
+    let install = env::var_os("programfiles(x86)")
+        .map(|program_files_dir| {
+            PathBuf::from(program_files_dir)
+                .join(LAUNCHER_PATH[0])
+                .join(LAUNCHER_PATH[1])
+        })
+        .filter(|install| install.exists());
+    let config = env::var_os("appdata")
+        .map(|appdata| {
+            PathBuf::from(appdata)
+                .join("Battle.net")
+                .join(battle_net::CONFIG_FILE)
+        })
+        .filter(|config| config.exists());
+
+    if install.is_none() && config.is_none() {
+        return None;
+    }
+
+    Some(BattleNetCandidate {
+        source: PrefixSource::Native,
+        prefix: None,
+        runner: None,
+        install: install.map(|path| path.to_string_lossy().to_string()),
+        config: config.map(|path| path.to_string_lossy().to_string()),
+    })
+}
+
+fn find_prefix_battle_net(
+    home: &Path,
+    source: PrefixSource,
+    prefix: &Path,
+) -> Option<BattleNetCandidate> {
+    let drive_c = prefix.join("drive_c");
+    if !drive_c.is_dir() {
+        return None;
+    }
+
+    let install = ["Program Files (x86)", "Program Files"]
+        .iter()
+        .map(|program_files_dir| {
+            drive_c
+                .join(program_files_dir)
+                .join(LAUNCHER_PATH[0])
+                .join(LAUNCHER_PATH[1])
+        })
+        .find(|install| install.exists());
+
+    // drive_c/users/<user>/AppData/Roaming/Battle.net/Battle.net.config
+    let config = fs::read_dir(drive_c.join("users"))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| {
+            entry
+                .path()
+                .join("AppData")
+                .join("Roaming")
+                .join("Battle.net")
+                .join(battle_net::CONFIG_FILE)
+        })
+        .find(|config| config.exists());
+
+    if install.is_none() && config.is_none() {
+        return None;
+    }
+
+    Some(BattleNetCandidate {
+        source,
+        prefix: Some(prefix.to_string_lossy().to_string()),
+        runner: get_runner_in(home, prefix),
+        install: install.map(|path| path.to_string_lossy().to_string()),
+        config: config.map(|path| path.to_string_lossy().to_string()),
+    })
+}
+
+/// List the Wine prefixes created by common Wine frontends in a home directory, after
+/// the prefix set in `WINEPREFIX`.
+fn get_wine_prefixes(home: &Path, wine_prefix: Option<PathBuf>) -> Vec<(PrefixSource, PathBuf)> {
+    let list_dirs = |path: PathBuf| -> Vec<PathBuf> {
+        fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect()
+    };
+
+    let mut prefixes = vec![];
+
+    // Plain Wine
+    if let Some(wine_prefix) = wine_prefix {
+        prefixes.push((PrefixSource::Wine, wine_prefix));
+    }
+    prefixes.push((PrefixSource::Wine, home.join(".wine")));
+
+    // Heroic, checked before Lutris since it also lives in ~/Games
+    let heroic_prefixes = home.join("Games").join("Heroic").join("Prefixes");
+    for prefix in list_dirs(heroic_prefixes.clone())
+        .into_iter()
+        .chain(list_dirs(heroic_prefixes.join("default")))
+    {
+        // Proton keeps the Wine prefix in a subdirectory
+        let proton_prefix = prefix.join("pfx");
+        if proton_prefix.is_dir() {
+            prefixes.push((PrefixSource::Heroic, proton_prefix));
+        }
+        prefixes.push((PrefixSource::Heroic, prefix));
+    }
+
+    // Lutris
+    for prefix in list_dirs(home.join("Games")) {
+        prefixes.push((PrefixSource::Lutris, prefix));
+    }
+
+    // Bottles (native and Flatpak)
+    for bottles in [
+        home.join(".local")
+            .join("share")
+            .join("bottles")
+            .join("bottles"),
+        home.join(".var")
+            .join("app")
+            .join("com.usebottles.bottles")
+            .join("data")
+            .join("bottles")
+            .join("bottles"),
+    ] {
+        for prefix in list_dirs(bottles) {
+            prefixes.push((PrefixSource::Bottles, prefix));
+        }
+    }
+
+    // Remove duplicates, keeping the first (most specific) source
+    let mut seen: Vec<PathBuf> = vec![];
+    prefixes.retain(|(_, prefix)| {
+        let canonical = dunce::canonicalize(prefix).unwrap_or_else(|_| prefix.clone());
+        if seen.contains(&canonical) {
+            return false;
+        }
+        seen.push(canonical);
+        true
+    });
+
+    prefixes
+}
+
+fn is_same_path(a: &Path, b: &Path) -> bool {
+    let canonical = |path: &Path| dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    canonical(a) == canonical(b)
+}
+
+/// Find the runner the Wine frontend that created a prefix uses for it. Prefixes
+/// without a known frontend are run with the `wine` on the path.
+pub fn get_runner(prefix: &Path) -> Option<WineRunner> {
+    match env::var_os("HOME") {
+        Some(home) => get_runner_in(Path::new(&home), prefix),
+        None => get_bottles_runner(prefix),
+    }
+}
+
+/// Find the runner of a prefix, with the frontend configurations in a home directory.
+fn get_runner_in(home: &Path, prefix: &Path) -> Option<WineRunner> {
+    get_bottles_runner(prefix)
+        .or_else(|| get_heroic_runner(home, prefix))
+        .or_else(|| get_lutris_runner(home, prefix))
+}
+
+/// Bottles live in `.../bottles/bottles/<bottle>` and are named in their `bottle.yml`.
+fn get_bottles_runner(prefix: &Path) -> Option<WineRunner> {
+    let parent = prefix.parent()?;
+    if !parent.ends_with(Path::new("bottles").join("bottles")) {
+        return None;
+    }
+
+    let name = fs::read_to_string(prefix.join("bottle.yml"))
+        .ok()
+        .and_then(|contents| {
+            contents.lines().find_map(|line| {
+                line.strip_prefix("Name:")
+                    .map(|name| name.trim().trim_matches(['"', '\'']).to_string())
+            })
+        })
+        .filter(|name| !name.is_empty());
+    let bottle = match name {
+        Some(name) => name,
+        None => prefix.file_name()?.to_string_lossy().to_string(),
+    };
+
+    Some(WineRunner::Bottles {
+        bottle,
+        flatpak: prefix
+            .components()
+            .any(|component| component.as_os_str() == "com.usebottles.bottles"),
+    })
+}
+
+/// Heroic stores the Wine version of every game in `GamesConfig/<app>.json`.
+fn get_heroic_runner(home: &Path, prefix: &Path) -> Option<WineRunner> {
+    let config_dirs = [
+        home.join(".config").join("heroic"),
+        home.join(".var")
+            .join("app")
+            .join("com.heroicgameslauncher.hgl")
+            .join("config")
+            .join("heroic"),
+    ];
+
+    config_dirs
+        .iter()
+        .flat_map(|dir| fs::read_dir(dir.join("GamesConfig")).into_iter().flatten())
+        .filter_map(Result::ok)
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .filter_map(|json| json.as_object().cloned())
+        .flat_map(|games| games.into_iter().map(|(_, game)| game))
+        .find_map(|game| {
+            let wine_prefix = Path::new(game.get("winePrefix")?.as_str()?);
+            let version = game.get("wineVersion")?;
+            let path = version.get("bin")?.as_str()?.to_string();
+            match version.get("type").and_then(|kind| kind.as_str()) {
+                Some("proton") if is_same_path(&wine_prefix.join("pfx"), prefix) => {
+                    Some(WineRunner::Proton { path })
+                }
+                Some("wine") if is_same_path(wine_prefix, prefix) => {
+                    Some(WineRunner::Wine { path })
+                }
+                _ => None,
+            }
+        })
+}
+
+/// Lutris stores every game in `games/<slug>.yml`, with the prefix under `game` and the
+/// Wine version under `wine`.
+fn get_lutris_runner(home: &Path, prefix: &Path) -> Option<WineRunner> {
+    let flatpak = home.join(".var").join("app").join("net.lutris.Lutris");
+    let data_dirs = [
+        home.join(".local").join("share").join("lutris"),
+        flatpak.join("data").join("lutris"),
+    ];
+    let config_dirs = [
+        home.join(".config").join("lutris"),
+        flatpak.join("config").join("lutris"),
+    ];
+
+    let value = |line: &str, key: &str| -> Option<String> {
+        line.trim()
+            .strip_prefix(key)
+            .map(|value| value.trim().trim_matches(['"', '\'']).to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let version = config_dirs
+        .iter()
+        .chain(&data_dirs)
+        .flat_map(|dir| fs::read_dir(dir.join("games")).into_iter().flatten())
+        .filter_map(Result::ok)
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .find_map(|contents| {
+            let game_prefix = contents.lines().find_map(|line| value(line, "prefix:"))?;
+            if !is_same_path(Path::new(&game_prefix), prefix) {
+                return None;
+            }
+            Some(contents.lines().find_map(|line| value(line, "version:")))
+        })??;
+
+    data_dirs
+        .iter()
+        .map(|dir| {
+            dir.join("runners")
+                .join("wine")
+                .join(&version)
+                .join("bin")
+                .join("wine")
+        })
+        .find(|wine| wine.exists())
+        .map(|wine| WineRunner::Wine {
+            path: wine.to_string_lossy().to_string(),
+        })
+}
+
+/// Build the command that runs a Windows program in a Wine prefix.
+pub fn wine_command(runner: Option<&WineRunner>, prefix: &str, program: &str) -> Command {
+    match runner {
+        Some(WineRunner::Wine { path }) => {
+            let mut command = Command::new(path);
+            command.env("WINEPREFIX", prefix).arg(program);
+            command
+        }
+        Some(WineRunner::Proton { path }) => {
+            // Proton expects the directory that contains the `pfx` prefix
+            let compat_data = Path::new(prefix).parent().unwrap_or(Path::new(prefix));
+            let steam = env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".steam").join("steam"))
+                .unwrap_or_default();
+            let mut command = Command::new(path);
+            command
+                .env("STEAM_COMPAT_DATA_PATH", compat_data)
+                .env("STEAM_COMPAT_CLIENT_INSTALL_PATH", steam)
+                .arg("run")
+                .arg(program);
+            command
+        }
+        Some(WineRunner::Bottles { bottle, flatpak }) => {
+            let mut command = if *flatpak {
+                let mut command = Command::new("flatpak");
+                command.args(["run", "--command=bottles-cli", "com.usebottles.bottles"]);
+                command
+            } else {
+                Command::new("bottles-cli")
+            };
+            command.args(["run", "-b", bottle, "-e", program]);
+            command
+        }
+        None => {
+            let mut command = Command::new("wine");
+            command.env("WINEPREFIX", prefix).arg(program);
+            command
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty home directory for a test.
+    fn home_dir(name: &str) -> PathBuf {
+        let home = env::temp_dir().join(format!(
+            "overbuddy-discovery-{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::remove_dir_all(&home).ok();
+        fs::create_dir_all(&home).unwrap();
+
+        home
+    }
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Install Battle.net with its config in a prefix.
+    fn install_battle_net(prefix: &Path) -> (PathBuf, PathBuf) {
+        let drive_c = prefix.join("drive_c");
+        let install = drive_c
+            .join("Program Files (x86)")
+            .join(LAUNCHER_PATH[0])
+            .join(LAUNCHER_PATH[1]);
+        let config = drive_c
+            .join("users")
+            .join("steamuser")
+            .join("AppData")
+            .join("Roaming")
+            .join("Battle.net")
+            .join(battle_net::CONFIG_FILE);
+        write(&install, "");
+        write(&config, "{}");
+
+        (install, config)
+    }
+
+    fn find(home: &Path, prefix: &Path) -> BattleNetCandidate {
+        find_prefix_battle_nets(home, None)
+            .into_iter()
+            .find(|candidate| candidate.prefix.as_deref() == Some(&*prefix.to_string_lossy()))
+            .unwrap()
+    }
+
+    fn to_string(path: &Path) -> Option<String> {
+        Some(path.to_string_lossy().to_string())
+    }
+
+    #[test]
+    fn finds_battle_net_in_plain_wine_prefix() {
+        let home = home_dir("wine");
+        let prefix = home.join(".wine");
+        let (install, config) = install_battle_net(&prefix);
+
+        let candidate = find(&home, &prefix);
+        assert!(candidate.source == PrefixSource::Wine);
+        assert_eq!(candidate.install, to_string(&install));
+        assert_eq!(candidate.config, to_string(&config));
+        assert_eq!(candidate.runner, None);
+    }
+
+    #[test]
+    fn finds_battle_net_in_wineprefix_first() {
+        let home = home_dir("wineprefix");
+        let prefix = home.join("custom");
+        install_battle_net(&prefix);
+        install_battle_net(&home.join(".wine"));
+
+        let candidates = find_prefix_battle_nets(&home, Some(prefix.clone()));
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].prefix, to_string(&prefix));
+    }
+
+    #[test]
+    fn finds_battle_net_in_lutris_prefix_with_its_wine() {
+        let home = home_dir("lutris");
+        let prefix = home.join("Games").join("battlenet");
+        let (install, _) = install_battle_net(&prefix);
+        write(
+            &home.join(".config/lutris/games/battlenet-1700000000.yml"),
+            &format!(
+                "game:\n  exe: {}\n  prefix: {}\nwine:\n  version: wine-ge-8-26-x86_64\n",
+                install.display(),
+                prefix.display()
+            ),
+        );
+        let wine = home.join(".local/share/lutris/runners/wine/wine-ge-8-26-x86_64/bin/wine");
+        write(&wine, "");
+
+        let candidate = find(&home, &prefix);
+        assert!(candidate.source == PrefixSource::Lutris);
+        assert_eq!(candidate.install, to_string(&install));
+        assert_eq!(
+            candidate.runner,
+            Some(WineRunner::Wine {
+                path: wine.to_string_lossy().to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn finds_battle_net_in_bottles() {
+        let home = home_dir("bottles");
+        let prefix = home.join(".local/share/bottles/bottles/Battle-net");
+        install_battle_net(&prefix);
+        write(
+            &prefix.join("bottle.yml"),
+            "Arch: win64\nName: 'Battle.net'\n",
+        );
+        let flatpak_prefix =
+            home.join(".var/app/com.usebottles.bottles/data/bottles/bottles/Gaming");
+        install_battle_net(&flatpak_prefix);
+
+        let candidate = find(&home, &prefix);
+        assert!(candidate.source == PrefixSource::Bottles);
+        assert_eq!(
+            candidate.runner,
+            Some(WineRunner::Bottles {
+                bottle: "Battle.net".to_string(),
+                flatpak: false
+            })
+        );
+
+        // Bottles without a bottle.yml are named after their directory
+        let candidate = find(&home, &flatpak_prefix);
+        assert_eq!(
+            candidate.runner,
+            Some(WineRunner::Bottles {
+                bottle: "Gaming".to_string(),
+                flatpak: true
+            })
+        );
+    }
+
+    #[test]
+    fn finds_battle_net_in_heroic_proton_prefix() {
+        let home = home_dir("heroic");
+        let game_prefix = home.join("Games/Heroic/Prefixes/default/Battle.net");
+        let prefix = game_prefix.join("pfx");
+        let (_, config) = install_battle_net(&prefix);
+        let proton = home.join(".steam/steam/steamapps/common/Proton - Experimental/proton");
+        write(
+            &home.join(".config/heroic/GamesConfig/battlenet.json"),
+            &serde_json::json!({
+                "battlenet": {
+                    "winePrefix": game_prefix,
+                    "wineVersion": { "bin": proton, "type": "proton" }
+                }
+            })
+            .to_string(),
+        );
+
+        let candidate = find(&home, &prefix);
+        assert!(candidate.source == PrefixSource::Heroic);
+        assert_eq!(candidate.config, to_string(&config));
+        assert_eq!(
+            candidate.runner,
+            Some(WineRunner::Proton {
+                path: proton.to_string_lossy().to_string()
+            })
+        );
+        // The directory that holds the Proton prefix is not a prefix itself
+        assert!(
+            find_prefix_battle_nets(&home, None)
+                .iter()
+                .all(|candidate| candidate.prefix != to_string(&game_prefix))
+        );
+    }
+
+    #[test]
+    fn skips_prefixes_without_battle_net() {
+        let home = home_dir("empty");
+        fs::create_dir_all(home.join(".wine/drive_c")).unwrap();
+        fs::create_dir_all(home.join("Games/other/drive_c/users")).unwrap();
+
+        assert!(find_prefix_battle_nets(&home, None).is_empty());
+    }
+
+    #[test]
+    fn gets_prefix_from_path() {
+        assert_eq!(
+            get_prefix_from_path("/home/a/.wine/drive_c/Program Files/Battle.net/x.exe"),
+            Some("/home/a/.wine".to_string())
+        );
+        assert_eq!(get_prefix_from_path("/home/a/Battle.net/x.exe"), None);
+    }
+}
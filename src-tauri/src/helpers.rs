@@ -1,41 +1,238 @@
+use crate::launch_args::LaunchArgs;
 use serde::Serialize;
 use serde_json::{Serializer, Value, from_reader};
 use std::fs;
-use std::io;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 // Global helpers
 
-#[derive(Debug, thiserror::Error)]
-pub enum Error {
-    #[error(transparent)]
-    Io(#[from] std::io::Error),
+/// Stable error codes, which the frontend uses to decide how to handle an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ErrorCode {
+    Io,
+    Json,
+    Vdf,
+    /// The OverBuddy config could not be read or written.
+    Config,
+    /// None of the requested platforms could be set up.
+    NotSetup,
+    /// A value passed to a command is invalid.
+    InvalidArgument,
+    /// The background catalog or its override file is invalid.
+    Catalog,
+    /// A request to a remote server failed, e.g. while offline.
+    Network,
+    NoOverwatch,
+    BattleNetInstall,
+    BattleNetConfig,
+    BattleNetPrefix,
+    SteamInstall,
+    SteamAccount,
+    SteamConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Platform {
+    BattleNet,
+    Steam,
+}
+
+/// An error that is returned to the frontend as an object.
+///
+/// Messages may mark values such as paths and file names with `[[` and `]]`. How they
+/// are displayed is up to the renderer, see [`render_plain`] and [`render_segments`].
+#[derive(Debug)]
+pub struct Error {
+    pub code: ErrorCode,
+    pub message: String,
+    pub platform: Option<Platform>,
+    pub path: Option<String>,
+    pub io_kind: Option<io::ErrorKind>,
+    /// Suggestions the user can follow to resolve the error.
+    pub hints: Vec<String>,
+    /// Platforms that were being set up, so setup can be retried once the error is resolved.
+    pub platforms: Option<Vec<String>>,
+}
+
+impl Error {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Error {
+            code,
+            message: message.into(),
+            platform: None,
+            path: None,
+            io_kind: None,
+            hints: vec![],
+            platforms: None,
+        }
+    }
+
+    pub fn with_platform(mut self, platform: Platform) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.path = Some(path.as_ref().display().to_string());
+        self
+    }
+
+    /// Record the kind of the underlying io error, with a hint for common causes.
+    pub fn with_io(mut self, err: &io::Error) -> Self {
+        self.io_kind = Some(err.kind());
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => {
+                self.hints.push(
+                    "Make sure OverBuddy has permission to access this file. Running it as administrator may help.".to_string(),
+                );
+            }
+            io::ErrorKind::NotFound => {
+                self.hints.push(
+                    "The file may have been moved or deleted. Resetting OverBuddy will search for it again."
+                        .to_string(),
+                );
+            }
+            _ => {}
+        }
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hints.push(hint.into());
+        self
+    }
+
+    pub fn with_platforms(mut self, platforms: &[&str]) -> Self {
+        self.platforms = Some(platforms.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
+    /// Add context on a new line, e.g. a description of a rollback.
+    pub fn with_detail(mut self, detail: impl AsRef<str>) -> Self {
+        self.message = format!("{}\n{}", self.message, detail.as_ref());
+        self
+    }
+
+    /// Prefix the message, keeping the rest of the error as is.
+    pub fn with_context(mut self, context: impl AsRef<str>) -> Self {
+        self.message = format!("{}: {}", context.as_ref(), self.message);
+        self
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&render_plain(&self.message))
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::new(ErrorCode::Io, err.to_string()).with_io(&err)
+    }
+}
 
-    #[error(transparent)]
-    Json(#[from] serde_json::Error),
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        let error = Error::new(ErrorCode::Json, err.to_string());
+        match err.io_error_kind() {
+            Some(kind) => error.with_io(&io::Error::from(kind)),
+            None => error,
+        }
+    }
+}
 
-    #[error("{0}")]
-    Custom(String),
+impl From<crate::vdf::ParseError> for Error {
+    fn from(err: crate::vdf::ParseError) -> Self {
+        Error::new(ErrorCode::Vdf, err.to_string())
+    }
 }
 
-impl serde::Serialize for Error {
+impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::ser::Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Error", 8)?;
+        state.serialize_field("code", &self.code)?;
+        state.serialize_field("message", &render_plain(&self.message))?;
+        state.serialize_field("segments", &render_segments(&self.message))?;
+        state.serialize_field("platform", &self.platform)?;
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field("io_kind", &self.io_kind.map(|kind| format!("{:?}", kind)))?;
+        state.serialize_field("hints", &self.hints)?;
+        state.serialize_field("platforms", &self.platforms)?;
+        state.end()
     }
 }
 
+#[derive(Serialize, PartialEq, Debug)]
+pub struct Segment {
+    pub text: String,
+    /// Whether the text was marked with `[[ ]]`, e.g. a path or file name.
+    pub highlight: bool,
+}
+
+/// Render a message as plain text, quoting the marked values.
+pub fn render_plain(message: &str) -> String {
+    message.replace("[[", "\"").replace("]]", "\"")
+}
+
+/// Split a message into plain and marked segments.
+pub fn render_segments(message: &str) -> Vec<Segment> {
+    let mut segments = vec![];
+    let mut rest = message;
+
+    while let Some(start) = rest.find("[[") {
+        let Some(end) = rest[start + 2..].find("]]") else {
+            break;
+        };
+        if start > 0 {
+            segments.push(Segment {
+                text: rest[..start].to_string(),
+                highlight: false,
+            });
+        }
+        segments.push(Segment {
+            text: rest[start + 2..start + 2 + end].to_string(),
+            highlight: true,
+        });
+        rest = &rest[start + 2 + end + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment {
+            text: rest.to_string(),
+            highlight: false,
+        });
+    }
+
+    segments
+}
+
+/// Get the current Unix time in seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+}
+
 pub fn display_path_string(path: &PathBuf) -> Result<String, Error> {
     dunce::canonicalize(path)
         .map(|canonicalized| canonicalized.display().to_string())
         .map_err(|err| {
-            Error::Custom(format!(
-                "Error processing path [[{}]]: {:?}",
-                path.display(),
-                err
-            ))
+            Error::new(
+                ErrorCode::Io,
+                format!("Error processing path [[{}]]: {:?}", path.display(), err),
+            )
+            .with_path(path)
+            .with_io(&err)
         })
 }
 
@@ -43,26 +240,50 @@ pub fn get_file_name_from_path(path: &str) -> Option<&str> {
     Path::new(path).file_name().and_then(|name| name.to_str())
 }
 
+/// Serialize JSON the way it is written to launcher config files.
+pub fn to_pretty_json(json: &serde_json::Value) -> Result<String, Error> {
+    let mut contents = vec![];
+    let pretty_formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = Serializer::with_formatter(&mut contents, pretty_formatter);
+    json.serialize(&mut serializer)?;
+
+    Ok(String::from_utf8_lossy(&contents).to_string())
+}
+
 pub fn safe_json_write(path: String, json: &serde_json::Value) -> Result<(), Error> {
     // Create backup
     let backup_path = format!("{}.backup", path);
     if Path::new(&path).exists() {
         fs::copy(&path, &backup_path).map_err(|e| {
-            Error::Custom(format!(
-                "Failed to create backup of [[{}]]: {}",
-                get_file_name_from_path(&path).unwrap_or("unknown"),
-                e
-            ))
+            Error::new(
+                ErrorCode::Io,
+                format!(
+                    "Failed to create backup of [[{}]]: {}",
+                    get_file_name_from_path(&path).unwrap_or("unknown"),
+                    e
+                ),
+            )
+            .with_path(&path)
+            .with_io(&e)
         })?;
     }
 
     let cleanup = |replace: bool| -> Result<(), Error> {
         if Path::new(&backup_path).exists() {
             if replace {
-                fs::copy(&backup_path, &path).map_err(|_| {
-                    Error::Custom(format!(
-                        "Failed to restore backup of [[{}]]",
-                        get_file_name_from_path(&path).unwrap_or("unknown")
+                fs::copy(&backup_path, &path).map_err(|e| {
+                    Error::new(
+                        ErrorCode::Io,
+                        format!(
+                            "Failed to restore backup of [[{}]]",
+                            get_file_name_from_path(&path).unwrap_or("unknown")
+                        ),
+                    )
+                    .with_path(&path)
+                    .with_io(&e)
+                    .with_hint(format!(
+                        "A backup of the file was kept at [[{}]]",
+                        backup_path
                     ))
                 })?;
             }
@@ -78,36 +299,47 @@ pub fn safe_json_write(path: String, json: &serde_json::Value) -> Result<(), Err
         .open(&path)
     {
         Ok(file) => file,
-        Err(_) => {
+        Err(e) => {
             cleanup(false)?;
-            return Err(Error::Custom(format!(
-                "Failed to open [[{}]] file at [[{}]]",
-                get_file_name_from_path(&path).unwrap_or("unknown"),
-                path
-            )));
+            return Err(Error::new(
+                ErrorCode::Io,
+                format!(
+                    "Failed to open [[{}]] file at [[{}]]",
+                    get_file_name_from_path(&path).unwrap_or("unknown"),
+                    path
+                ),
+            )
+            .with_path(&path)
+            .with_io(&e));
         }
     };
-    let pretty_formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
-    let mut serializer = Serializer::with_formatter(&mut file, pretty_formatter);
-    match json.serialize(&mut serializer) {
+    match to_pretty_json(json).and_then(|contents| Ok(file.write_all(contents.as_bytes())?)) {
         Ok(_) => (),
         Err(_) => {
             cleanup(false)?;
-            return Err(Error::Custom(format!("Failed to write to [[{}]]", path)));
+            return Err(
+                Error::new(ErrorCode::Io, format!("Failed to write to [[{}]]", path))
+                    .with_path(&path),
+            );
         }
     }
 
     // Validate new config
     let file = match fs::File::open(&path) {
         Ok(file) => file,
-        Err(_) => {
+        Err(e) => {
             // Restore backup
             cleanup(true)?;
-            return Err(Error::Custom(format!(
-                "Failed to open [[{}]] file at [[{}]]",
-                get_file_name_from_path(&path).unwrap_or("unknown"),
-                path
-            )));
+            return Err(Error::new(
+                ErrorCode::Io,
+                format!(
+                    "Failed to open [[{}]] file at [[{}]]",
+                    get_file_name_from_path(&path).unwrap_or("unknown"),
+                    path
+                ),
+            )
+            .with_path(&path)
+            .with_io(&e));
         }
     };
 
@@ -121,10 +353,11 @@ pub fn safe_json_write(path: String, json: &serde_json::Value) -> Result<(), Err
         Err(_) => {
             // JSON is corrupted, restore the backup
             cleanup(true)?;
-            Err(Error::Io(std::io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Failed to write to [[{}]]", path),
-            )))
+            Err(
+                Error::new(ErrorCode::Io, format!("Failed to write to [[{}]]", path))
+                    .with_path(&path)
+                    .with_io(&io::Error::from(io::ErrorKind::InvalidData)),
+            )
         }
     }
 }
@@ -133,70 +366,31 @@ pub fn safe_json_write(path: String, json: &serde_json::Value) -> Result<(), Err
 
 /// Get the background id from the launch arguments.
 pub fn get_background(launch_args: &str) -> Option<String> {
-    launch_args
-        .split_whitespace()
-        .find(|s| s.starts_with(format!("{}=", BACKGROUND_LAUNCH_ARG).as_str()))
-        .and_then(|s| s.split('=').nth(1))
-        .and_then(|s| {
-            if s.is_empty() {
-                None
-            } else {
-                Some(String::from(s))
-            }
-        })
+    LaunchArgs::parse(launch_args)
+        .get(BACKGROUND_LAUNCH_ARG)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
 }
 
 /// Get the debug console state from the launch arguments.
 pub fn get_console_enabled(launch_args: &str) -> bool {
-    launch_args
-        .split_whitespace()
-        .any(|s| s == CONSOLE_LAUNCH_ARG)
+    LaunchArgs::parse(launch_args).contains(CONSOLE_LAUNCH_ARG)
 }
 
 const BACKGROUND_LAUNCH_ARG: &str = "--lobbyMap";
 /// Generate background launch arguments
 pub fn generate_background_launch_args(launch_args: Option<&str>, id: Option<&str>) -> String {
-    let new_arg = id.map(|id| format!("{}={}", BACKGROUND_LAUNCH_ARG, id));
-
-    let filtered_args = launch_args
-        .map(|args| {
-            args.split_whitespace()
-                .filter(|&part| !part.starts_with(BACKGROUND_LAUNCH_ARG))
-                .collect::<Vec<_>>()
-                .join(" ")
-        })
-        .unwrap_or_default();
+    let mut launch_args = LaunchArgs::parse(launch_args.unwrap_or_default());
+    launch_args.set_value(BACKGROUND_LAUNCH_ARG, id);
 
-    match (filtered_args.is_empty(), new_arg) {
-        (true, Some(arg)) => arg,
-        (false, Some(arg)) => format!("{} {}", filtered_args, arg),
-        (false, None) => filtered_args,
-        (true, None) => String::new(),
-    }
+    launch_args.to_string()
 }
 
 const CONSOLE_LAUNCH_ARG: &str = "--tank_Console";
 /// Generate debug console launch arguments
 pub fn generate_console_launch_args(launch_args: Option<&str>, enable_console: bool) -> String {
-    let new_arg = if enable_console {
-        Some(CONSOLE_LAUNCH_ARG.to_string())
-    } else {
-        None
-    };
-
-    let filtered_args = launch_args
-        .map(|args| {
-            args.split_whitespace()
-                .filter(|&part| part != CONSOLE_LAUNCH_ARG)
-                .collect::<Vec<_>>()
-                .join(" ")
-        })
-        .unwrap_or_default();
+    let mut launch_args = LaunchArgs::parse(launch_args.unwrap_or_default());
+    launch_args.set_flag(CONSOLE_LAUNCH_ARG, enable_console);
 
-    match (filtered_args.is_empty(), new_arg) {
-        (true, Some(arg)) => arg,
-        (false, Some(arg)) => format!("{} {}", filtered_args, arg),
-        (false, None) => filtered_args,
-        (true, None) => String::new(),
-    }
+    launch_args.to_string()
 }
@@ -0,0 +1,270 @@
+use crate::backgrounds;
+use crate::config::{Config, HistoryEntry};
+use crate::custom_backgrounds;
+use crate::helpers;
+use serde::Serialize;
+
+// Favorites and the history of applied backgrounds.
+
+/// Number of history entries to keep.
+const HISTORY_LIMIT: usize = 200;
+/// Number of distinct recently applied backgrounds used for sorting and the rotation.
+pub const RECENT_LIMIT: usize = 20;
+
+/// A background with its apply statistics.
+#[derive(Serialize)]
+pub struct BackgroundStats {
+    pub id: String,
+    pub name: String,
+    pub apply_count: u32,
+    /// Unix time in seconds.
+    pub last_applied: Option<u64>,
+    /// Whether the background can still be applied.
+    pub available: bool,
+}
+
+#[derive(Serialize)]
+pub struct HistoryItem {
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    pub available: bool,
+}
+
+#[derive(Serialize)]
+pub struct History {
+    pub favorites: Vec<BackgroundStats>,
+    /// Every background that was applied, most applied first.
+    pub backgrounds: Vec<BackgroundStats>,
+    pub entries: Vec<HistoryItem>,
+}
+
+fn is_available(config: &Config, id: &str) -> bool {
+    backgrounds::find_background_by_id(id).is_some()
+        || custom_backgrounds::find(config, id).is_some()
+}
+
+/// Get the name of a background from the catalog, the custom library or the history.
+fn name(config: &Config, id: &str) -> String {
+    backgrounds::find_background_by_id(id)
+        .map(|background| background.name)
+        .or_else(|| custom_backgrounds::find(config, id).map(|custom| custom.name.clone()))
+        .or_else(|| {
+            config
+                .history
+                .iter()
+                .find(|entry| entry.id == id)
+                .map(|entry| entry.name.clone())
+        })
+        .or_else(|| backgrounds::find_retired_background(id).map(|retired| retired.name))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Record that a background was applied.
+pub fn record(config: &mut Config, id: &str, is_custom: bool) {
+    let entry = HistoryEntry {
+        id: id.to_string(),
+        name: name(config, id),
+        applied_at: helpers::now(),
+        is_custom,
+    };
+
+    config.history.insert(0, entry);
+    config.history.truncate(HISTORY_LIMIT);
+    *config.apply_counts.entry(id.to_string()).or_default() += 1;
+}
+
+/// Ids of recently applied backgrounds without duplicates, most recent first.
+pub fn recent(config: &Config) -> Vec<String> {
+    let mut recent: Vec<String> = vec![];
+    for entry in &config.history {
+        if recent.len() == RECENT_LIMIT {
+            break;
+        }
+        if !recent.contains(&entry.id) {
+            recent.push(entry.id.clone());
+        }
+    }
+
+    recent
+}
+
+/// Find the background that was applied before the current one.
+pub fn previous(config: &Config) -> Option<&HistoryEntry> {
+    let current = config.shared.background.current.as_deref();
+
+    config
+        .history
+        .iter()
+        .find(|entry| Some(entry.id.as_str()) != current)
+}
+
+fn stats(config: &Config, id: &str) -> BackgroundStats {
+    BackgroundStats {
+        id: id.to_string(),
+        name: name(config, id),
+        apply_count: config.apply_counts.get(id).copied().unwrap_or_default(),
+        last_applied: config
+            .history
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.applied_at),
+        available: is_available(config, id),
+    }
+}
+
+pub fn get_history(config: &Config) -> History {
+    let mut backgrounds: Vec<BackgroundStats> = config
+        .apply_counts
+        .keys()
+        .map(|id| stats(config, id))
+        .collect();
+    backgrounds.sort_by_key(|background| std::cmp::Reverse(background.apply_count));
+
+    History {
+        favorites: config
+            .favorites
+            .iter()
+            .map(|id| stats(config, id))
+            .collect(),
+        backgrounds,
+        entries: config
+            .history
+            .iter()
+            .map(|entry| HistoryItem {
+                entry: entry.clone(),
+                available: is_available(config, &entry.id),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+
+    const GONE: &str = "0x0800000000000001";
+
+    fn ids(entries: &[HistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.id.as_str()).collect()
+    }
+
+    fn catalog_id(index: usize) -> String {
+        backgrounds::get_backgrounds()[index].id.clone()
+    }
+
+    #[test]
+    fn keeps_the_latest_entries_first() {
+        let mut config = config::get_default_config();
+        for index in 0..HISTORY_LIMIT + 10 {
+            record(&mut config, &format!("0x08000000000{:05X}", index), true);
+        }
+
+        assert_eq!(config.history.len(), HISTORY_LIMIT);
+        assert_eq!(config.history[0].id, "0x08000000000000D1");
+        assert_eq!(config.history[HISTORY_LIMIT - 1].id, "0x080000000000000A");
+        // Counts are kept for entries that dropped out of the history
+        assert_eq!(config.apply_counts.len(), HISTORY_LIMIT + 10);
+    }
+
+    #[test]
+    fn lists_distinct_recent_backgrounds() {
+        let mut config = config::get_default_config();
+        for id in ["a", "b", "a", "c", "c"] {
+            record(&mut config, id, true);
+        }
+        assert_eq!(ids(&config.history), ["c", "c", "a", "b", "a"]);
+        assert_eq!(recent(&config), ["c", "a", "b"]);
+
+        for index in 0..RECENT_LIMIT {
+            record(&mut config, &index.to_string(), true);
+        }
+        assert_eq!(recent(&config).len(), RECENT_LIMIT);
+        assert_eq!(recent(&config)[0], (RECENT_LIMIT - 1).to_string());
+    }
+
+    #[test]
+    fn finds_the_previous_background() {
+        let mut config = config::get_default_config();
+        assert!(previous(&config).is_none());
+
+        record(&mut config, "a", true);
+        record(&mut config, "b", true);
+        record(&mut config, "b", true);
+        config.shared.background.current = Some("b".to_string());
+        assert_eq!(previous(&config).unwrap().id, "a");
+
+        // After a reset the last applied background is the previous one
+        config.shared.background.current = None;
+        assert_eq!(previous(&config).unwrap().id, "b");
+
+        config.shared.background.current = Some("a".to_string());
+        assert_eq!(previous(&config).unwrap().id, "b");
+    }
+
+    #[test]
+    fn keeps_entries_of_removed_backgrounds() {
+        let mut config = config::get_default_config();
+        custom_backgrounds::remember(&mut config, GONE);
+        config.custom_backgrounds[0].name = "Gone".to_string();
+        record(&mut config, GONE, true);
+        record(&mut config, &catalog_id(0), false);
+        config.custom_backgrounds.clear();
+
+        let history = get_history(&config);
+        let entries: Vec<(&str, bool)> = history
+            .entries
+            .iter()
+            .map(|item| (item.entry.id.as_str(), item.available))
+            .collect();
+        assert_eq!(entries, [(catalog_id(0).as_str(), true), (GONE, false)]);
+
+        // The name is taken from the history once the background is gone
+        let gone = history.backgrounds.iter().find(|stats| stats.id == GONE);
+        assert_eq!(gone.unwrap().name, "Gone");
+        record(&mut config, GONE, true);
+        assert_eq!(config.history[0].name, "Gone");
+    }
+
+    #[test]
+    fn reports_favorites_with_their_stats() {
+        let mut config = config::get_default_config();
+        record(&mut config, &catalog_id(1), false);
+        record(&mut config, &catalog_id(0), false);
+        record(&mut config, &catalog_id(1), false);
+        config.favorites = vec![catalog_id(0), catalog_id(2), GONE.to_string()];
+
+        let history = get_history(&config);
+        let favorites: Vec<(&str, u32, bool, bool)> = history
+            .favorites
+            .iter()
+            .map(|stats| {
+                (
+                    stats.id.as_str(),
+                    stats.apply_count,
+                    stats.last_applied.is_some(),
+                    stats.available,
+                )
+            })
+            .collect();
+        assert_eq!(
+            favorites,
+            [
+                (catalog_id(0).as_str(), 1, true, true),
+                (catalog_id(2).as_str(), 0, false, true),
+                (GONE, 0, false, false),
+            ]
+        );
+
+        // Most applied first
+        let backgrounds: Vec<(&str, u32)> = history
+            .backgrounds
+            .iter()
+            .map(|stats| (stats.id.as_str(), stats.apply_count))
+            .collect();
+        assert_eq!(
+            backgrounds,
+            [(catalog_id(1).as_str(), 2), (catalog_id(0).as_str(), 1)]
+        );
+    }
+}
@@ -0,0 +1,430 @@
+use std::ops::Range;
+
+// Launch arguments, as entered in Battle.net and the Steam launch options.
+//
+// Arguments are split the way Windows command lines are (and Steam follows): whitespace
+// separates arguments unless it is inside double quotes, and backslashes only escape
+// quotes. The original text is kept and only the arguments that are changed are spliced,
+// so the spacing and quoting of everything else is written back byte-for-byte.
+
+/// Token in Steam launch options that is replaced with the game command.
+pub const STEAM_COMMAND_TOKEN: &str = "%command%";
+
+#[derive(Debug, Clone)]
+pub struct Arg {
+    /// The argument with quotes and escapes removed.
+    pub value: String,
+    /// Byte range of the raw argument, including its quotes.
+    pub span: Range<usize>,
+}
+
+impl Arg {
+    /// Whether the argument is the given flag, with or without a `=value`.
+    pub fn is_flag(&self, flag: &str) -> bool {
+        self.value == flag
+            || self
+                .value
+                .strip_prefix(flag)
+                .is_some_and(|rest| rest.starts_with('='))
+    }
+
+    /// Whether the argument sets an environment variable, e.g. `PROTON_LOG=1`.
+    fn is_environment(&self) -> bool {
+        self.value.split_once('=').is_some_and(|(name, _)| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LaunchArgs {
+    source: String,
+    args: Vec<Arg>,
+}
+
+impl LaunchArgs {
+    /// Parse launch arguments. Parsing never fails: an unterminated quote runs to the
+    /// end, like it does on Windows.
+    pub fn parse(source: impl Into<String>) -> LaunchArgs {
+        let source = source.into();
+        let args = split(&source);
+        LaunchArgs { source, args }
+    }
+
+    pub fn args(&self) -> &[Arg] {
+        &self.args
+    }
+
+    /// Whether the flag is present, with or without a value.
+    pub fn contains(&self, flag: &str) -> bool {
+        self.args.iter().any(|arg| arg.is_flag(flag))
+    }
+
+    /// Get the value of the first `flag=value` argument.
+    pub fn get(&self, flag: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .filter(|arg| arg.is_flag(flag))
+            .find_map(|arg| arg.value.split_once('=').map(|(_, value)| value))
+    }
+
+    /// Set a flag without a value, or remove it.
+    pub fn set_flag(&mut self, flag: &str, enabled: bool) {
+        self.replace(flag, enabled.then(|| flag.to_string()));
+    }
+
+    /// Set a `flag=value` argument, or remove the flag if there is no value.
+    pub fn set_value(&mut self, flag: &str, value: Option<&str>) {
+        self.replace(flag, value.map(|value| format!("{}={}", flag, value)));
+    }
+
+    /// Replace every occurrence of a flag with a single argument.
+    ///
+    /// The first occurrence is replaced in place when it reaches the game, otherwise the
+    /// argument is appended. Other arguments are not touched.
+    fn replace(&mut self, flag: &str, new_arg: Option<String>) {
+        let command = self
+            .args
+            .iter()
+            .position(|arg| arg.value == STEAM_COMMAND_TOKEN);
+        let in_place = self
+            .args
+            .iter()
+            .position(|arg| arg.is_flag(flag))
+            .filter(|&index| command.is_none_or(|command| index > command));
+
+        match (new_arg, in_place) {
+            (Some(new_arg), Some(index)) => {
+                let span = self.args[index].span.clone();
+                self.remove_where(|arg| arg.is_flag(flag) && arg.span.start > span.start);
+                self.splice(span, &quote(&new_arg));
+            }
+            (Some(new_arg), None) => {
+                self.remove_where(|arg| arg.is_flag(flag));
+                self.push(&new_arg);
+            }
+            (None, _) => self.remove_where(|arg| arg.is_flag(flag)),
+        }
+    }
+
+    /// Append an argument.
+    ///
+    /// Steam launch options may wrap the game with `%command%` (e.g. Proton environment
+    /// variables or `gamemoderun`). Only arguments after that token reach the game, so
+    /// new arguments are always placed after it.
+    pub fn push(&mut self, arg: &str) {
+        self.append(&quote(arg));
+    }
+
+    /// Insert `%command%` after the environment variables the launch options start with,
+    /// if they do not have the token yet. Steam passes launch options without the token to
+    /// the game as arguments, so the variables would not be set.
+    ///
+    /// Only meant for Steam launch options, other launchers do not know the token.
+    pub fn insert_command_token(&mut self) {
+        if self.args.iter().any(|arg| arg.value == STEAM_COMMAND_TOKEN) {
+            return;
+        }
+
+        let environment = self
+            .args
+            .iter()
+            .take_while(|arg| arg.is_environment())
+            .count();
+        if environment > 0 {
+            let end = self.args[environment - 1].span.end;
+            self.splice(end..end, &format!(" {}", STEAM_COMMAND_TOKEN));
+        }
+    }
+
+    fn append(&mut self, raw: &str) {
+        let end = self.source.len();
+        let separator = if self.source.is_empty() || self.source.ends_with(char::is_whitespace) {
+            ""
+        } else {
+            " "
+        };
+        self.splice(end..end, &format!("{}{}", separator, raw));
+    }
+
+    /// Remove the arguments that match, together with the whitespace before them (or
+    /// after them if they come first).
+    fn remove_where(&mut self, mut predicate: impl FnMut(&Arg) -> bool) {
+        let removed: Vec<usize> = (0..self.args.len())
+            .filter(|&index| predicate(&self.args[index]))
+            .collect();
+
+        for &index in removed.iter().rev() {
+            let span = &self.args[index].span;
+            let range = if index > 0 {
+                self.args[index - 1].span.end..span.end
+            } else if let Some(next) = self.args.get(index + 1) {
+                span.start..next.span.start
+            } else {
+                span.clone()
+            };
+            self.splice(range, "");
+        }
+    }
+
+    /// Replace a byte range of the source and parse the arguments again.
+    fn splice(&mut self, range: Range<usize>, text: &str) {
+        self.source.replace_range(range, text);
+        self.args = split(&self.source);
+    }
+}
+
+impl std::fmt::Display for LaunchArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Split a command line into arguments.
+///
+/// Follows the rules of `CommandLineToArgvW`: `2n` backslashes before a quote become `n`
+/// backslashes and the quote toggles quoting, `2n + 1` backslashes become `n`
+/// backslashes and a literal quote, and `""` inside quotes is a literal quote.
+fn split(source: &str) -> Vec<Arg> {
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let offset = |index: usize| chars.get(index).map_or(source.len(), |&(offset, _)| offset);
+    let mut args = vec![];
+    let mut i = 0;
+
+    loop {
+        while i < chars.len() && chars[i].1.is_whitespace() {
+            i += 1;
+        }
+        if i == chars.len() {
+            break;
+        }
+
+        let start = i;
+        let mut value = String::new();
+        let mut in_quotes = false;
+        while i < chars.len() {
+            let c = chars[i].1;
+            if c.is_whitespace() && !in_quotes {
+                break;
+            }
+
+            match c {
+                '\\' => {
+                    let count = chars[i..].iter().take_while(|&&(_, c)| c == '\\').count();
+                    i += count;
+                    if chars.get(i).is_some_and(|&(_, c)| c == '"') {
+                        value.extend(std::iter::repeat_n('\\', count / 2));
+                        if count % 2 == 1 {
+                            value.push('"');
+                            i += 1;
+                        }
+                    } else {
+                        value.extend(std::iter::repeat_n('\\', count));
+                    }
+                }
+                '"' => {
+                    if in_quotes && chars.get(i + 1).is_some_and(|&(_, c)| c == '"') {
+                        value.push('"');
+                        i += 2;
+                    } else {
+                        in_quotes = !in_quotes;
+                        i += 1;
+                    }
+                }
+                _ => {
+                    value.push(c);
+                    i += 1;
+                }
+            }
+        }
+
+        args.push(Arg {
+            value,
+            span: offset(start)..offset(i),
+        });
+    }
+
+    args
+}
+
+/// Quote an argument so it is split back into the same value.
+pub fn quote(value: &str) -> String {
+    if !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || c == '"') {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for c in value.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // Escape the backslashes before the quote and the quote itself
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                backslashes = 0;
+            }
+            _ => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes));
+                backslashes = 0;
+            }
+        }
+        if c != '\\' {
+            quoted.push(c);
+        }
+    }
+    // Backslashes before the closing quote must be escaped
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(source: &str) -> Vec<String> {
+        split(source).into_iter().map(|arg| arg.value).collect()
+    }
+
+    #[test]
+    fn splits_on_unquoted_whitespace() {
+        assert_eq!(values("  -a\t--b=1 \n -c  "), ["-a", "--b=1", "-c"]);
+        assert_eq!(
+            values(r#"--path="C:\Program Files\Game" -x"#),
+            [r"--path=C:\Program Files\Game", "-x"]
+        );
+        assert!(values("   ").is_empty());
+    }
+
+    #[test]
+    fn handles_backslashes_before_quotes() {
+        // 2n backslashes become n and the quote toggles quoting
+        assert_eq!(values(r#"a\\\\"b c""#), [r"a\\b c"]);
+        // 2n + 1 backslashes become n and a literal quote
+        assert_eq!(values(r#"a\\\"b c"#), [r#"a\"b"#, "c"]);
+        assert_eq!(values(r#"\"quoted\""#), [r#""quoted""#]);
+        // Backslashes that are not followed by a quote are literal
+        assert_eq!(values(r"a\\b\"), [r"a\\b\"]);
+        assert_eq!(
+            values(r#""C:\Program Files\\" -x"#),
+            [r"C:\Program Files\", "-x"]
+        );
+    }
+
+    #[test]
+    fn keeps_empty_quoted_args() {
+        assert_eq!(values(r#"a "" b"#), ["a", "", "b"]);
+        assert_eq!(values(r#""""#), [""]);
+        assert_eq!(values(r#""a""b""#), [r#"a"b"#]);
+        // An unterminated quote runs to the end
+        assert_eq!(values(r#"-a "b c"#), ["-a", "b c"]);
+    }
+
+    #[test]
+    fn splits_around_steam_command_token() {
+        let launch_args = LaunchArgs::parse("PROTON_LOG=1 gamemoderun %command% --lobbyMap=0x08");
+        let args: Vec<&str> = launch_args
+            .args()
+            .iter()
+            .map(|arg| arg.value.as_str())
+            .collect();
+        assert_eq!(
+            args,
+            [
+                "PROTON_LOG=1",
+                "gamemoderun",
+                STEAM_COMMAND_TOKEN,
+                "--lobbyMap=0x08"
+            ]
+        );
+        assert_eq!(launch_args.get("--lobbyMap"), Some("0x08"));
+    }
+
+    #[test]
+    fn quotes_values_that_split_back_the_same() {
+        for value in [
+            "plain",
+            "",
+            "a b",
+            r#"a"b"#,
+            r"C:\a b\",
+            r#"a\"b c"#,
+            r"a\\b",
+        ] {
+            assert_eq!(values(&quote(value)), [value], "{}", quote(value));
+        }
+        assert_eq!(quote("--lobbyMap=0x08"), "--lobbyMap=0x08");
+    }
+
+    #[test]
+    fn keeps_foreign_args_byte_for_byte() {
+        let source = "PROTON_LOG=1  \"%command%\"   -foo \"x  y\"\t--lobbyMap=A  -bar\\\\";
+
+        let mut launch_args = LaunchArgs::parse(source);
+        launch_args.set_value("--lobbyMap", Some("B"));
+        assert_eq!(
+            launch_args.to_string(),
+            "PROTON_LOG=1  \"%command%\"   -foo \"x  y\"\t--lobbyMap=B  -bar\\\\"
+        );
+
+        launch_args.set_flag("--tank_Console", true);
+        assert_eq!(
+            launch_args.to_string(),
+            "PROTON_LOG=1  \"%command%\"   -foo \"x  y\"\t--lobbyMap=B  -bar\\\\ --tank_Console"
+        );
+
+        launch_args.set_value("--lobbyMap", None);
+        launch_args.set_flag("--tank_Console", false);
+        assert_eq!(
+            launch_args.to_string(),
+            "PROTON_LOG=1  \"%command%\"   -foo \"x  y\"  -bar\\\\"
+        );
+    }
+
+    #[test]
+    fn replaces_duplicates_and_flags_before_the_command() {
+        let mut launch_args = LaunchArgs::parse("-x --lobbyMap=A -y --lobbyMap=B");
+        launch_args.set_value("--lobbyMap", Some("C"));
+        assert_eq!(launch_args.to_string(), "-x --lobbyMap=C -y");
+
+        // Arguments before %command% do not reach the game, so the flag moves after it
+        let mut launch_args = LaunchArgs::parse("--lobbyMap=A %command% -x");
+        launch_args.set_value("--lobbyMap", Some("B"));
+        assert_eq!(launch_args.to_string(), "%command% -x --lobbyMap=B");
+
+        let mut launch_args = LaunchArgs::parse("");
+        launch_args.set_value("--lobbyMap", Some("a b"));
+        assert_eq!(launch_args.to_string(), r#""--lobbyMap=a b""#);
+    }
+
+    #[test]
+    fn inserts_command_token_only_when_asked() {
+        let mut launch_args = LaunchArgs::parse("PROTON_LOG=1 DXVK_HUD=fps");
+        launch_args.set_value("--lobbyMap", Some("B"));
+        assert_eq!(
+            launch_args.to_string(),
+            "PROTON_LOG=1 DXVK_HUD=fps --lobbyMap=B"
+        );
+
+        launch_args.insert_command_token();
+        assert_eq!(
+            launch_args.to_string(),
+            "PROTON_LOG=1 DXVK_HUD=fps %command% --lobbyMap=B"
+        );
+
+        // Nothing changes if the token is there or no environment variables are set
+        launch_args.insert_command_token();
+        assert_eq!(
+            launch_args.to_string(),
+            "PROTON_LOG=1 DXVK_HUD=fps %command% --lobbyMap=B"
+        );
+        let mut launch_args = LaunchArgs::parse("-x PROTON_LOG=1");
+        launch_args.insert_command_token();
+        assert_eq!(launch_args.to_string(), "-x PROTON_LOG=1");
+    }
+}
@@ -0,0 +1,341 @@
+use crate::config::Target;
+use crate::helpers::{self, Error, ErrorCode};
+use crate::launch_args::{LaunchArgs, STEAM_COMMAND_TOKEN};
+use serde::Serialize;
+
+// Editing arbitrary launch options, checked against a catalog of known Overwatch flags.
+//
+// Only flags, arguments that start with `-` and may carry a `=value`, can be edited.
+// Other arguments, such as Steam environment variables, wrappers like `gamemoderun` or
+// values passed as a separate argument, are shown and kept but have to be edited in the
+// launcher.
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueType {
+    /// The flag is set on its own, e.g. `--tank_Console`.
+    None,
+    Integer,
+    /// A background id, e.g. `0x0800000000000E77`.
+    Background,
+}
+
+#[derive(Serialize)]
+pub struct KnownFlag {
+    pub flag: &'static str,
+    pub description: &'static str,
+    pub value: ValueType,
+    /// Whether OverBuddy sets the flag itself, so it cannot be edited directly.
+    pub managed: bool,
+}
+
+/// Overwatch flags OverBuddy knows about.
+pub const CATALOG: &[KnownFlag] = &[
+    KnownFlag {
+        flag: "--lobbyMap",
+        description: "Menu background",
+        value: ValueType::Background,
+        managed: true,
+    },
+    KnownFlag {
+        flag: "--tank_Console",
+        description: "Debug console, opened in-game with Alt + ~",
+        value: ValueType::None,
+        managed: true,
+    },
+    KnownFlag {
+        flag: "--tank_WorkerThreadCount",
+        description: "Number of worker threads the game uses",
+        value: ValueType::Integer,
+        managed: false,
+    },
+    KnownFlag {
+        flag: "-USEALLAVAILABLECORES",
+        description: "Often recommended for performance, Overwatch already uses every core",
+        value: ValueType::None,
+        managed: false,
+    },
+    KnownFlag {
+        flag: "-high",
+        description: "Often recommended to run the game with high process priority",
+        value: ValueType::None,
+        managed: false,
+    },
+    KnownFlag {
+        flag: "-nosplash",
+        description: "Often recommended to skip the intro video",
+        value: ValueType::None,
+        managed: false,
+    },
+];
+
+pub fn find_flag(flag: &str) -> Option<&'static KnownFlag> {
+    CATALOG.iter().find(|known| known.flag == flag)
+}
+
+#[derive(Serialize)]
+pub struct Warning {
+    pub flag: String,
+    pub message: String,
+}
+
+/// Launch options of a target, split into arguments.
+#[derive(Serialize)]
+pub struct TargetLaunchOptions {
+    pub target: Target,
+    pub name: String,
+    pub launch_args: Option<String>,
+    pub args: Vec<String>,
+    pub warnings: Vec<Warning>,
+    /// Why the launch options could not be read.
+    pub error: Option<Error>,
+}
+
+#[derive(Serialize)]
+pub struct LaunchOptions {
+    pub targets: Vec<TargetLaunchOptions>,
+    pub catalog: &'static [KnownFlag],
+}
+
+/// Split a flag argument into its name and value.
+fn split_flag(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((flag, value)) => (flag, Some(value)),
+        None => (arg, None),
+    }
+}
+
+/// Check that a value fits the type of a known flag.
+fn check_value(known: &KnownFlag, value: Option<&str>) -> Result<(), String> {
+    match (known.value, value) {
+        (ValueType::None, Some(_)) => Err(format!("[[{}]] does not take a value", known.flag)),
+        (ValueType::None, None) => Ok(()),
+        (_, None) => Err(format!("[[{}]] needs a value", known.flag)),
+        (ValueType::Integer, Some(value)) if value.parse::<i64>().is_err() => Err(format!(
+            "[[{}]] expects a number, not [[{}]]",
+            known.flag, value
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Find unknown and duplicate flags, and values that do not fit known flags.
+///
+/// With Steam, only arguments after `%command%` reach the game and are checked.
+pub fn check(launch_args: &LaunchArgs) -> Vec<Warning> {
+    let args = launch_args.args();
+    let game_args = match args.iter().position(|arg| arg.value == STEAM_COMMAND_TOKEN) {
+        Some(command) => &args[command + 1..],
+        None => args,
+    };
+
+    let mut warnings = vec![];
+    let mut seen: Vec<&str> = vec![];
+    let mut duplicates: Vec<&str> = vec![];
+    for arg in game_args.iter().filter(|arg| arg.value.starts_with('-')) {
+        let (flag, value) = split_flag(&arg.value);
+        let warning = |message: String| Warning {
+            flag: flag.to_string(),
+            message: helpers::render_plain(&message),
+        };
+
+        if seen.contains(&flag) {
+            if !duplicates.contains(&flag) {
+                duplicates.push(flag);
+                warnings.push(warning(format!(
+                    "[[{}]] is set more than once, only one of them is used",
+                    flag
+                )));
+            }
+            continue;
+        }
+        seen.push(flag);
+
+        match find_flag(flag) {
+            Some(known) => {
+                if let Err(message) = check_value(known, value) {
+                    warnings.push(warning(message));
+                }
+            }
+            None => warnings.push(warning(format!(
+                "[[{}]] is not a known Overwatch flag and may be ignored",
+                flag
+            ))),
+        }
+    }
+
+    warnings
+}
+
+/// Check that a flag can be edited. Arguments that do not start with `-` are rejected,
+/// they have to be edited in the launcher.
+pub fn validate_flag(flag: &str) -> Result<(), Error> {
+    if !flag.starts_with('-')
+        || flag.trim_start_matches('-').is_empty()
+        || flag.contains(|c: char| c.is_whitespace() || c == '=' || c == '"')
+    {
+        return Err(Error::new(
+            ErrorCode::InvalidArgument,
+            format!("[[{}]] is not a valid flag", flag),
+        )
+        .with_hint("Flags start with - or --, e.g. --tank_WorkerThreadCount.")
+        .with_hint(
+            "Other arguments, such as environment variables, can only be edited in the launcher.",
+        ));
+    }
+
+    if find_flag(flag).is_some_and(|known| known.managed) {
+        return Err(Error::new(
+            ErrorCode::InvalidArgument,
+            format!("[[{}]] is managed by OverBuddy", flag),
+        )
+        .with_hint("Use the background and debug console settings instead."));
+    }
+
+    Ok(())
+}
+
+/// Check a flag and its value before they are written to the launchers.
+pub fn validate(flag: &str, value: Option<&str>) -> Result<(), Error> {
+    validate_flag(flag)?;
+
+    match find_flag(flag) {
+        Some(known) => check_value(known, value)
+            .map_err(|message| Error::new(ErrorCode::InvalidArgument, message)),
+        None => Ok(()),
+    }
+}
+
+/// Set a flag in the launch arguments, replacing its value if it is already set.
+pub fn set(launch_args: Option<&str>, flag: &str, value: Option<&str>) -> String {
+    let mut launch_args = LaunchArgs::parse(launch_args.unwrap_or_default());
+    match value {
+        Some(value) => launch_args.set_value(flag, Some(value)),
+        None => launch_args.set_flag(flag, true),
+    }
+
+    launch_args.to_string()
+}
+
+/// Remove every occurrence of a flag from the launch arguments.
+pub fn remove(launch_args: Option<&str>, flag: &str) -> String {
+    let mut launch_args = LaunchArgs::parse(launch_args.unwrap_or_default());
+    launch_args.set_flag(flag, false);
+
+    launch_args.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warnings(launch_args: &str) -> Vec<(String, String)> {
+        check(&LaunchArgs::parse(launch_args))
+            .into_iter()
+            .map(|warning| (warning.flag, warning.message))
+            .collect()
+    }
+
+    fn flags(launch_args: &str) -> Vec<String> {
+        warnings(launch_args)
+            .into_iter()
+            .map(|(flag, _)| flag)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_known_flags() {
+        assert!(
+            warnings("--lobbyMap=0x0800000000000E77 --tank_WorkerThreadCount=4 -high").is_empty()
+        );
+        assert!(warnings("").is_empty());
+    }
+
+    #[test]
+    fn reports_duplicate_flags_once() {
+        let warnings =
+            warnings("-high -high --tank_WorkerThreadCount=4 -high --tank_WorkerThreadCount=2");
+        assert_eq!(
+            warnings,
+            [
+                (
+                    "-high".to_string(),
+                    "\"-high\" is set more than once, only one of them is used".to_string()
+                ),
+                (
+                    "--tank_WorkerThreadCount".to_string(),
+                    "\"--tank_WorkerThreadCount\" is set more than once, only one of them is used"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_only_arguments_after_the_steam_command() {
+        assert!(warnings("-unknown=1 gamemoderun %command% -high").is_empty());
+        // A flag before %command% is not a duplicate of the same flag after it
+        assert_eq!(flags("-high %command% -high -unknown"), ["-unknown"]);
+        // Without %command% every argument reaches the game
+        assert_eq!(flags("-unknown gamemoderun"), ["-unknown"]);
+    }
+
+    #[test]
+    fn reports_values_that_do_not_fit() {
+        assert_eq!(
+            flags("--tank_WorkerThreadCount=many -high=1 --tank_Console=1 --lobbyMap"),
+            [
+                "--tank_WorkerThreadCount",
+                "-high",
+                "--tank_Console",
+                "--lobbyMap"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_flags_that_cannot_be_edited() {
+        for flag in ["high", "-", "--", "-a b", "-a=1", "-a\"", "MANGOHUD=1"] {
+            assert!(validate_flag(flag).is_err(), "{}", flag);
+        }
+        for flag in ["--lobbyMap", "--tank_Console"] {
+            let error = validate(flag, None).err().unwrap();
+            assert!(error.message.contains("managed by OverBuddy"), "{}", flag);
+        }
+        assert!(validate_flag("-novid").is_ok());
+    }
+
+    #[test]
+    fn validates_values_of_known_flags() {
+        assert!(validate("--tank_WorkerThreadCount", Some("8")).is_ok());
+        assert!(validate("--tank_WorkerThreadCount", Some("8.5")).is_err());
+        assert!(validate("--tank_WorkerThreadCount", Some("")).is_err());
+        assert!(validate("--tank_WorkerThreadCount", None).is_err());
+        assert!(validate("-high", Some("1")).is_err());
+        assert!(validate("-high", None).is_ok());
+        // Unknown flags take any value
+        assert!(validate("-novid", Some("anything")).is_ok());
+    }
+
+    #[test]
+    fn sets_and_removes_flags_keeping_other_arguments() {
+        let launch_args =
+            "MANGOHUD=1 gamemoderun %command% -language english --lobbyMap=0x0800000000000E77";
+
+        let set_args = set(Some(launch_args), "--tank_WorkerThreadCount", Some("4"));
+        assert_eq!(
+            set_args,
+            "MANGOHUD=1 gamemoderun %command% -language english --lobbyMap=0x0800000000000E77 --tank_WorkerThreadCount=4"
+        );
+        let replaced = set(Some(&set_args), "--tank_WorkerThreadCount", Some("8"));
+        assert!(replaced.contains("--tank_WorkerThreadCount=8"));
+        assert!(!replaced.contains("--tank_WorkerThreadCount=4"));
+
+        let with_flag = set(Some(&replaced), "-high", None);
+        let removed = remove(Some(&with_flag), "--tank_WorkerThreadCount");
+        assert_eq!(remove(Some(&removed), "-high"), launch_args);
+
+        assert_eq!(set(None, "-high", None), "-high");
+        assert_eq!(remove(None, "-high"), "");
+    }
+}
@@ -1,342 +1,45 @@
+mod actions;
 mod backgrounds;
+mod cli;
 mod config;
+mod custom_backgrounds;
+mod discovery;
 mod helpers;
+mod history;
+mod launch_args;
+mod launch_options;
 mod platforms;
-
-use config::{ErrorKey, SetupError};
-use helpers::Error;
-use platforms::{battle_net, steam};
+mod rotation;
+mod search;
+mod seasonal;
+mod transaction;
+mod vdf;
+
+use helpers::{Error, ErrorCode, Platform};
+use platforms::steam;
 use std::env;
-use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use tauri::AppHandle;
+use tauri::Emitter;
 use tauri::Manager;
 
 #[tauri::command]
 fn get_launch_config(handle: AppHandle) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
-    let mut battle_net_shared: Option<config::SharedConfig> = None;
-    let mut steam_shared: Option<config::SharedConfig> = None;
-
-    if !config.battle_net.enabled && !config.steam.enabled {
-        config.is_setup = false;
-    }
-
-    if config.is_setup {
-        // TODO: Show user a warning if the backup file exists
-
-        if config.battle_net.enabled {
-            battle_net_shared = battle_net::update_config(&config)?;
-        }
-
-        if config.steam.enabled && !config.steam.in_setup {
-            steam_shared = steam::update_config(&mut config)?;
-        }
-
-        // Merge shared config
-        if let (Some(battle_net_shared), Some(steam_shared)) = (&battle_net_shared, &steam_shared) {
-            if battle_net_shared.background.current == steam_shared.background.current {
-                config.shared.background.current = battle_net_shared.background.current.clone();
-            } else {
-                config.shared.background.current = None;
-            }
-            config.shared.background.is_outdated =
-                battle_net_shared.background.is_outdated || steam_shared.background.is_outdated;
-
-            config.shared.additional.console_enabled = battle_net_shared.additional.console_enabled
-                && steam_shared.additional.console_enabled;
-        } else if let Some(shared) = battle_net_shared.as_ref().or(steam_shared.as_ref()) {
-            config.shared.background.current = shared.background.current.clone();
-            config.shared.background.is_outdated = shared.background.is_outdated;
-            config.shared.additional.console_enabled = shared.additional.console_enabled;
-        } else {
-            config.shared.background.current = None;
-            config.shared.background.is_outdated = false;
-            config.shared.additional.console_enabled = false;
-        }
-
-        if let Some(custom) = config.shared.background.custom.as_deref() {
-            if config.shared.background.current.is_none()
-                || config.shared.background.current.as_deref() != Some(custom)
-            {
-                config.shared.background.custom = None;
-            }
-        }
-    }
-
-    config::write_config(&handle, &config)?;
+    let config = actions::get_launch_config(&config::data_dir(&handle))?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn setup(handle: AppHandle, platforms: Vec<&str>, is_initialized: bool) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
-
-    if platforms.contains(&"BattleNet") {
-        // Check if Battle.net is installed
-        if config.battle_net.install.is_none() {
-            static LAUNCHER_PATH: &str = "Battle.net\\Battle.net Launcher.exe";
-            if let Some(program_files_dir) = env::var_os("programfiles(x86)") {
-                let battle_net_install =
-                    Path::new(program_files_dir.to_str().unwrap()).join(LAUNCHER_PATH);
-                if battle_net_install.exists() {
-                    config.battle_net.install =
-                        Some(battle_net_install.to_string_lossy().to_string());
-                }
-            }
-        }
-        if config.battle_net.install.is_none() {
-            return Err(Error::Custom(serde_json::to_string(&SetupError {
-                error_key: ErrorKey::BattleNetInstall,
-                message: "Failed to find your Battle.net installation".to_string(),
-                platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-            })?));
-        }
-
-        // Check if Battle.net config exists
-        let battle_net_config = match &config.battle_net.config {
-            Some(battle_net_config) => battle_net_config.clone(),
-            None => {
-                let path = env::var_os("appdata").map(|path| Path::new(&path).join("Battle.net"));
-
-                if let Some(resource_path) = path {
-                    // Check if Battle.net AppData directory exists
-                    if let Ok(entries) = fs::read_dir(&resource_path) {
-                        // Check if Battle.net.config exists in the directory
-                        if let Some(target_entry) =
-                            entries.filter_map(|entry| entry.ok()).find(|entry| {
-                                entry.file_name().to_string_lossy() == battle_net::CONFIG_FILE
-                            })
-                        {
-                            let display_path = helpers::display_path_string(&target_entry.path())?;
-                            config.battle_net.config = Some(display_path.clone());
-                            display_path
-                        } else {
-                            let display_path = helpers::display_path_string(&resource_path)?;
-                            return Err(Error::Custom(serde_json::to_string(&SetupError {
-                                error_key: ErrorKey::BattleNetConfig,
-                                message: format!(
-                                    "Failed to find [[{}]] file at [[{}]]",
-                                    battle_net::CONFIG_FILE,
-                                    display_path
-                                ),
-                                platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-                            })?));
-                        }
-                    } else {
-                        let display_path = helpers::display_path_string(&resource_path)?;
-                        return Err(Error::Custom(serde_json::to_string(&SetupError {
-                            error_key: ErrorKey::BattleNetConfig,
-                            message: format!(
-                                "Failed to read [[{}]] file at [[{}]]",
-                                battle_net::CONFIG_FILE,
-                                display_path
-                            ),
-                            platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-                        })?));
-                    }
-                } else {
-                    return Err(Error::Custom(serde_json::to_string(&SetupError {
-                        error_key: ErrorKey::BattleNetConfig,
-                        message: "Failed to find the Battle.net AppData directory".to_string(),
-                        platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-                    })?));
-                }
-            }
-        };
-
-        // Read and parse Battle.net.config file
-        let file = match std::fs::File::open(&battle_net_config) {
-            Ok(file) => file,
-            Err(e) => {
-                return Err(Error::Custom(serde_json::to_string(&SetupError {
-                    error_key: ErrorKey::BattleNetConfig,
-                    message: format!(
-                        "Failed to open the [[{}]] file at [[{}]]: {}",
-                        battle_net::CONFIG_FILE,
-                        battle_net_config,
-                        e
-                    ),
-                    platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-                })?));
-            }
-        };
-        let mut json: serde_json::Value = match serde_json::from_reader(file) {
-            Ok(json) => json,
-            Err(e) => {
-                return Err(Error::Custom(serde_json::to_string(&SetupError {
-                    error_key: ErrorKey::BattleNetConfig,
-                    message: format!(
-                        "Failed to read [[{}]] file at [[{}]]: {}",
-                        battle_net::CONFIG_FILE,
-                        battle_net_config,
-                        e
-                    ),
-                    platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-                })?));
-            }
-        };
-
-        // Check Overwatch installation on Battle.net
-        if let None = json
-            .get_mut("Games")
-            .and_then(|games| games.get_mut("prometheus"))
-        {
-            return Err(Error::Custom(serde_json::to_string(&SetupError {
-                error_key: ErrorKey::NoOverwatch,
-                message: "Unable to find an Overwatch installation on Battle.net".to_string(),
-                platforms: None,
-            })?));
-        };
-
-        let mut battle_net_was_closed = false;
-
-        // Check and create DefaultStartupScreen if it doesn't exist
-        if let Some(client_config) = json.get_mut("Client") {
-            if client_config.get("DefaultStartupScreen").is_none() {
-                client_config
-                    .as_object_mut()
-                    .unwrap()
-                    .insert("DefaultStartupScreen".to_string(), serde_json::json!("1"));
-
-                battle_net_was_closed = battle_net::close_app();
-            } else {
-                let startup_screen = client_config["DefaultStartupScreen"]
-                    .as_str()
-                    .map(|s| s.to_string());
-
-                if startup_screen.is_none() || startup_screen.unwrap() == "0" {
-                    client_config
-                        .as_object_mut()
-                        .unwrap()
-                        .insert("DefaultStartupScreen".to_string(), serde_json::json!("1"));
-
-                    battle_net_was_closed = battle_net::close_app();
-                }
-            }
-        }
-
-        // Update config
-        let battle_net_shared = battle_net::update_config(&config)?;
-        if let Some(battle_net_shared) = battle_net_shared {
-            config.shared = battle_net_shared;
-        }
-
-        // Cleanup: Reopen Battle.net if it was closed
-        if battle_net_was_closed {
-            helpers::safe_json_write(battle_net_config, &json)?;
-            Command::new(config.battle_net.install.clone().unwrap())
-                .spawn()
-                .map_err(|_| {
-                    Error::Custom(format!(
-                        "Failed to open Battle.net at [[{}]]",
-                        config.battle_net.install.clone().unwrap()
-                    ))
-                })?;
-        }
-
-        // Enable Battle.net
-        config.battle_net.enabled = true;
-    } else {
-        battle_net::reset_config(&mut config)?;
-
-        // Disable Battle.net
-        config.battle_net.enabled = false;
-    }
-
-    if platforms.contains(&"Steam") {
-        // Check if Steam is installed
-        if config.steam.install.is_none() {
-            static LAUNCHER_PATH: &str = "Steam\\steam.exe";
-            if let Some(program_files_dir) = env::var_os("programfiles(x86)") {
-                let steam_install =
-                    Path::new(program_files_dir.to_str().unwrap()).join(LAUNCHER_PATH);
-                if steam_install.exists() {
-                    config.steam.install = Some(steam_install.to_string_lossy().to_string());
-                }
-            }
-        }
-        if config.steam.install.is_none() {
-            return Err(Error::Custom(serde_json::to_string(&SetupError {
-                error_key: ErrorKey::SteamInstall,
-                message: "Failed to find your Steam installation".to_string(),
-                platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-            })?));
-        }
-        let steam_install = config.steam.install.clone().unwrap();
-
-        // Check if Steam localconfig exists
-        let steam_path = Path::new(&steam_install).parent().ok_or_else(|| {
-            Error::Custom(
-                serde_json::to_string(&SetupError {
-                    error_key: ErrorKey::SteamInstall,
-                    message: "Failed to read the parent directory of your Steam installation"
-                        .to_string(),
-                    platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-                })
-                .unwrap(),
-            )
-        })?;
-
-        let userdata_path = steam_path.join("userdata");
-        if !userdata_path.exists() || !userdata_path.is_dir() {
-            return Err(Error::Custom(serde_json::to_string(&SetupError {
-                error_key: ErrorKey::SteamAccount,
-                message: format!(
-                    "Failed to read your Steam [[userdata]] folder, located at [[{}]]",
-                    userdata_path.to_string_lossy()
-                ),
-                platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-            })?));
-        }
-
-        config.steam.configs = Some(steam::get_configs(&config)?);
-        if config.steam.configs.is_none() || config.steam.configs.as_ref().unwrap().is_empty() {
-            return Err(Error::Custom(serde_json::to_string(&SetupError {
-                error_key: ErrorKey::SteamAccount,
-                message: format!(
-                    "Failed to find any accounts in your Steam [[userdata]] folder, located at [[{}]]",
-                    userdata_path.to_string_lossy()
-                ),
-                platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-            })?));
-        }
-
-        // Enable Steam
-        if !config.steam.enabled {
-            config.steam.in_setup = true;
-        }
-        config.steam.enabled = true;
-    } else {
-        steam::reset_config(&config)?;
-
-        // Disable Steam
-        config.steam.profiles = None;
-        config.steam.configs = None;
-        config.steam.in_setup = false;
-        config.steam.enabled = false;
-    }
+fn get_status(handle: AppHandle) -> Result<String, Error> {
+    let status = actions::get_status(&config::data_dir(&handle))?;
 
-    // Check if no platforms were enabled
-    if !config.battle_net.enabled && !config.steam.enabled {
-        config.is_setup = false;
-        if !is_initialized {
-            return Err(Error::Custom(format!(
-                "Failed to setup one of your requested platforms: [[{}]]",
-                platforms.join("]], [[").replace("BattleNet", "Battle.net")
-            )));
-        }
-
-        config.shared.background.current = None;
-        config.shared.background.is_outdated = false;
-        config.shared.additional.console_enabled = false;
-    } else {
-        config.is_setup = true;
-    }
+    Ok(serde_json::to_string(&status)?)
+}
 
-    config::write_config(&handle, &config)?;
+#[tauri::command]
+fn setup(handle: AppHandle, platforms: Vec<&str>, is_initialized: bool) -> Result<String, Error> {
+    let config = actions::setup(&config::data_dir(&handle), &platforms, is_initialized)?;
 
     Ok(serde_json::to_string(&config)?)
 }
@@ -353,6 +56,12 @@ fn resolve_setup_error(
     match key {
         "BattleNetInstall" => {
             config.battle_net.install = Some(path.to_string());
+            config.battle_net.prefix = discovery::get_prefix_from_path(path);
+            config.battle_net.runner = config
+                .battle_net
+                .prefix
+                .as_deref()
+                .and_then(|prefix| discovery::get_runner(Path::new(prefix)));
         }
         "BattleNetConfig" => {
             config.battle_net.config = Some(path.to_string());
@@ -361,10 +70,11 @@ fn resolve_setup_error(
             config.steam.install = Some(path.to_string());
         }
         _ => {
-            return Err(Error::Custom(format!(
-                "Encountered incorrect setup resolution key [[{}]]. Please report this issue to the developer",
-                key
-            )));
+            return Err(Error::new(
+                ErrorCode::InvalidArgument,
+                format!("Encountered incorrect setup resolution key [[{}]]", key),
+            )
+            .with_hint("Please report this issue to the developer."));
         }
     };
 
@@ -401,13 +111,18 @@ fn get_setup_path(key: &str) -> Result<String, Error> {
             }))?)
         }
         "SteamInstall" => {
-            let path = env::var_os("programfiles(x86)")
-                .map(|path| Path::new(&path).join("Steam"))
-                .and_then(|path| helpers::display_path_string(&path).ok());
-
-            let default_path = env::var_os("programfiles(x86)")
-                .map(|path| PathBuf::from(path))
-                .and_then(|path| helpers::display_path_string(&path).ok());
+            let steam_path = steam::get_default_paths()
+                .into_iter()
+                .find(|path| path.exists())
+                .or_else(|| steam::get_default_paths().into_iter().next());
+            let path = steam_path
+                .as_ref()
+                .and_then(|path| helpers::display_path_string(path).ok());
+
+            let default_path = steam_path
+                .as_ref()
+                .and_then(|path| path.parent())
+                .and_then(|path| helpers::display_path_string(&path.to_path_buf()).ok());
 
             Ok(serde_json::to_string(&serde_json::json!({
                 "path": path,
@@ -415,8 +130,9 @@ fn get_setup_path(key: &str) -> Result<String, Error> {
             }))?)
         }
         "SteamAccount" => {
-            let path = env::var_os("programfiles(x86)")
-                .map(|path| Path::new(&path).join("Steam"))
+            let path = steam::get_default_paths()
+                .into_iter()
+                .find(|path| path.exists())
                 .and_then(|path| helpers::display_path_string(&path).ok());
 
             Ok(serde_json::to_string(&serde_json::json!({
@@ -424,17 +140,55 @@ fn get_setup_path(key: &str) -> Result<String, Error> {
                 "defaultPath": path
             }))?)
         }
-        _ => Err(Error::Custom(
-            "Encountered an incorrect setup key. Please report this issue to the developer".into(),
-        )),
+        _ => Err(Error::new(
+            ErrorCode::InvalidArgument,
+            "Encountered an incorrect setup key",
+        )
+        .with_hint("Please report this issue to the developer.")),
     }
 }
 
+#[tauri::command]
+fn get_battle_net_candidates() -> Result<String, Error> {
+    let candidates = discovery::find_battle_net_candidates();
+
+    Ok(serde_json::to_string(&candidates)?)
+}
+
+#[tauri::command]
+fn select_battle_net_candidate(
+    handle: AppHandle,
+    prefix: Option<&str>,
+    platforms: Vec<&str>,
+) -> Result<String, Error> {
+    let mut config = config::read_config(&handle)?;
+
+    let candidate = discovery::find_battle_net_candidate(prefix).ok_or_else(|| {
+        Error::new(
+            ErrorCode::BattleNetPrefix,
+            format!(
+                "Failed to find a Battle.net installation at [[{}]]",
+                prefix.unwrap_or("the default location")
+            ),
+        )
+        .with_platform(Platform::BattleNet)
+    })?;
+    config.battle_net.install = candidate.install;
+    config.battle_net.config = candidate.config;
+    config.battle_net.prefix = candidate.prefix;
+    config.battle_net.runner = candidate.runner;
+
+    config::write_config(&handle, &config)?;
+
+    setup(handle, platforms, false)
+}
+
 #[tauri::command]
 fn get_steam_accounts(handle: AppHandle) -> Result<String, Error> {
     let mut config = config::read_config(&handle)?;
 
     config.steam.configs = Some(steam::get_configs(&config)?);
+    config.steam.overwatch = steam::get_overwatch_install(&config)?;
     let profiles = steam::get_profiles(&config)?;
     config::write_config(&handle, &config)?;
 
@@ -443,258 +197,216 @@ fn get_steam_accounts(handle: AppHandle) -> Result<String, Error> {
 
 #[tauri::command]
 fn confirm_steam_setup(handle: AppHandle) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
-
-    let steam_shared = steam::update_config(&mut config)?;
-
-    // Check that at least one account has Overwatch
-    if let Some(profiles) = &config.steam.profiles {
-        let steam_has_overwatch = profiles.iter().any(|profile| profile.has_overwatch);
-        if !steam_has_overwatch {
-            if config.battle_net.enabled {
-                return Ok("NoSteamOverwatch".into());
-            } else {
-                return Ok("NoSteamOverwatchFatal".into());
-            }
-        }
-    }
-
-    if let Some(steam_shared) = steam_shared {
-        if config.battle_net.enabled {
-            if let (Some(battle_net_background), Some(steam_background)) = (
-                &config.shared.background.current,
-                &steam_shared.background.current,
-            ) {
-                if battle_net_background != steam_background {
-                    config.shared.background.current = None;
-                    config.shared.background.is_outdated = false;
-                }
-            }
-            config.shared.background.is_outdated =
-                config.shared.background.is_outdated || steam_shared.background.is_outdated;
-
-            config.shared.additional.console_enabled &= steam_shared.additional.console_enabled;
-        } else {
-            config.shared.background.current = steam_shared.background.current;
-            config.shared.background.is_outdated = steam_shared.background.is_outdated;
-            config.shared.additional.console_enabled = steam_shared.additional.console_enabled;
-        }
+    match actions::confirm_steam_setup(&config::data_dir(&handle))? {
+        actions::SteamSetup::Confirmed(config) => Ok(serde_json::to_string(&config)?),
+        actions::SteamSetup::NoOverwatch { fatal: false } => Ok("NoSteamOverwatch".into()),
+        actions::SteamSetup::NoOverwatch { fatal: true } => Ok("NoSteamOverwatchFatal".into()),
     }
+}
 
-    config.steam.in_setup = false;
-    config::write_config(&handle, &config)?;
+#[tauri::command]
+fn undo_steam_setup(handle: AppHandle) -> Result<String, Error> {
+    let config = actions::undo_steam_setup(&config::data_dir(&handle))?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn undo_steam_setup(handle: AppHandle) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
+fn set_steam_account_included(
+    handle: AppHandle,
+    id: &str,
+    included: bool,
+) -> Result<String, Error> {
+    let config = actions::set_steam_account_included(&config::data_dir(&handle), id, included)?;
 
-    config.steam.profiles = None;
-    config.steam.configs = None;
-    config.steam.in_setup = false;
-    config.steam.enabled = false;
+    Ok(serde_json::to_string(&config)?)
+}
 
-    if !config.battle_net.enabled {
-        config.is_setup = false;
-    }
+#[tauri::command]
+fn mark_steam_accounts_seen(handle: AppHandle) -> Result<String, Error> {
+    let config = actions::mark_steam_accounts_seen(&config::data_dir(&handle))?;
 
-    config::write_config(&handle, &config)?;
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn set_include_new_steam_accounts(handle: AppHandle, include: bool) -> Result<String, Error> {
+    let config = actions::set_include_new_steam_accounts(&config::data_dir(&handle), include)?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn get_backgrounds() -> String {
+async fn get_backgrounds(handle: AppHandle) -> Result<String, Error> {
+    let data_dir = config::data_dir(&handle);
+
+    // Reload so that catalog updates and changes to the override file show up without a restart
+    backgrounds::update_if_due(&data_dir).await;
+    backgrounds::load(&data_dir)?;
     let backgrounds = backgrounds::get_backgrounds();
 
-    serde_json::to_string(&backgrounds).unwrap()
+    Ok(serde_json::to_string(&backgrounds)?)
 }
 
 #[tauri::command]
-fn set_background(handle: AppHandle, id: &str, is_custom: Option<bool>) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
-    let mut battle_net_error: Option<Error> = None;
-    let mut steam_error: Option<Error> = None;
-
-    if config.battle_net.enabled {
-        match battle_net::set_launch_args(
-            &config,
-            Some(id),
-            helpers::generate_background_launch_args,
-        ) {
-            Ok(_) => {}
-            Err(error) => {
-                battle_net_error = Some(error);
-            }
-        }
-    }
+fn query_backgrounds(handle: AppHandle, query: search::BackgroundQuery) -> Result<String, Error> {
+    let result = actions::query_backgrounds(&config::data_dir(&handle), &query)?;
 
-    if config.steam.enabled {
-        match steam::set_launch_args(&config, Some(id), helpers::generate_background_launch_args) {
-            Ok(_) => {}
-            Err(error) => {
-                steam_error = Some(error);
-            }
-        }
-    }
+    Ok(serde_json::to_string(&result)?)
+}
 
-    if steam_error.is_some() {
-        if battle_net_error.is_some() {
-            return Err(Error::Custom(format!(
-                "Failed to apply background on Battle.net: {}\nAlso failed to apply background on Steam: {}",
-                battle_net_error.unwrap(),
-                steam_error.unwrap(),
-            )));
-        } else {
-            return Err(Error::Custom(format!(
-                "Failed to apply background: {}",
-                steam_error.unwrap()
-            )));
-        }
-    } else if let Some(error) = battle_net_error {
-        return Err(Error::Custom(format!(
-            "Failed to apply background: {}",
-            error
-        )));
-    }
+#[tauri::command]
+fn set_background(
+    handle: AppHandle,
+    id: &str,
+    is_custom: Option<bool>,
+    targets: Option<Vec<config::Target>>,
+    preview: Option<bool>,
+) -> Result<String, Error> {
+    actions::set_background(
+        &config::data_dir(&handle),
+        id,
+        is_custom == Some(true),
+        targets.as_deref(),
+        preview == Some(true),
+    )?
+    .to_json()
+}
 
-    config.shared.background.current = Some(id.to_string());
-    config.shared.background.is_outdated = false;
-    if is_custom == Some(true) {
-        config.shared.background.custom = Some(id.to_string());
-    } else {
-        config.shared.background.custom = None;
-    }
-    config::write_config(&handle, &config)?;
+#[tauri::command]
+fn save_custom_background(
+    handle: AppHandle,
+    background: config::CustomBackground,
+    image: Option<&str>,
+) -> Result<String, Error> {
+    let config = actions::save_custom_background(
+        &config::data_dir(&handle),
+        background,
+        image.map(Path::new),
+    )?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn reset_background(handle: AppHandle) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
-    let mut battle_net_error: Option<Error> = None;
-    let mut steam_error: Option<Error> = None;
-
-    if config.battle_net.enabled {
-        match battle_net::set_launch_args(&config, None, helpers::generate_background_launch_args) {
-            Ok(_) => {}
-            Err(error) => {
-                battle_net_error = Some(error);
-            }
-        }
-    }
-
-    if config.steam.enabled {
-        match steam::set_launch_args(&config, None, helpers::generate_background_launch_args) {
-            Ok(_) => {}
-            Err(error) => {
-                steam_error = Some(error);
-            }
-        }
-    }
+fn delete_custom_background(handle: AppHandle, id: &str) -> Result<String, Error> {
+    let config = actions::delete_custom_background(&config::data_dir(&handle), id)?;
 
-    if let Some(error) = steam_error {
-        if battle_net_error.is_some() {
-            return Err(Error::Custom(format!(
-                "Failed to reset background on Battle.net: {}\nAlso failed to apply background on Steam: {}",
-                battle_net_error.unwrap(),
-                error
-            )));
-        } else {
-            return Err(Error::Custom(format!(
-                "Failed to reset background: {}",
-                error
-            )));
-        }
-    } else if let Some(error) = battle_net_error {
-        return Err(Error::Custom(format!(
-            "Failed to reset background: {}",
-            error
-        )));
-    }
+    Ok(serde_json::to_string(&config)?)
+}
 
-    config.shared.background.current = None;
-    config.shared.background.is_outdated = false;
-    config.shared.background.custom = None;
-    config::write_config(&handle, &config)?;
+#[tauri::command]
+fn set_favorite(handle: AppHandle, id: &str, favorite: bool) -> Result<String, Error> {
+    let config = actions::set_favorite(&config::data_dir(&handle), id, favorite)?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn set_debug_console(handle: AppHandle, enable_console: bool) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
-    let mut battle_net_error: Option<Error> = None;
-    let mut steam_error: Option<Error> = None;
-
-    if config.battle_net.enabled {
-        match battle_net::set_launch_args(
-            &config,
-            enable_console,
-            helpers::generate_console_launch_args,
-        ) {
-            Ok(_) => {}
-            Err(error) => {
-                battle_net_error = Some(error);
-            }
-        }
-    }
+fn set_rotation(handle: AppHandle, rotation: config::RotationConfig) -> Result<String, Error> {
+    let config = actions::set_rotation(&config::data_dir(&handle), rotation)?;
 
-    if config.steam.enabled {
-        match steam::set_launch_args(
-            &config,
-            enable_console,
-            helpers::generate_console_launch_args,
-        ) {
-            Ok(_) => {}
-            Err(error) => {
-                steam_error = Some(error);
-            }
-        }
-    }
+    Ok(serde_json::to_string(&config)?)
+}
 
-    if steam_error.is_some() {
-        if battle_net_error.is_some() {
-            return Err(Error::Custom(format!(
-                "Failed to apply debug console on Battle.net: {}\nAlso failed to apply debug console on Steam: {}",
-                battle_net_error.unwrap(),
-                steam_error.unwrap(),
-            )));
-        } else {
-            return Err(Error::Custom(format!(
-                "Failed to apply debug console: {}",
-                steam_error.unwrap()
-            )));
-        }
-    } else if let Some(error) = battle_net_error {
-        return Err(Error::Custom(format!(
-            "Failed to apply debug console: {}",
-            error
-        )));
-    }
+#[tauri::command]
+fn rotate_background(handle: AppHandle) -> Result<String, Error> {
+    let config = actions::rotate_background(&config::data_dir(&handle))?;
 
-    config.shared.additional.console_enabled = enable_console;
-    config::write_config(&handle, &config)?;
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn set_seasonal_calendar(
+    handle: AppHandle,
+    enabled: bool,
+    events: Option<Vec<config::SeasonalEvent>>,
+) -> Result<String, Error> {
+    let config = actions::set_seasonal_calendar(&config::data_dir(&handle), enabled, events)?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn reset(handle: AppHandle) -> Result<String, Error> {
-    let config = config::read_config(&handle);
+fn get_history(handle: AppHandle) -> Result<String, Error> {
+    let history = actions::get_history(&config::data_dir(&handle))?;
 
-    if let Ok(config) = config {
-        battle_net::reset_config(&config)?;
-        steam::reset_config(&config)?;
-    }
+    Ok(serde_json::to_string(&history)?)
+}
 
-    let config = config::get_default_config();
-    config::write_config(&handle, &config)?;
+#[tauri::command]
+fn revert_background(handle: AppHandle, preview: Option<bool>) -> Result<String, Error> {
+    actions::revert_background(&config::data_dir(&handle), preview == Some(true))?.to_json()
+}
 
-    Ok(serde_json::to_string(&config)?)
+#[tauri::command]
+fn reset_background(handle: AppHandle, preview: Option<bool>) -> Result<String, Error> {
+    actions::reset_background(&config::data_dir(&handle), None, preview == Some(true))?.to_json()
+}
+
+#[tauri::command]
+fn set_debug_console(
+    handle: AppHandle,
+    enable_console: bool,
+    preview: Option<bool>,
+) -> Result<String, Error> {
+    actions::set_debug_console(
+        &config::data_dir(&handle),
+        enable_console,
+        preview == Some(true),
+    )?
+    .to_json()
+}
+
+#[tauri::command]
+fn get_launch_options(handle: AppHandle) -> Result<String, Error> {
+    let launch_options = actions::get_launch_options(&config::data_dir(&handle))?;
+
+    Ok(serde_json::to_string(&launch_options)?)
+}
+
+#[tauri::command]
+fn set_launch_option(
+    handle: AppHandle,
+    flag: &str,
+    value: Option<&str>,
+    targets: Option<Vec<config::Target>>,
+    preview: Option<bool>,
+) -> Result<String, Error> {
+    actions::set_launch_option(
+        &config::data_dir(&handle),
+        flag,
+        value,
+        targets.as_deref(),
+        preview == Some(true),
+    )?
+    .to_json()
+}
+
+#[tauri::command]
+fn remove_launch_option(
+    handle: AppHandle,
+    flag: &str,
+    targets: Option<Vec<config::Target>>,
+    preview: Option<bool>,
+) -> Result<String, Error> {
+    actions::remove_launch_option(
+        &config::data_dir(&handle),
+        flag,
+        targets.as_deref(),
+        preview == Some(true),
+    )?
+    .to_json()
+}
+
+#[tauri::command]
+fn reset(handle: AppHandle, preview: Option<bool>) -> Result<String, Error> {
+    actions::reset(&config::data_dir(&handle), preview == Some(true))?.to_json()
+}
+
+/// Entry point of the `overbuddy-cli` binary.
+pub fn run_cli() -> std::process::ExitCode {
+    cli::run(env::args().skip(1).collect())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -709,18 +421,88 @@ pub fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_dialog::init())
+        .setup(|app| {
+            // An invalid override file falls back to the bundled catalog, the error is
+            // reported when the frontend requests the backgrounds
+            backgrounds::load(&config::data_dir(app.handle())).ok();
+
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                rotation::watch(
+                    &config::data_dir(&handle),
+                    |_| {
+                        handle.emit(rotation::ROTATED_EVENT, ()).ok();
+                    },
+                    |error| {
+                        handle.emit(rotation::ROTATION_FAILED_EVENT, error).ok();
+                    },
+                )
+            });
+            Ok(())
+        })
+        .register_uri_scheme_protocol(steam::AVATAR_PROTOCOL, |context, request| {
+            let steam_id = request.uri().path().trim_start_matches('/');
+            let avatar = config::read_config(context.app_handle())
+                .ok()
+                .and_then(|config| steam::get_avatar(&config, steam_id));
+
+            match avatar {
+                Some(avatar) => tauri::http::Response::builder()
+                    .header(tauri::http::header::CONTENT_TYPE, "image/png")
+                    .body(avatar)
+                    .unwrap(),
+                None => tauri::http::Response::builder()
+                    .status(tauri::http::StatusCode::NOT_FOUND)
+                    .body(Vec::new())
+                    .unwrap(),
+            }
+        })
+        .register_uri_scheme_protocol(custom_backgrounds::IMAGE_PROTOCOL, |context, request| {
+            let file_name = request.uri().path().trim_start_matches('/');
+            let image =
+                custom_backgrounds::read_image(&config::data_dir(context.app_handle()), file_name);
+
+            match image {
+                Some((image, content_type)) => tauri::http::Response::builder()
+                    .header(tauri::http::header::CONTENT_TYPE, content_type)
+                    .body(image)
+                    .unwrap(),
+                None => tauri::http::Response::builder()
+                    .status(tauri::http::StatusCode::NOT_FOUND)
+                    .body(Vec::new())
+                    .unwrap(),
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             get_launch_config,
+            get_status,
             setup,
             resolve_setup_error,
             get_setup_path,
+            get_battle_net_candidates,
+            select_battle_net_candidate,
             get_steam_accounts,
             confirm_steam_setup,
             undo_steam_setup,
+            set_steam_account_included,
+            mark_steam_accounts_seen,
+            set_include_new_steam_accounts,
             get_backgrounds,
+            query_backgrounds,
             set_background,
+            save_custom_background,
+            delete_custom_background,
+            set_favorite,
+            set_rotation,
+            rotate_background,
+            set_seasonal_calendar,
+            get_history,
+            revert_background,
             reset_background,
             set_debug_console,
+            get_launch_options,
+            set_launch_option,
+            remove_launch_option,
             reset
         ])
         .run(tauri::generate_context!())
@@ -1,13 +1,659 @@
+use crate::config::{self, Config, Target};
+use crate::helpers::{self, Error, Platform};
+use crate::transaction::Transaction;
+use crate::{backgrounds, custom_backgrounds};
+use serde::Serialize;
+use similar::TextDiff;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// A launcher that Overwatch can be started from, and whose launch arguments OverBuddy manages.
+///
+/// Commands operate on every enabled platform through this trait, so supporting another
+/// launcher only requires a new implementation added to [`PLATFORMS`].
+pub trait LauncherPlatform: Sync {
+    /// Name of the platform shown to the user.
+    fn name(&self) -> &'static str;
+
+    /// Identifier of the platform, attached to the errors it causes.
+    fn platform(&self) -> Platform;
+
+    /// Whether the platform is enabled in the OverBuddy configuration.
+    fn is_enabled(&self, config: &Config) -> bool;
+
+    /// Find the platform installation and save it in the configuration.
+    ///
+    /// Returns `false` if the platform could not be found.
+    fn discover(&self, config: &mut Config) -> Result<bool, Error>;
+
+    /// Files that are modified when launch arguments are applied.
+    fn files(&self, config: &Config) -> Vec<PathBuf>;
+
+    /// Targets of the platform that launch arguments are applied to.
+    fn targets(&self, config: &Config) -> Vec<Target>;
+
+    /// Read the current launch argument state of every target.
+    fn read_state(&self, config: &mut Config) -> Result<Vec<config::TargetConfig>, Error>;
+
+    /// Read the detailed state of every target without updating the configuration.
+    /// Unreadable files are reported on their target instead of failing.
+    fn status(&self, config: &Config) -> Vec<TargetStatus>;
+
+    /// Plan the changes a launch argument transform makes to the given targets, or to
+    /// every target if `None`. Nothing is written and unchanged files are left out.
+    fn plan(
+        &self,
+        config: &Config,
+        targets: Option<&[Target]>,
+        transform: &dyn Fn(Option<&str>) -> String,
+    ) -> Result<Vec<FileChange>, Error>;
+
+    /// Write a planned change.
+    ///
+    /// The launcher must be closed first, see [`apply_launch_args`].
+    fn write(&self, change: &FileChange) -> Result<(), Error>;
+
+    /// Apply a launch argument transform to the given targets, or to every target if `None`.
+    ///
+    /// The launcher must be closed first, see [`apply_launch_args`].
+    fn apply(
+        &self,
+        config: &Config,
+        targets: Option<&[Target]>,
+        transform: &dyn Fn(Option<&str>) -> String,
+    ) -> Result<(), Error> {
+        for change in self.plan(config, targets, transform)? {
+            self.write(&change)?;
+        }
+
+        Ok(())
+    }
+
+    /// Close all instances of the launcher. Returns `true` if any were closed.
+    fn close_app(&self) -> bool;
+
+    /// Reopen the launcher.
+    fn open_app(&self, config: &Config) -> Result<(), Error>;
+
+    /// Plan the removal of every launch argument managed by OverBuddy.
+    fn plan_reset(&self, config: &Config) -> Result<Vec<FileChange>, Error> {
+        if !has_background(config) && !has_console(config) {
+            return Ok(vec![]);
+        }
+
+        self.plan(config, None, &|launch_args| {
+            reset_launch_args(config, launch_args)
+        })
+    }
+
+    /// Remove every launch argument managed by OverBuddy.
+    ///
+    /// The launcher must be closed first, see [`reset_platforms`].
+    fn reset(&self, config: &Config) -> Result<(), Error> {
+        for change in self.plan_reset(config)? {
+            self.write(&change)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A pending change to a launcher file.
+pub struct FileChange {
+    pub path: PathBuf,
+    pub before: String,
+    pub after: String,
+}
+
+impl FileChange {
+    /// Unified diff of the change.
+    pub fn diff(&self) -> String {
+        let path = self.path.display().to_string();
+        TextDiff::from_lines(&self.before, &self.after)
+            .unified_diff()
+            .context_radius(3)
+            .header(&path, &path)
+            .to_string()
+    }
+}
+
+/// The detailed state of a target, see [`get_status`].
+#[derive(Serialize)]
+pub struct TargetStatus {
+    #[serde(flatten)]
+    pub state: config::TargetConfig,
+    /// File the launch arguments are stored in.
+    pub path: String,
+    /// Launch arguments as stored in the file.
+    pub launch_args: Option<String>,
+    /// Unix time in seconds the file was last modified.
+    pub modified: Option<u64>,
+    /// Why the file could not be read.
+    pub error: Option<Error>,
+}
+
+/// The state of every target, along with the state merged into one.
+#[derive(Serialize)]
+pub struct StatusReport {
+    pub targets: Vec<TargetStatus>,
+    /// The state of every readable target merged into one, see [`merge_states`].
+    pub summary: config::SharedConfig,
+}
+
+/// A launcher file change, shown to the user before it is applied.
+#[derive(Serialize)]
+pub struct FilePreview {
+    pub platform: Platform,
+    pub path: String,
+    /// Unified diff of the file contents.
+    pub diff: String,
+}
+
+/// The result of a command run in preview mode.
+#[derive(Serialize)]
+pub struct Preview<'a> {
+    /// The configuration that would be saved.
+    pub config: &'a Config,
+    pub files: &'a [FilePreview],
+}
+
+/// Remove the launch arguments that the configuration reports as applied.
+pub fn reset_launch_args(config: &Config, launch_args: Option<&str>) -> String {
+    let mut launch_args = launch_args.map(ToString::to_string);
+    if has_background(config) {
+        launch_args = Some(helpers::generate_background_launch_args(
+            launch_args.as_deref(),
+            None,
+        ));
+    }
+    if has_console(config) {
+        launch_args = Some(helpers::generate_console_launch_args(
+            launch_args.as_deref(),
+            false,
+        ));
+    }
+
+    launch_args.unwrap_or_default()
+}
+
+/// Whether any target has a background applied by OverBuddy.
+fn has_background(config: &Config) -> bool {
+    config.shared.background.current.is_some()
+        || config
+            .targets
+            .iter()
+            .any(|state| state.background.current.is_some())
+}
+
+/// Whether any target has the debug console enabled.
+fn has_console(config: &Config) -> bool {
+    config.shared.additional.console_enabled
+        || config
+            .targets
+            .iter()
+            .any(|state| state.additional.console_enabled)
+}
+
+/// Whether a target is one of the given targets, `None` selects every target.
+pub fn is_selected(targets: Option<&[Target]>, target: &Target) -> bool {
+    targets.is_none_or(|targets| targets.contains(target))
+}
+
+pub static PLATFORMS: &[&dyn LauncherPlatform] = &[&battle_net::BattleNet, &steam::Steam];
+
+/// Get all platforms that are enabled in the configuration.
+pub fn enabled_platforms(config: &Config) -> Vec<&'static dyn LauncherPlatform> {
+    PLATFORMS
+        .iter()
+        .copied()
+        .filter(|platform| platform.is_enabled(config))
+        .collect()
+}
+
+/// Get the enabled platforms that have any of the given targets, `None` selects every target.
+fn selected_platforms(
+    config: &Config,
+    targets: Option<&[Target]>,
+) -> Vec<&'static dyn LauncherPlatform> {
+    enabled_platforms(config)
+        .into_iter()
+        .filter(|platform| {
+            targets.is_none_or(|targets| {
+                targets
+                    .iter()
+                    .any(|target| target.platform() == platform.platform())
+            })
+        })
+        .collect()
+}
+
+/// Get the targets of all enabled platforms.
+pub fn targets(config: &Config) -> Vec<Target> {
+    enabled_platforms(config)
+        .into_iter()
+        .flat_map(|platform| platform.targets(config))
+        .collect()
+}
+
+/// Close the launcher while running an action, and reopen it afterwards if it was running.
+pub fn with_app_closed(
+    platform: &dyn LauncherPlatform,
+    config: &Config,
+    action: impl FnOnce() -> Result<(), Error>,
+) -> Result<(), Error> {
+    let was_closed = platform.close_app();
+    let result = action();
+    if was_closed {
+        platform.open_app(config).ok();
+    }
+
+    result
+}
+
+/// Remove every launch argument managed by OverBuddy from a platform.
+pub fn reset_platform(platform: &dyn LauncherPlatform, config: &Config) -> Result<(), Error> {
+    if !platform.is_enabled(config) {
+        return Ok(());
+    }
+
+    with_app_closed(platform, config, || platform.reset(config)).map_err(|mut error| {
+        error.platform.get_or_insert(platform.platform());
+        error
+    })
+}
+
+/// Run an operation on the enabled platforms with any of the given targets as a single
+/// transaction.
+///
+/// Every file the platforms may modify is snapshotted and their launchers are closed first.
+/// If any platform fails, every file that was already written is restored.
+fn run_transaction(
+    config: &Config,
+    targets: Option<&[Target]>,
+    action: &str,
+    operation: &dyn Fn(&dyn LauncherPlatform) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let platforms = selected_platforms(config, targets);
+
+    let mut transaction = Transaction::default();
+    for platform in &platforms {
+        for file in platform.files(config) {
+            transaction
+                .snapshot(&file)
+                .map_err(|error| error.with_context(format!("Failed to {}", action)))?;
+        }
+    }
+
+    let closed: Vec<_> = platforms
+        .iter()
+        .copied()
+        .filter(|platform| platform.close_app())
+        .collect();
+
+    let result = platforms.iter().try_for_each(|platform| {
+        operation(*platform).map_err(|mut error| {
+            error.platform.get_or_insert(platform.platform());
+            if platforms.len() > 1 {
+                error.with_context(format!("Failed to {} on {}", action, platform.name()))
+            } else {
+                error.with_context(format!("Failed to {}", action))
+            }
+        })
+    });
+
+    let result = match result {
+        Ok(()) => {
+            transaction.commit();
+            Ok(())
+        }
+        Err(error) => {
+            let rollback = transaction.rollback().describe();
+
+            if rollback.is_empty() {
+                Err(error)
+            } else {
+                Err(error.with_detail(rollback))
+            }
+        }
+    };
+
+    for platform in closed {
+        platform.open_app(config).ok();
+    }
+
+    result
+}
+
+/// Apply a launch argument transform to the given targets of all enabled platforms, or to
+/// every target if `None`, rolling back on failure.
+pub fn apply_launch_args(
+    config: &Config,
+    targets: Option<&[Target]>,
+    action: &str,
+    transform: &dyn Fn(Option<&str>) -> String,
+) -> Result<(), Error> {
+    run_transaction(config, targets, action, &|platform| {
+        platform.apply(config, targets, transform)
+    })
+}
+
+/// Remove every launch argument managed by OverBuddy from all enabled platforms.
+pub fn reset_platforms(config: &Config) -> Result<(), Error> {
+    run_transaction(config, None, "reset settings", &|platform| {
+        platform.reset(config)
+    })
+}
+
+/// Collect the planned changes of the selected platforms as diffs.
+///
+/// Launchers are left running and nothing is written.
+fn preview<F>(
+    config: &Config,
+    targets: Option<&[Target]>,
+    plan: F,
+) -> Result<Vec<FilePreview>, Error>
+where
+    F: Fn(&dyn LauncherPlatform) -> Result<Vec<FileChange>, Error>,
+{
+    let mut files = vec![];
+    for platform in selected_platforms(config, targets) {
+        let changes = plan(platform).map_err(|mut error| {
+            error.platform.get_or_insert(platform.platform());
+            error
+        })?;
+        files.extend(changes.into_iter().map(|change| FilePreview {
+            platform: platform.platform(),
+            path: change.path.display().to_string(),
+            diff: change.diff(),
+        }));
+    }
+
+    Ok(files)
+}
+
+/// Preview a launch argument transform, see [`apply_launch_args`].
+pub fn preview_launch_args(
+    config: &Config,
+    targets: Option<&[Target]>,
+    transform: &dyn Fn(Option<&str>) -> String,
+) -> Result<Vec<FilePreview>, Error> {
+    preview(config, targets, |platform| {
+        platform.plan(config, targets, transform)
+    })
+}
+
+/// Preview resetting all enabled platforms, see [`reset_platforms`].
+pub fn preview_reset(config: &Config) -> Result<Vec<FilePreview>, Error> {
+    preview(config, None, |platform| platform.plan_reset(config))
+}
+
+/// Resolve a background id found in launch arguments to one OverBuddy knows: a background
+/// from the catalog, one from the custom background library, or the applied custom id.
+pub fn resolve_background(config: &Config, id: &str) -> Option<String> {
+    backgrounds::find_background_by_id(id)
+        .map(|background| background.id)
+        .or_else(|| custom_backgrounds::find(config, id).map(|custom| custom.id.clone()))
+        .or_else(|| {
+            config
+                .shared
+                .background
+                .custom
+                .as_deref()
+                .filter(|custom_id| custom_id.eq_ignore_ascii_case(id))
+                .map(ToString::to_string)
+        })
+}
+
+/// Read the state of a target from its launch arguments.
+fn read_target(
+    config: &Config,
+    target: Target,
+    name: String,
+    launch_args: Option<&str>,
+) -> config::TargetConfig {
+    let mut state = config::TargetConfig {
+        target,
+        name,
+        background: config::BackgroundConfig {
+            current: None,
+            is_outdated: false,
+            custom: None,
+            retired: None,
+        },
+        additional: config::AdditionalConfig {
+            console_enabled: false,
+        },
+    };
+
+    if let Some(launch_args) = launch_args {
+        // Get current background from launch arguments
+        let current_background = helpers::get_background(launch_args);
+
+        // Save current background
+        state.background.current = current_background
+            .as_deref()
+            .and_then(|id| resolve_background(config, id));
+        state.background.is_outdated =
+            state.background.current.is_none() && current_background.is_some();
+        if state.background.is_outdated {
+            state.background.retired = current_background
+                .as_deref()
+                .and_then(backgrounds::find_retired_background);
+        }
+        state.background.custom = state
+            .background
+            .current
+            .clone()
+            .filter(|id| backgrounds::find_background_by_id(id).is_none());
+
+        // Save debug console state
+        state.additional.console_enabled = helpers::get_console_enabled(launch_args);
+    }
+
+    state
+}
+
+/// Merge the state of targets into a single shared configuration.
+///
+/// The background is only reported if every target agrees on it.
+pub fn merge_states<'a>(
+    config: &Config,
+    states: impl IntoIterator<Item = &'a config::TargetConfig>,
+) -> config::SharedConfig {
+    let states: Vec<&config::TargetConfig> = states.into_iter().collect();
+    let mut shared = config::SharedConfig {
+        background: config::BackgroundConfig {
+            current: None,
+            is_outdated: false,
+            custom: config.shared.background.custom.clone(),
+            retired: None,
+        },
+        additional: config::AdditionalConfig {
+            console_enabled: false,
+        },
+    };
+    if let Some((first, rest)) = states.split_first() {
+        shared.background.current = first.background.current.clone();
+        if rest
+            .iter()
+            .any(|state| state.background.current != first.background.current)
+        {
+            shared.background.current = None;
+        }
+        shared.background.is_outdated = states.iter().any(|state| state.background.is_outdated);
+        shared.background.retired = states
+            .iter()
+            .find_map(|state| state.background.retired.clone());
+        shared.additional.console_enabled =
+            states.iter().all(|state| state.additional.console_enabled);
+    }
+
+    shared
+}
+
+/// Read the state of every target of the enabled platforms into `config.targets`, and
+/// merge it into a single shared configuration.
+pub fn read_shared_state(config: &mut Config) -> Result<config::SharedConfig, Error> {
+    let mut states = vec![];
+    for platform in enabled_platforms(config) {
+        states.extend(platform.read_state(config)?);
+    }
+
+    let shared = merge_states(config, &states);
+    config.targets = states;
+
+    Ok(shared)
+}
+
+/// Build the status of a target from the result of reading its launch arguments.
+fn target_status(
+    config: &Config,
+    target: Target,
+    name: String,
+    path: &Path,
+    launch_args: Result<Option<String>, Error>,
+) -> TargetStatus {
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| modified.as_secs());
+    let (launch_args, error) = match launch_args {
+        Ok(launch_args) => (launch_args, None),
+        Err(error) => (None, Some(error)),
+    };
+
+    TargetStatus {
+        state: read_target(config, target, name, launch_args.as_deref()),
+        path: path.display().to_string(),
+        launch_args,
+        modified,
+        error,
+    }
+}
+
+/// Read the detailed state of every target of the enabled platforms.
+pub fn get_status(config: &Config) -> StatusReport {
+    let targets: Vec<TargetStatus> = enabled_platforms(config)
+        .into_iter()
+        .flat_map(|platform| platform.status(config))
+        .collect();
+    let summary = merge_states(
+        config,
+        targets
+            .iter()
+            .filter(|status| status.error.is_none())
+            .map(|status| &status.state),
+    );
+
+    StatusReport { targets, summary }
+}
+
 pub mod battle_net {
-    use crate::config::Config;
-    use crate::helpers::{self, Error};
-    use crate::{backgrounds, config};
+    use super::{
+        FileChange, LauncherPlatform, TargetStatus, is_selected, read_target, target_status,
+    };
+    use crate::config::{Config, Target};
+    use crate::helpers::{self, Error, ErrorCode, Platform};
+    use crate::{config, discovery};
     use serde_json::json;
+    use std::path::{Path, PathBuf};
     use std::process::Command;
     use sysinfo::System;
 
     pub static CONFIG_FILE: &str = "Battle.net.config";
 
+    pub struct BattleNet;
+
+    impl LauncherPlatform for BattleNet {
+        fn name(&self) -> &'static str {
+            "Battle.net"
+        }
+
+        fn platform(&self) -> Platform {
+            Platform::BattleNet
+        }
+
+        fn is_enabled(&self, config: &Config) -> bool {
+            config.battle_net.enabled
+        }
+
+        fn discover(&self, config: &mut Config) -> Result<bool, Error> {
+            if config.battle_net.install.is_none() {
+                let candidate = discovery::find_battle_net_candidates()
+                    .into_iter()
+                    .find(|candidate| candidate.install.is_some());
+                if let Some(candidate) = candidate {
+                    config.battle_net.install = candidate.install;
+                    config.battle_net.prefix = candidate.prefix;
+                    config.battle_net.runner = candidate.runner;
+                    if config.battle_net.config.is_none() {
+                        config.battle_net.config = candidate.config;
+                    }
+                }
+            }
+
+            Ok(config.battle_net.install.is_some() && config.battle_net.config.is_some())
+        }
+
+        fn files(&self, config: &Config) -> Vec<PathBuf> {
+            config.battle_net.config.iter().map(PathBuf::from).collect()
+        }
+
+        fn targets(&self, config: &Config) -> Vec<Target> {
+            config
+                .battle_net
+                .config
+                .iter()
+                .map(|_| Target::BattleNet)
+                .collect()
+        }
+
+        fn read_state(&self, config: &mut Config) -> Result<Vec<config::TargetConfig>, Error> {
+            update_config(config)
+        }
+
+        fn status(&self, config: &Config) -> Vec<TargetStatus> {
+            let Some(path) = &config.battle_net.config else {
+                return vec![];
+            };
+
+            vec![target_status(
+                config,
+                Target::BattleNet,
+                self.name().to_string(),
+                Path::new(path),
+                get_launch_args(config),
+            )]
+        }
+
+        fn plan(
+            &self,
+            config: &Config,
+            targets: Option<&[Target]>,
+            transform: &dyn Fn(Option<&str>) -> String,
+        ) -> Result<Vec<FileChange>, Error> {
+            if !is_selected(targets, &Target::BattleNet) {
+                return Ok(vec![]);
+            }
+
+            plan_launch_args(config, transform)
+        }
+
+        fn write(&self, change: &FileChange) -> Result<(), Error> {
+            write_change(change)
+        }
+
+        fn close_app(&self) -> bool {
+            close_app()
+        }
+
+        fn open_app(&self, config: &Config) -> Result<(), Error> {
+            open_app(config)
+        }
+    }
+
     /// Close all instances of Battle.net.
     pub fn close_app() -> bool {
         let mut flag = false;
@@ -21,27 +667,43 @@ pub mod battle_net {
         flag
     }
 
-    /// Set the Battle.net launch arguments.
-    pub fn set_launch_args<F, P>(
+    /// Open Battle.net, through the runner of its Wine prefix if it is installed in one.
+    pub fn open_app(config: &Config) -> Result<(), Error> {
+        let install = config.battle_net.install.clone().ok_or_else(|| {
+            Error::new(
+                ErrorCode::BattleNetInstall,
+                "Failed to find your Battle.net installation",
+            )
+            .with_platform(Platform::BattleNet)
+        })?;
+
+        let result = match &config.battle_net.prefix {
+            Some(prefix) => {
+                discovery::wine_command(config.battle_net.runner.as_ref(), prefix, &install).spawn()
+            }
+            None => Command::new(&install).spawn(),
+        };
+
+        result.map(|_| ()).map_err(|e| {
+            Error::new(
+                ErrorCode::BattleNetInstall,
+                format!("Failed to open Battle.net at [[{}]]", install),
+            )
+            .with_platform(Platform::BattleNet)
+            .with_path(&install)
+            .with_io(&e)
+        })
+    }
+
+    /// Plan the change to the Battle.net launch arguments.
+    pub fn plan_launch_args(
         config: &Config,
-        params: P,
-        generate_launch_args: F,
-    ) -> Result<(), Error>
-    where
-        F: Fn(Option<&str>, P) -> String,
-    {
-        let battle_net_was_closed = close_app();
+        transform: &dyn Fn(Option<&str>) -> String,
+    ) -> Result<Vec<FileChange>, Error> {
         let battle_net_config = config.battle_net.config.clone().unwrap();
-        let battle_net_cleanup: Box<dyn FnOnce()> = Box::new(move || {
-            if battle_net_was_closed {
-                Command::new(config.battle_net.install.clone().unwrap())
-                    .spawn()
-                    .ok();
-            }
-        });
 
         // Read config file
-        let mut json = read_config(&config)?;
+        let (contents, mut json) = read_config_contents(config)?;
 
         // Check Overwatch installation on Battle.net
         let overwatch_config = match json
@@ -50,144 +712,314 @@ pub mod battle_net {
         {
             Some(config) => config,
             None => {
-                battle_net_cleanup();
-                return Err(Error::Custom(
-                    "Unable to find an Overwatch installation on Battle.net. If you have changed your Battle.net installation, please reset settings".to_string(),
+                return Err(Error::new(
+                    ErrorCode::NoOverwatch,
+                    "Unable to find an Overwatch installation on Battle.net",
+                )
+                .with_platform(Platform::BattleNet)
+                .with_path(&battle_net_config)
+                .with_hint(
+                    "If you have changed your Battle.net installation, please reset settings.",
                 ));
             }
         };
 
-        // Get launch arguments config
-        let launch_args = match overwatch_config.get_mut("AdditionalLaunchArguments") {
-            Some(launch_args) => launch_args.as_str(),
-            None => {
-                overwatch_config
-                    .as_object_mut()
-                    .unwrap()
-                    .insert("AdditionalLaunchArguments".to_string(), json!(""));
-                overwatch_config.as_str()
-            }
-        };
-
         // Set launch arguments
-        let new_launch_args = generate_launch_args(launch_args, params);
-        json["Games"]["prometheus"]["AdditionalLaunchArguments"] = json!(new_launch_args);
+        let launch_args = overwatch_config
+            .get("AdditionalLaunchArguments")
+            .and_then(|launch_args| launch_args.as_str());
+        let new_launch_args = transform(launch_args);
+        if launch_args == Some(new_launch_args.as_str()) {
+            return Ok(vec![]);
+        }
+        overwatch_config.as_object_mut().unwrap().insert(
+            "AdditionalLaunchArguments".to_string(),
+            json!(new_launch_args),
+        );
+
+        Ok(vec![FileChange {
+            path: PathBuf::from(battle_net_config),
+            before: contents,
+            after: helpers::to_pretty_json(&json)?,
+        }])
+    }
 
-        helpers::safe_json_write(battle_net_config, &json)?;
-        battle_net_cleanup();
+    /// Write a planned change to the Battle.net config file.
+    ///
+    /// Battle.net must be closed, otherwise it overwrites the config file when it exits.
+    pub fn write_change(change: &FileChange) -> Result<(), Error> {
+        let json: serde_json::Value = serde_json::from_str(&change.after)?;
+        helpers::safe_json_write(change.path.to_string_lossy().to_string(), &json)
+    }
 
-        Ok(())
+    /// Read the state of the Battle.net launch arguments.
+    pub fn update_config(config: &Config) -> Result<Vec<config::TargetConfig>, Error> {
+        let launch_args = get_launch_args(config)?;
+
+        Ok(vec![read_target(
+            config,
+            Target::BattleNet,
+            BattleNet.name().to_string(),
+            launch_args.as_deref(),
+        )])
     }
 
-    /// Update OverBuddy configuration with the current state of the Battle.net.config file.
-    ///
-    /// **Warning**: This function modifies the shared configuration fields.
-    pub fn update_config(config: &Config) -> Result<Option<config::SharedConfig>, Error> {
+    /// Read the Overwatch launch arguments from the Battle.net config file.
+    fn get_launch_args(config: &Config) -> Result<Option<String>, Error> {
         let json = read_config(config)?;
-        let mut shared_config = config::SharedConfig {
-            background: config::BackgroundConfig {
-                current: None,
-                is_outdated: false,
-                custom: config.shared.background.custom.clone(),
-            },
-            additional: config::AdditionalConfig {
-                console_enabled: false,
-            },
-        };
 
-        if let Some(launch_args) = json
+        Ok(json
             .get("Games")
             .and_then(|games| games.get("prometheus"))
             .and_then(|overwatch| overwatch.get("AdditionalLaunchArguments"))
             .and_then(|launch_args| launch_args.as_str())
-        {
-            // Get current background from launch arguments
-            let current_background = helpers::get_background(launch_args);
-
-            // Save current background
-            shared_config.background.current = current_background.as_ref().and_then(|id| {
-                backgrounds::find_background_by_id(id)
-                    .map(|background| background.id.to_string())
-                    .or_else(|| {
-                        shared_config
-                            .background
-                            .custom
-                            .as_deref()
-                            .filter(|&custom_id| custom_id == id)
-                            .map(ToString::to_string)
-                    })
-            });
-            shared_config.background.is_outdated =
-                shared_config.background.current.is_none() && current_background.is_some();
-
-            // Save debug console state
-            shared_config.additional.console_enabled = helpers::get_console_enabled(launch_args);
-        }
-
-        Ok(Some(shared_config))
-    }
-
-    /// Reset all Battle.net configuration.
-    pub fn reset_config(config: &Config) -> Result<(), Error> {
-        if config.battle_net.enabled {
-            // Reset background
-            if config.shared.background.current.is_some() {
-                set_launch_args(&config, None, helpers::generate_background_launch_args)?;
-            }
-            // Reset debug console state
-            if config.shared.additional.console_enabled {
-                set_launch_args(&config, false, helpers::generate_console_launch_args)?;
-            }
-        }
-
-        Ok(())
+            .map(ToString::to_string))
     }
 
     fn read_config(config: &Config) -> Result<serde_json::Value, Error> {
+        read_config_contents(config).map(|(_, json)| json)
+    }
+
+    /// Read the Battle.net.config file, returning its contents along with the parsed JSON.
+    fn read_config_contents(config: &Config) -> Result<(String, serde_json::Value), Error> {
         let battle_net_config = config.battle_net.config.clone().unwrap();
 
         // Read and parse Battle.net.config file
-        let file = match std::fs::File::open(&battle_net_config) {
-            Ok(file) => file,
+        let contents = match std::fs::read_to_string(&battle_net_config) {
+            Ok(contents) => contents,
             Err(e) => {
-                return Err(Error::Custom(format!(
-                    "Failed to open [[{}]] file at [[{}]]: {}. If you have changed your Battle.net installation, please reset settings",
-                    CONFIG_FILE, battle_net_config, e
-                )));
+                return Err(Error::new(
+                    ErrorCode::BattleNetConfig,
+                    format!(
+                        "Failed to open [[{}]] file at [[{}]]: {}",
+                        CONFIG_FILE, battle_net_config, e
+                    ),
+                )
+                .with_platform(Platform::BattleNet)
+                .with_path(&battle_net_config)
+                .with_io(&e)
+                .with_hint(
+                    "If you have changed your Battle.net installation, please reset settings.",
+                ));
             }
         };
-        let json: serde_json::Value = match serde_json::from_reader(file) {
+        let json: serde_json::Value = match serde_json::from_str(&contents) {
             Ok(json) => json,
             Err(e) => {
-                return Err(Error::Custom(format!(
-                    "Failed to read [[{}]] file at [[{}]]: {}",
-                    CONFIG_FILE, battle_net_config, e
-                )));
+                return Err(Error::new(
+                    ErrorCode::BattleNetConfig,
+                    format!(
+                        "Failed to read [[{}]] file at [[{}]]: {}",
+                        CONFIG_FILE, battle_net_config, e
+                    ),
+                )
+                .with_platform(Platform::BattleNet)
+                .with_path(&battle_net_config));
             }
         };
 
-        Ok(json)
+        Ok((contents, json))
     }
 }
 
 pub mod steam {
-    use crate::backgrounds;
-    use crate::config::{self, Config, SteamProfile};
-    use crate::helpers::{self, Error};
+    use super::{
+        FileChange, LauncherPlatform, TargetStatus, is_selected, read_target, target_status,
+    };
+    use crate::config::{self, Config, SteamProfile, Target};
+    use crate::helpers::{Error, ErrorCode, Platform};
+    use crate::launch_args::LaunchArgs;
+    use crate::vdf;
     use similar::{ChangeTag, TextDiff};
-    use std::collections::VecDeque;
+    use std::env;
     use std::fs::{self, File};
-    use std::io::{self, BufRead, BufReader, Read, Write};
-    use std::os::windows::process::CommandExt; // NOTE: Windows only
-    use std::path::Path;
+    use std::io::{self, BufRead, BufReader};
+    #[cfg(windows)]
+    use std::os::windows::process::CommandExt;
+    use std::path::{Path, PathBuf};
     use std::process::Command;
     use sysinfo::System;
 
+    pub struct Steam;
+
+    impl LauncherPlatform for Steam {
+        fn name(&self) -> &'static str {
+            "Steam"
+        }
+
+        fn platform(&self) -> Platform {
+            Platform::Steam
+        }
+
+        fn is_enabled(&self, config: &Config) -> bool {
+            config.steam.enabled
+        }
+
+        fn discover(&self, config: &mut Config) -> Result<bool, Error> {
+            if config.steam.install.is_none() {
+                config.steam.install =
+                    find_install().map(|steam_install| steam_install.to_string_lossy().to_string());
+            }
+            if config.steam.install.is_none() {
+                return Ok(false);
+            }
+
+            config.steam.configs = Some(get_configs(config)?);
+            Ok(config
+                .steam
+                .configs
+                .as_ref()
+                .is_some_and(|configs| !configs.is_empty()))
+        }
+
+        fn files(&self, config: &Config) -> Vec<PathBuf> {
+            config
+                .steam
+                .configs
+                .iter()
+                .flatten()
+                .filter(|steam_config| is_managed(config, &steam_config.id).unwrap_or(false))
+                .map(|steam_config| PathBuf::from(&steam_config.file))
+                .collect()
+        }
+
+        fn targets(&self, config: &Config) -> Vec<Target> {
+            config
+                .steam
+                .configs
+                .iter()
+                .flatten()
+                .filter(|steam_config| is_managed(config, &steam_config.id).unwrap_or(false))
+                .map(|steam_config| Target::Steam(steam_config.id.clone()))
+                .collect()
+        }
+
+        fn read_state(&self, config: &mut Config) -> Result<Vec<config::TargetConfig>, Error> {
+            // Accounts are only read once the user has confirmed the Steam setup
+            if config.steam.in_setup {
+                return Ok(vec![]);
+            }
+
+            update_config(config)
+        }
+
+        fn status(&self, config: &Config) -> Vec<TargetStatus> {
+            if config.steam.in_setup {
+                return vec![];
+            }
+
+            config
+                .steam
+                .configs
+                .iter()
+                .flatten()
+                .filter(|steam_config| is_managed(config, &steam_config.id).unwrap_or(false))
+                .map(|steam_config| {
+                    target_status(
+                        config,
+                        Target::Steam(steam_config.id.clone()),
+                        profile_name(config, &steam_config.id),
+                        Path::new(&steam_config.file),
+                        get_config_launch_args(&steam_config.file),
+                    )
+                })
+                .collect()
+        }
+
+        fn plan(
+            &self,
+            config: &Config,
+            targets: Option<&[Target]>,
+            transform: &dyn Fn(Option<&str>) -> String,
+        ) -> Result<Vec<FileChange>, Error> {
+            plan_launch_args(config, targets, transform)
+        }
+
+        fn write(&self, change: &FileChange) -> Result<(), Error> {
+            write_change(change)
+        }
+
+        fn close_app(&self) -> bool {
+            close_app()
+        }
+
+        fn open_app(&self, config: &Config) -> Result<(), Error> {
+            open_app(config);
+            Ok(())
+        }
+    }
+
+    /// URI scheme used to serve avatars from Steam's local avatar cache.
+    pub const AVATAR_PROTOCOL: &str = "steam-avatar";
+
+    const OVERWATCH_STEAM_URL: &str = "steam://open/games/details/2357570";
+    const FLATPAK_APP_ID: &str = "com.valvesoftware.Steam";
+
+    /// Find the Steam installation in its default locations.
+    ///
+    /// On Linux this checks the native (`~/.steam/steam`, `~/.local/share/Steam`) and Flatpak installs.
+    pub fn find_install() -> Option<PathBuf> {
+        let launcher = if cfg!(windows) {
+            "steam.exe"
+        } else {
+            "steam.sh"
+        };
+
+        get_default_paths()
+            .into_iter()
+            .find(|steam_path| {
+                steam_path.join(launcher).exists() || steam_path.join("userdata").is_dir()
+            })
+            .map(|steam_path| steam_path.join(launcher))
+    }
+
+    /// Get the default Steam directories for this platform.
+    pub fn get_default_paths() -> Vec<PathBuf> {
+        if cfg!(windows) {
+            return env::var_os("programfiles(x86)")
+                .map(|program_files_dir| PathBuf::from(program_files_dir).join("Steam"))
+                .into_iter()
+                .collect();
+        }
+
+        let Some(home) = env::var_os("HOME").map(PathBuf::from) else {
+            return vec![];
+        };
+        let mut paths = vec![
+            home.join(".steam").join("steam"),
+            home.join(".local").join("share").join("Steam"),
+            home.join(".var")
+                .join("app")
+                .join(FLATPAK_APP_ID)
+                .join(".local")
+                .join("share")
+                .join("Steam"),
+        ];
+
+        // ~/.steam/steam is usually a symlink to ~/.local/share/Steam
+        let mut seen = vec![];
+        paths.retain(|path| {
+            let canonical = dunce::canonicalize(path).unwrap_or_else(|_| path.clone());
+            if seen.contains(&canonical) {
+                return false;
+            }
+            seen.push(canonical);
+            true
+        });
+
+        paths
+    }
+
     /// Close all instances of Steam.
     pub fn close_app() -> bool {
         let mut flag = false;
         let system = System::new_all();
-        for process in system.processes_by_name("steam.exe".as_ref()) {
+        let processes: Vec<_> = if cfg!(windows) {
+            system.processes_by_name("steam.exe".as_ref()).collect()
+        } else {
+            system.processes_by_exact_name("steam".as_ref()).collect()
+        };
+        for process in processes {
             if process.kill() {
                 flag = true;
             }
@@ -196,11 +1028,43 @@ pub mod steam {
         flag
     }
 
+    /// Open Steam on the Overwatch page.
+    #[cfg(windows)]
+    fn open_app(_config: &Config) {
+        Command::new("cmd")
+            .args(["/C", "start", OVERWATCH_STEAM_URL])
+            .creation_flags(0x0800_0000)
+            .spawn()
+            .ok();
+    }
+
+    /// Open Steam on the Overwatch page.
+    #[cfg(not(windows))]
+    fn open_app(config: &Config) {
+        let is_flatpak = config
+            .steam
+            .install
+            .as_deref()
+            .is_some_and(|install| install.contains(FLATPAK_APP_ID));
+
+        if is_flatpak {
+            Command::new("flatpak")
+                .args(["run", FLATPAK_APP_ID, OVERWATCH_STEAM_URL])
+                .spawn()
+                .ok();
+        } else {
+            Command::new("steam").arg(OVERWATCH_STEAM_URL).spawn().ok();
+        }
+    }
+
     /// Get all Steam profiles from the Steam config files.
     pub fn get_profiles(config: &Config) -> Result<Vec<SteamProfile>, Error> {
         let mut profiles: Vec<SteamProfile> = vec![];
 
         if let Some(available_configs) = &config.steam.configs {
+            let steam_path = get_steam_path(config)?;
+            let login_users = read_login_users(&steam_path)?;
+
             for steam_config in available_configs {
                 let config_path = Path::new(&steam_config.file);
 
@@ -208,162 +1072,236 @@ pub mod steam {
                     continue;
                 }
 
-                match extract_steam_user_info(steam_config, config_path) {
-                    Ok(profile) => profiles.push(profile),
+                match extract_steam_user_info(
+                    steam_config,
+                    config_path,
+                    &steam_path,
+                    login_users.as_ref(),
+                    config.steam.overwatch.as_ref(),
+                ) {
+                    Ok(mut profile) => {
+                        // Keep the choice of known accounts, other accounts start with the
+                        // configured default and are new if they were found after the setup
+                        let known = config.steam.profiles.as_ref();
+                        match known
+                            .and_then(|known| known.iter().find(|known| known.id == profile.id))
+                        {
+                            Some(known) => {
+                                profile.included = known.included;
+                                profile.is_new = known.is_new;
+                            }
+                            None => {
+                                profile.included = config.steam.include_new_accounts;
+                                profile.is_new = known.is_some();
+                            }
+                        }
+                        profiles.push(profile);
+                    }
                     Err(err) => {
-                        return Err(Error::Custom(format!(
-                            "{} while reading config file [[{}]] for Steam account [[{}]]",
-                            err, steam_config.file, steam_config.id,
-                        )));
+                        return Err(Error {
+                            message: format!(
+                                "{} while reading config file [[{}]] for Steam account [[{}]]",
+                                err.message, steam_config.file, steam_config.id,
+                            ),
+                            ..err
+                        }
+                        .with_platform(Platform::Steam));
                     }
                 }
             }
 
             if profiles.len() < available_configs.len() {
-                return Err(Error::Custom(format!(
-                    "Failed to find all accounts in your Steam [[userdata]] folder at [[{}]]",
-                    config.steam.install.clone().unwrap()
-                )));
+                return Err(error(
+                    ErrorCode::SteamAccount,
+                    format!(
+                        "Failed to find all accounts in your Steam [[userdata]] folder at [[{}]]",
+                        config.steam.install.clone().unwrap()
+                    ),
+                ));
             }
         }
 
         Ok(profiles)
     }
 
-    /// Set the Steam launch arguments.
-    pub fn set_launch_args<F, P>(
+    /// Find the Overwatch installation across all Steam libraries.
+    ///
+    /// Reads `steamapps/libraryfolders.vdf` and the `appmanifest_2357570.acf` of each library.
+    pub fn get_overwatch_install(
         config: &Config,
-        params: P,
-        generate_launch_args: F,
-    ) -> Result<(), Error>
-    where
-        F: Fn(Option<&str>, P) -> String,
-        P: Clone,
-    {
+    ) -> Result<Option<config::SteamAppInstall>, Error> {
+        let steam_path = get_steam_path(config)?;
+
+        // Collect library paths, the Steam installation is always a library
+        let mut libraries = vec![steam_path.to_path_buf()];
+        let library_folders_path = steam_path.join("steamapps").join(LIBRARY_FOLDERS_FILE);
+        if let Ok(contents) = fs::read_to_string(&library_folders_path) {
+            let document = vdf::Document::parse(contents).map_err(|err| {
+                error(
+                    ErrorCode::SteamConfig,
+                    format!(
+                        "Failed to parse Steam library folders at [[{}]]: {}",
+                        library_folders_path.display(),
+                        err
+                    ),
+                )
+                .with_path(&library_folders_path)
+            })?;
+
+            let folders = document
+                .root()
+                .entries()
+                .and_then(|entries| entries.first())
+                .and_then(|entry| entry.value.entries())
+                .unwrap_or_default();
+            for folder in folders {
+                if !folder.key.chars().all(|c| c.is_ascii_digit()) {
+                    continue;
+                }
+                // Older Steam versions store the path directly as the value
+                let library_path = match &folder.value {
+                    vdf::Value::String { value, .. } => Some(value.as_str()),
+                    object => object.get("path").and_then(vdf::Value::as_str),
+                };
+                if let Some(library_path) = library_path {
+                    let library_path = PathBuf::from(library_path);
+                    if !libraries.contains(&library_path) {
+                        libraries.push(library_path);
+                    }
+                }
+            }
+        }
+
+        // Find the library that contains the Overwatch app manifest
+        for library in libraries {
+            let steamapps_path = library.join("steamapps");
+            let manifest_path = steamapps_path.join(OVERWATCH_MANIFEST_FILE);
+            let Ok(contents) = fs::read_to_string(&manifest_path) else {
+                continue;
+            };
+            let document = vdf::Document::parse(contents).map_err(|err| {
+                error(
+                    ErrorCode::SteamConfig,
+                    format!(
+                        "Failed to parse the Overwatch app manifest at [[{}]]: {}",
+                        manifest_path.display(),
+                        err
+                    ),
+                )
+                .with_path(&manifest_path)
+            })?;
+
+            let Some(install_dir) = document.get_str(&["AppState", "installdir"]) else {
+                continue;
+            };
+            let install_path = steamapps_path.join("common").join(install_dir);
+            if !install_path.is_dir() {
+                continue;
+            }
+
+            let branch = document
+                .get_str(&["AppState", "UserConfig", "BetaKey"])
+                .or_else(|| document.get_str(&["AppState", "MountedConfig", "BetaKey"]))
+                .filter(|branch| !branch.is_empty())
+                .unwrap_or("public");
+
+            return Ok(Some(config::SteamAppInstall {
+                library: library.to_string_lossy().to_string(),
+                install_dir: install_path.to_string_lossy().to_string(),
+                build_id: document
+                    .get_str(&["AppState", "buildid"])
+                    .map(ToString::to_string),
+                branch: branch.to_string(),
+                last_owner: document
+                    .get_str(&["AppState", "LastOwner"])
+                    .filter(|owner| *owner != "0")
+                    .map(ToString::to_string),
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Plan the change to the launch arguments of the given included Steam accounts with
+    /// Overwatch, or of every included account if `None`.
+    pub fn plan_launch_args(
+        config: &Config,
+        targets: Option<&[Target]>,
+        transform: &dyn Fn(Option<&str>) -> String,
+    ) -> Result<Vec<FileChange>, Error> {
         let steam_configs = config.steam.configs.as_ref().unwrap();
         if steam_configs.is_empty() {
-            return Err(Error::Custom(
-                "Failed to find any accounts in your Steam [[userdata]] folder".into(),
+            return Err(error(
+                ErrorCode::SteamAccount,
+                "Failed to find any accounts in your Steam [[userdata]] folder",
             ));
         }
 
-        let steam_was_closed = close_app();
-        let steam_cleanup: Box<dyn FnOnce()> = Box::new(move || {
-            if steam_was_closed {
-                Command::new("cmd")
-                    .args(["/C", "start", "steam://open/games/details/2357570"])
-                    .creation_flags(0x0800_0000)
-                    .spawn()
-                    .ok();
-            }
-        });
-
-        // Modify each Steam localconfig.vdf file
+        // Plan changes to each Steam localconfig.vdf file
+        let mut changes = vec![];
         for steam_config in steam_configs {
-            if !is_overwatch_installed(config, &steam_config.id)? {
+            if !is_selected(targets, &Target::Steam(steam_config.id.clone()))
+                || !is_managed(config, &steam_config.id)?
+            {
                 continue;
             }
 
-            let result = set_config_launch_args(
-                steam_config.file.as_str(),
-                params.clone(),
-                &generate_launch_args,
-            );
-
-            if result.is_err() {
-                steam_cleanup();
-                return Err(result.err().unwrap());
+            if let Some(change) = plan_config_launch_args(steam_config.file.as_str(), transform)? {
+                changes.push(change);
             }
         }
 
-        steam_cleanup();
-        Ok(())
+        Ok(changes)
     }
 
-    /// Update OverBuddy configuration with the current state of the Battle.net.config file.
-    ///
-    /// **Warning**: This function modifies the shared configuration fields.
-    pub fn update_config(config: &mut Config) -> Result<Option<config::SharedConfig>, Error> {
+    /// Update the Steam accounts in the OverBuddy configuration, and read the state of the
+    /// launch arguments of every included account with Overwatch.
+    pub fn update_config(config: &mut Config) -> Result<Vec<config::TargetConfig>, Error> {
         // Update config files
         config.steam.configs = Some(get_configs(config)?);
 
         if config.steam.configs.is_none() || config.steam.configs.as_ref().unwrap().is_empty() {
-            return Err(Error::Custom(
-                "Failed to find any accounts in your Steam [[userdata]] folder".to_string(),
+            return Err(error(
+                ErrorCode::SteamAccount,
+                "Failed to find any accounts in your Steam [[userdata]] folder",
             ));
         }
 
         // Update profiles
-        config.steam.profiles = Some(get_profiles(&config)?);
-
-        // Update configuration state
-        let mut shared_config = config::SharedConfig {
-            background: config::BackgroundConfig {
-                current: None,
-                is_outdated: false,
-                custom: config.shared.background.custom.clone(),
-            },
-            additional: config::AdditionalConfig {
-                console_enabled: false,
-            },
-        };
-        let mut background_conflict = false;
+        config.steam.overwatch = get_overwatch_install(config)?;
+        config.steam.profiles = Some(get_profiles(config)?);
 
-        // Update shared state
-        if let Some(available_configs) = &config.steam.configs {
-            for steam_config in available_configs {
-                if !is_overwatch_installed(config, &steam_config.id)? {
-                    continue;
-                }
+        // Read the state of each account
+        let mut states = vec![];
+        for steam_config in config.steam.configs.iter().flatten() {
+            if !is_managed(config, &steam_config.id)? {
+                continue;
+            }
 
-                // NOTE: Currently fails when config is malformed instead of continuing.
-                let launch_args = get_config_launch_args(&steam_config.file)?.0;
-
-                if let Some(launch_args) = launch_args {
-                    // Save current background
-                    let current_background = helpers::get_background(&launch_args);
-                    let resolved_background =
-                        current_background
-                            .as_ref()
-                            .and_then(|current_background_id| {
-                                backgrounds::find_background_by_id(current_background_id)
-                                    .map(|background| background.id.to_string())
-                                    .or_else(|| {
-                                        shared_config
-                                            .background
-                                            .custom
-                                            .as_deref()
-                                            .filter(|&custom_id| custom_id == current_background_id)
-                                            .map(ToString::to_string)
-                                    })
-                            });
-
-                    if !background_conflict && resolved_background.is_some() {
-                        if shared_config.background.current.is_some() {
-                            if resolved_background.unwrap()
-                                != shared_config.background.current.clone().unwrap()
-                            {
-                                shared_config.background.current = None;
-                                shared_config.background.is_outdated = false;
-
-                                background_conflict = true;
-                            }
-                        } else {
-                            shared_config.background.current = resolved_background;
-                            shared_config.background.is_outdated = false;
-                        }
-                    } else if resolved_background.is_none() && current_background.is_some() {
-                        shared_config.background.is_outdated = true;
-                    }
+            // NOTE: Currently fails when config is malformed instead of continuing.
+            let launch_args = get_config_launch_args(&steam_config.file)?;
 
-                    // Save debug console state
-                    shared_config.additional.console_enabled =
-                        helpers::get_console_enabled(&launch_args);
-                }
-            }
+            states.push(read_target(
+                config,
+                Target::Steam(steam_config.id.clone()),
+                profile_name(config, &steam_config.id),
+                launch_args.as_deref(),
+            ));
         }
 
-        Ok(Some(shared_config))
+        Ok(states)
+    }
+
+    /// Get the name of a Steam account, its id if the profile is unknown.
+    fn profile_name(config: &Config, steam_id: &str) -> String {
+        config
+            .steam
+            .profiles
+            .iter()
+            .flatten()
+            .find(|profile| profile.id == steam_id)
+            .map(|profile| profile.name.clone())
+            .unwrap_or_else(|| steam_id.to_string())
     }
 
     /// Get all Steam configs from the Steam installation.
@@ -371,15 +1309,7 @@ pub mod steam {
         let mut configs: Vec<config::SteamLocalconfig> = vec![];
 
         // Get Steam path
-        let steam_install =
-            config.steam.install.clone().ok_or_else(|| {
-                Error::Custom("Failed to find your Steam installation".to_string())
-            })?;
-        let steam_path = Path::new(&steam_install).parent().ok_or_else(|| {
-            Error::Custom(
-                "Failed to read the parent directory of your Steam installation".to_string(),
-            )
-        })?;
+        let steam_path = get_steam_path(config)?;
 
         // Fetch Steam userdata
         static CONFIG_FILE: &str = "localconfig.vdf";
@@ -402,224 +1332,212 @@ pub mod steam {
         Ok(configs)
     }
 
-    /// Reset all Steam configuration.
-    pub fn reset_config(config: &Config) -> Result<(), Error> {
-        if config.steam.enabled {
-            // Reset background
-            if config.shared.background.current.is_some() {
-                set_launch_args(&config, None, helpers::generate_background_launch_args)?;
-            }
-            // Reset debug console state
-            if config.shared.additional.console_enabled {
-                set_launch_args(&config, false, helpers::generate_console_launch_args)?;
-            }
+    /// Get the Steam directory from the Steam installation.
+    fn get_steam_path(config: &Config) -> Result<PathBuf, Error> {
+        let steam_install = config.steam.install.clone().ok_or_else(|| {
+            error(
+                ErrorCode::SteamInstall,
+                "Failed to find your Steam installation".to_string(),
+            )
+        })?;
+        let steam_path = Path::new(&steam_install).parent().ok_or_else(|| {
+            error(
+                ErrorCode::SteamInstall,
+                "Failed to read the parent directory of your Steam installation",
+            )
+        })?;
+
+        Ok(steam_path.to_path_buf())
+    }
+
+    fn error(code: ErrorCode, message: impl Into<String>) -> Error {
+        Error::new(code, message).with_platform(Platform::Steam)
+    }
+
+    const LIBRARY_FOLDERS_FILE: &str = "libraryfolders.vdf";
+    const OVERWATCH_MANIFEST_FILE: &str = "appmanifest_2357570.acf";
+    const STEAM_ID64_BASE: u64 = 76561197960265728;
+    const LOGIN_USERS_FILE: &str = "loginusers.vdf";
+    const OVERWATCH_PATH: [&str; 6] = [
+        "UserLocalConfigStore",
+        "Software",
+        "Valve",
+        "Steam",
+        "apps",
+        "2357570",
+    ];
+    const LAUNCH_OPTIONS_PATH: [&str; 7] = [
+        "UserLocalConfigStore",
+        "Software",
+        "Valve",
+        "Steam",
+        "apps",
+        "2357570",
+        "LaunchOptions",
+    ];
+
+    /// Convert a Steam account id (as used in the userdata folder) to a SteamID64.
+    fn to_steam_id64(account_id: &str) -> Option<String> {
+        account_id
+            .parse::<u64>()
+            .ok()
+            .map(|account_id| (STEAM_ID64_BASE + account_id).to_string())
+    }
+
+    /// Build the URL the UI uses to load a cached avatar through [`AVATAR_PROTOCOL`].
+    fn avatar_url(steam_id64: &str) -> String {
+        if cfg!(windows) {
+            format!("http://{}.localhost/{}", AVATAR_PROTOCOL, steam_id64)
+        } else {
+            format!("{}://localhost/{}", AVATAR_PROTOCOL, steam_id64)
         }
+    }
 
-        Ok(())
+    fn get_avatar_path(steam_path: &Path, steam_id64: &str) -> Option<PathBuf> {
+        // Only accept SteamID64s to avoid reading anything outside the avatar cache
+        if steam_id64.is_empty() || !steam_id64.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let avatar_path = steam_path
+            .join("config")
+            .join("avatarcache")
+            .join(format!("{}.png", steam_id64));
+        avatar_path.is_file().then_some(avatar_path)
     }
 
-    const STEAM_AVATAR_URL: &str = "https://avatars.akamai.steamstatic.com";
+    /// Read a cached avatar from Steam's local avatar cache.
+    pub fn get_avatar(config: &Config, steam_id64: &str) -> Option<Vec<u8>> {
+        let steam_path = get_steam_path(config).ok()?;
+        let avatar_path = get_avatar_path(&steam_path, steam_id64)?;
+
+        fs::read(avatar_path).ok()
+    }
+
+    fn read_login_users(steam_path: &Path) -> Result<Option<vdf::Document>, Error> {
+        let login_users_path = steam_path.join("config").join(LOGIN_USERS_FILE);
+        let Ok(contents) = fs::read_to_string(&login_users_path) else {
+            return Ok(None);
+        };
+
+        vdf::Document::parse(contents).map(Some).map_err(|err| {
+            error(
+                ErrorCode::SteamConfig,
+                format!(
+                    "Failed to parse Steam login users at [[{}]]: {}",
+                    login_users_path.display(),
+                    err
+                ),
+            )
+            .with_path(&login_users_path)
+        })
+    }
 
     fn extract_steam_user_info(
         steam_config: &config::SteamLocalconfig,
         config_path: &Path,
+        steam_path: &Path,
+        login_users: Option<&vdf::Document>,
+        overwatch_install: Option<&config::SteamAppInstall>,
     ) -> Result<SteamProfile, Error> {
         let contents = match fs::read_to_string(config_path) {
             Ok(contents) => contents,
             Err(err) => {
-                return Err(Error::Custom(format!(
-                    "Failed to read config file at [[{}]]. {}",
-                    steam_config.file, err
-                )));
+                return Err(error(
+                    ErrorCode::SteamConfig,
+                    format!(
+                        "Failed to read config file at [[{}]]. {}",
+                        steam_config.file, err
+                    ),
+                )
+                .with_path(config_path)
+                .with_io(&err));
             }
         };
+        let document = vdf::Document::parse(contents)?;
 
-        let outer_key = "\"UserLocalConfigStore\"";
-        let middle_key = "\n\t\"friends\"";
         let id = steam_config.id.as_str();
-
-        if let Some(outer_start) = contents.to_lowercase().find(&outer_key.to_lowercase()) {
-            if let Some(middle_start) = contents[outer_start..]
-                .to_lowercase()
-                .find(&middle_key.to_lowercase())
-            {
-                if let Some(id_start) =
-                    contents[outer_start + middle_start..].find(&format!("\"{}\"", id))
-                {
-                    let object_start = outer_start + middle_start + id_start;
-                    if let Some(open_brace_index) = contents[object_start..].find('{') {
-                        let object_start = object_start + open_brace_index;
-                        let mut open_braces = 1;
-                        let mut in_quotes = false;
-
-                        for (i, c) in contents[object_start + 1..].chars().enumerate() {
-                            match c {
-                                '{' if !in_quotes => open_braces += 1,
-                                '}' if !in_quotes => {
-                                    open_braces -= 1;
-                                    if open_braces == 0 {
-                                        let end_index = object_start + i + 2;
-                                        let object_str = &contents[object_start..end_index];
-
-                                        let avatar =
-                                            extract_value(object_str, "avatar").map(|avatar| {
-                                                format!("{}/{}_full.jpg", STEAM_AVATAR_URL, avatar)
-                                            });
-
-                                        let mut name = extract_name_history(object_str);
-                                        if name.is_none() || name.as_ref().unwrap().is_empty() {
-                                            name = extract_value(object_str, "name");
-                                        }
-                                        if name.is_none() || name.as_ref().unwrap().is_empty() {
-                                            return Err(Error::Custom(
-                                                "Failed to find profile name".into(),
-                                            ));
-                                        }
-
-                                        let has_overwatch = get_overwatch_installed(&contents)?;
-
-                                        return Ok(SteamProfile {
-                                            id: id.to_string(),
-                                            name: name.unwrap(),
-                                            avatar,
-                                            has_overwatch,
-                                        });
-                                    }
-                                }
-                                '"' => in_quotes = !in_quotes,
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Err(Error::Custom(
-            "Reached the end of file without finding Steam user info".to_string(),
-        ))
-    }
-
-    fn extract_value(object_str: &str, key: &str) -> Option<String> {
-        let search_key = format!("\n\t\t\t\"{}\"", key);
-        object_str.find(&search_key).and_then(|start| {
-            let value_slice = &object_str[start + search_key.len()..];
-            let value_start = value_slice.find('"')? + 1;
-            let value_end = value_slice[value_start..].find('"')?;
-            Some(value_slice[value_start..value_start + value_end].to_string())
-        })
-    }
-
-    fn extract_name_history(object_str: &str) -> Option<String> {
-        let search_key = "\n\t\t\t\"NameHistory\"";
-        object_str.find(search_key).and_then(|start| {
-            let after_key = &object_str[start + search_key.len()..];
-            let nested_start = after_key.find('{')? + 1;
-            let after_brace = &after_key[nested_start..];
-            let zero_key_start = after_brace.find("\"0\"")? + 3;
-            let after_zero_key = &after_brace[zero_key_start..];
-            let value_start = after_zero_key.find('"')? + 1;
-            let value_end = after_zero_key[value_start..].find('"')?;
-            Some(after_zero_key[value_start..value_start + value_end].to_string())
+        let steam_id = to_steam_id64(id).ok_or_else(|| {
+            error(
+                ErrorCode::SteamAccount,
+                format!("Failed to read the Steam account id [[{}]]", id),
+            )
+        })?;
+        let login_user = login_users.and_then(|login_users| login_users.get(&["users", &steam_id]));
+
+        // Prefer the persona name from loginusers.vdf, falling back to the friends list
+        let friend = document.get(&["UserLocalConfigStore", "friends", id]);
+        let name = login_user
+            .and_then(|user| user.get("PersonaName"))
+            .and_then(vdf::Value::as_str)
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                friend
+                    .and_then(|friend| friend.get("NameHistory"))
+                    .and_then(|history| history.get("0"))
+                    .and_then(vdf::Value::as_str)
+            })
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                friend
+                    .and_then(|friend| friend.get("name"))
+                    .and_then(vdf::Value::as_str)
+            })
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| error(ErrorCode::SteamAccount, "Failed to find profile name"))?;
+        let account_name = login_user
+            .and_then(|user| user.get("AccountName"))
+            .and_then(vdf::Value::as_str)
+            .map(ToString::to_string);
+        let most_recent = login_user
+            .and_then(|user| user.get("MostRecent"))
+            .and_then(vdf::Value::as_str)
+            == Some("1");
+
+        let avatar = get_avatar_path(steam_path, &steam_id).map(|_| avatar_url(&steam_id));
+
+        // Overwatch must be installed in a library and have been launched by (or be owned by) this account
+        let is_last_owner = overwatch_install
+            .and_then(|install| install.last_owner.as_deref())
+            .is_some_and(|owner| owner == steam_id);
+        let has_overwatch =
+            overwatch_install.is_some() && (get_overwatch_installed(&document) || is_last_owner);
+
+        Ok(SteamProfile {
+            id: id.to_string(),
+            steam_id: Some(steam_id),
+            name: name.to_string(),
+            account_name,
+            avatar,
+            most_recent,
+            has_overwatch,
+            is_last_owner,
+            included: true,
+            is_new: false,
         })
     }
 
-    fn get_overwatch_installed(contents: &str) -> Result<bool, Error> {
-        // Traverse config file to Overwatch entry
-        let mut queue: VecDeque<&str> = VecDeque::from(vec![
-            "\"UserLocalConfigStore\"",
-            "\n\t\"Software\"",
-            "\n\t\t\"Valve\"",
-            "\n\t\t\t\"Steam\"",
-            "\n\t\t\t\t\"apps\"",
-            "\n\t\t\t\t\t\"2357570\"",
-        ]);
-        let mut current_start = 0;
-        let mut current_end = contents.len();
-
-        while let Some(key) = queue.pop_front() {
-            let formatted_key = key
-                .find('\"')
-                .and_then(|start| {
-                    key[start + 1..]
-                        .find('\"')
-                        .map(|end| &key[start + 1..start + 1 + end])
-                })
-                .unwrap_or(key);
-
-            if let Some(pos) = contents[current_start..current_end]
-                .to_lowercase()
-                .find(&key.to_lowercase())
-            {
-                if formatted_key == "2357570" {
-                    break;
-                }
-
-                // Update start position
-                current_start += pos;
-
-                // Identify start of block
-                let brace_pos =
-                    contents[current_start..current_end]
-                        .find('{')
-                        .ok_or_else(|| {
-                            Error::Custom(format!(
-                                "Failed to find an opening brace for the [[{}]] key",
-                                formatted_key
-                            ))
-                        })?;
-                let block_start = current_start + brace_pos + 1;
-
-                // Identify end of block
-                let current_indent = contents[current_start..block_start]
-                    .rfind("\n")
-                    .and_then(|inner_pos| {
-                        contents[current_start + inner_pos + 1..block_start]
-                            .chars()
-                            .take_while(|&c| c == '\t')
-                            .count()
-                            .into()
-                    })
-                    .unwrap_or(0);
-
-                // Update search end position
-                let search_pattern = format!("\n{}{}", "\t".repeat(current_indent), "}");
-                current_end = contents[block_start..current_end]
-                    .find(&search_pattern)
-                    .map(|i| block_start + i + 1)
-                    .ok_or_else(|| {
-                        Error::Custom(
-                            "Failed to find the closing brace for the [[2357570]] (Overwatch) key"
-                                .to_string(),
-                        )
-                    })?;
-            } else {
-                if formatted_key == "2357570" {
-                    // Overwatch not installed on this account
-                    return Ok(false);
-                }
-
-                return Err(Error::Custom(format!("Failed to find the [[{}]] key", key)));
-            }
-        }
-
-        Ok(true)
+    fn get_overwatch_installed(document: &vdf::Document) -> bool {
+        document.get(&OVERWATCH_PATH).is_some()
     }
 
-    fn is_overwatch_installed(config: &Config, steam_id: &str) -> Result<bool, Error> {
+    /// Whether the account has Overwatch and is included, so OverBuddy manages its launch
+    /// arguments.
+    fn is_managed(config: &Config, steam_id: &str) -> Result<bool, Error> {
         if let Some(steam_profiles) = &config.steam.profiles {
             if let Some(profile) = steam_profiles.iter().find(|profile| profile.id == steam_id) {
-                return Ok(profile.has_overwatch);
+                return Ok(profile.has_overwatch && profile.included);
             } else {
-                return Err(Error::Custom(format!(
-                    "Failed to find a Steam account with id [[{}]]",
-                    steam_id
-                )));
+                return Err(error(
+                    ErrorCode::SteamAccount,
+                    format!("Failed to find a Steam account with id [[{}]]", steam_id),
+                ));
             }
         }
 
-        Err(Error::Custom(
-            "Failed to find any accounts in your Steam [[userdata]] folder".to_string(),
+        Err(error(
+            ErrorCode::SteamAccount,
+            "Failed to find any accounts in your Steam [[userdata]] folder",
         ))
     }
 
@@ -676,250 +1594,139 @@ pub mod steam {
         }
     }
 
-    fn get_config_launch_args(
-        config_filename: &str,
-    ) -> Result<(Option<String>, Option<usize>, Option<usize>), Error> {
-        let mut file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(config_filename)
-            .map_err(|_| {
-                Error::Custom(format!(
-                    "Failed to open the Steam config file at [[{}]]",
+    fn read_localconfig(config_filename: &str) -> Result<vdf::Document, Error> {
+        let local_config = fs::read_to_string(config_filename).map_err(|err| {
+            error(
+                ErrorCode::SteamConfig,
+                format!(
+                    "Failed to read Steam config file at [[{}]]",
                     config_filename
-                ))
-            })?;
-        let mut local_config = String::new();
-        file.read_to_string(&mut local_config).map_err(|_| {
-            Error::Custom(format!(
-                "Failed to read Steam config file at [[{}]]",
-                config_filename
-            ))
+                ),
+            )
+            .with_path(config_filename)
+            .with_io(&err)
         })?;
 
-        // Traverse config file to Overwatch entry
-        let mut queue: VecDeque<&str> = VecDeque::from(vec![
-            "\"UserLocalConfigStore\"",
-            "\n\t\"Software\"",
-            "\n\t\t\"Valve\"",
-            "\n\t\t\t\"Steam\"",
-            "\n\t\t\t\t\"apps\"",
-            "\n\t\t\t\t\t\"2357570\"",
-        ]);
-        let mut current_start = 0;
-        let mut current_end = local_config.len();
-
-        while let Some(key) = queue.pop_front() {
-            let formatted_key = key
-                .find('\"')
-                .and_then(|start| {
-                    key[start + 1..]
-                        .find('\"')
-                        .map(|end| &key[start + 1..start + 1 + end])
-                })
-                .unwrap_or(key);
-
-            if let Some(pos) = &local_config[current_start..current_end]
-                .to_lowercase()
-                .find(&key.to_lowercase())
-            {
-                // Update start position
-                current_start += pos;
-
-                // Identify start of block
-                let brace_pos =
-                    local_config[current_start..current_end]
-                        .find('{')
-                        .ok_or_else(|| {
-                            Error::Custom(format!(
-                                "Failed to find an opening brace for the [[{}]] key in Steam config at [[{}]]",
-                                formatted_key,
-                                config_filename
-                            ))
-                        })?;
-                let block_start = current_start + brace_pos + 1;
-
-                // Identify end of block
-                let current_indent = local_config[current_start..block_start]
-                    .rfind("\n")
-                    .and_then(|inner_pos| {
-                        local_config[current_start + inner_pos + 1..block_start]
-                            .chars()
-                            .take_while(|&c| c == '\t')
-                            .count()
-                            .into()
-                    })
-                    .unwrap_or(0);
-
-                // Update search end position
-                let search_pattern = format!("\n{}{}", "\t".repeat(current_indent), "}");
-                current_end = local_config[block_start..current_end]
-                    .find(&search_pattern)
-                    .map(|i| block_start + i + 1)
-                    .ok_or_else(|| {
-                        Error::Custom(format!(
-                            "Failed to find the closing brace for the [[2357570]] (Overwatch) key in Steam config at [[{}]]", config_filename
-                        ))
-                    })?;
-            } else {
-                if formatted_key == "2357570" {
-                    // Overwatch not installed on this account
-                    return Ok((None, None, None));
-                }
+        vdf::Document::parse(local_config).map_err(|err| {
+            error(
+                ErrorCode::SteamConfig,
+                format!(
+                    "Failed to parse Steam config at [[{}]]: {}",
+                    config_filename, err
+                ),
+            )
+            .with_path(config_filename)
+        })
+    }
 
-                return Err(Error::Custom(format!(
-                    "Failed to find the [[{}]] key in Steam config at [[{}]]",
-                    formatted_key, config_filename
-                )));
-            }
+    /// Get the Overwatch launch options from a Steam localconfig.vdf file.
+    ///
+    /// Returns `None` if Overwatch has no entry in the file.
+    fn get_config_launch_args(config_filename: &str) -> Result<Option<String>, Error> {
+        let document = read_localconfig(config_filename)?;
+        if !get_overwatch_installed(&document) {
+            return Ok(None);
         }
 
-        // Get Overwatch config block
-        let brace_pos = local_config[current_start..].find('{').ok_or_else(|| {
-                Error::Custom(format!(
-                    "Failed to find an opening brace for the [[2357570]] (Overwatch) key in Steam config at [[{}]]",
-                    config_filename
-                ))
-            })?;
-        let block_start = current_start + brace_pos + 1;
-        let block_end = current_end;
-
-        // Set LaunchOptions config to background
-        if let Some(launch_options_pos) =
-            local_config[block_start..block_end].find("\"LaunchOptions\"")
-        {
-            let value_start = block_start + launch_options_pos + "\"LaunchOptions\"".len() + 3; // Skip the key, quotes, and tab
-            let value_end = value_start
-                + local_config[value_start..block_end]
-                    .find('"')
-                    .unwrap_or(value_start);
-
-            if value_start > value_end {
-                return Err(Error::Custom(format!(
-                    "Failed to read the [[LaunchOptions]] key, inside the [[2357570]] (Overwatch) key in Steam config at [[{}]]",
-                    config_filename
-                )));
-            }
-
-            let launch_args = &local_config[value_start..value_end];
-            return Ok((
-                Some(launch_args.to_string()),
-                Some(value_start),
-                Some(value_end),
-            ));
-        } else {
-            return Ok((Some(String::new()), Some(block_start + 1), None));
-        }
+        Ok(Some(
+            document
+                .get_str(&LAUNCH_OPTIONS_PATH)
+                .unwrap_or_default()
+                .to_string(),
+        ))
     }
 
-    fn set_config_launch_args<F, P>(
+    fn plan_config_launch_args(
         config_filename: &str,
-        params: P,
-        generate_launch_args: &F,
-    ) -> Result<(), Error>
-    where
-        F: Fn(Option<&str>, P) -> String,
-    {
-        let backup_path = format!("{}.backup", config_filename);
-
-        // Create scope to release config file lock
-        {
-            let mut file = fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open(config_filename)
-                .map_err(|_| {
-                    Error::Custom(format!(
-                        "Failed to open the Steam config file at [[{}]]",
-                        config_filename
-                    ))
-                })?;
-            let mut local_config = String::new();
-            file.read_to_string(&mut local_config).map_err(|_| {
-                Error::Custom(format!(
-                    "Failed to read Steam config file at [[{}]]",
-                    config_filename
-                ))
+        transform: &dyn Fn(Option<&str>) -> String,
+    ) -> Result<Option<FileChange>, Error> {
+        let mut document = read_localconfig(config_filename)?;
+        if !get_overwatch_installed(&document) {
+            return Ok(None);
+        }
+        let before = document.to_string();
+
+        // Set LaunchOptions config
+        let launch_args = document.get_str(&LAUNCH_OPTIONS_PATH);
+        let mut new_launch_args = transform(launch_args);
+        if launch_args.unwrap_or_default() != new_launch_args {
+            let mut parsed = LaunchArgs::parse(new_launch_args);
+            parsed.insert_command_token();
+            new_launch_args = parsed.to_string();
+        }
+        document
+            .set_str(&LAUNCH_OPTIONS_PATH, &new_launch_args)
+            .map_err(|err| {
+                error(
+                    ErrorCode::SteamConfig,
+                    format!(
+                        "{} while writing launch arguments in Steam config at [[{}]]",
+                        err, config_filename
+                    ),
+                )
+                .with_path(config_filename)
             })?;
+        if document.as_str() == before {
+            return Ok(None);
+        }
 
-            let (launch_args, start_index, end_index) = get_config_launch_args(config_filename)?;
-            if let Some(launch_args) = launch_args {
-                if let (Some(start_index), Some(end_index)) = (start_index, end_index) {
-                    let new_launch_args = generate_launch_args(Some(launch_args.as_str()), params);
-                    local_config.replace_range(start_index..end_index, &new_launch_args);
-                } else if let Some(start_index) = start_index {
-                    let new_launch_args = generate_launch_args(None, params);
-                    local_config.insert_str(
-                        start_index,
-                        format!("\t\t\t\t\t\t\"LaunchOptions\"\t\t\"{}\"\n", new_launch_args)
-                            .as_str(),
-                    );
-                } else {
-                    return Err(Error::Custom(format!(
-                        "Reached unexpected state while reading launch arguments in Steam config at [[{}]]",
-                        config_filename
-                    )));
-                }
-            } else {
-                return Ok(());
-            }
+        Ok(Some(FileChange {
+            path: PathBuf::from(config_filename),
+            before,
+            after: document.to_string(),
+        }))
+    }
 
-            // Backup config file
-            match fs::File::create(&backup_path) {
-                Ok(_) => {}
-                Err(_) => {
-                    return Err(Error::Custom(format!(
-                        "Failed to create backup of [[{}]]",
-                        config_filename
-                    )));
-                }
-            }
-            let mut file = match fs::OpenOptions::new()
-                .write(true)
-                .truncate(true)
-                .open(&backup_path)
-            {
-                Ok(file) => file,
-                Err(_) => {
-                    return Err(Error::Custom(format!(
-                        "Failed to open created backup file at [[{}]]",
-                        backup_path
-                    )));
-                }
-            };
-            match file.write_all(local_config.as_bytes()) {
-                Ok(_) => {}
-                Err(_) => {
-                    return Err(Error::Custom(format!(
-                        "Failed to write to the backup file at [[{}]]",
-                        backup_path
-                    )));
-                }
-            }
+    /// Write a planned change to a Steam localconfig.vdf file.
+    ///
+    /// Steam must be closed, otherwise it overwrites the config files when it exits.
+    pub fn write_change(change: &FileChange) -> Result<(), Error> {
+        let config_filename = change.path.to_string_lossy().to_string();
+        let config_filename = config_filename.as_str();
+        let backup_path = format!("{}.backup", config_filename);
 
-            // Verify backup file
-            let config_changed = verify_file_diff(&config_filename, &backup_path);
-            if config_changed.is_err() {
-                return Err(Error::Custom(format!(
-                    "Failed to verify the backup file at [[{}]], {}",
-                    backup_path,
-                    config_changed.unwrap_err()
-                )));
-            }
+        // Backup config file
+        if let Err(err) = fs::write(&backup_path, &change.after) {
+            return Err(error(
+                ErrorCode::Io,
+                format!("Failed to write to the backup file at [[{}]]", backup_path),
+            )
+            .with_path(&backup_path)
+            .with_io(&err));
+        }
 
-            // Apply backup file
-            if !config_changed.unwrap() {
+        // Verify backup file
+        match verify_file_diff(config_filename, &backup_path) {
+            Ok(true) => {}
+            Ok(false) => {
+                let _ = fs::remove_file(&backup_path);
                 return Ok(());
             }
+            Err(err) => {
+                let _ = fs::remove_file(&backup_path);
+                return Err(error(
+                    ErrorCode::Io,
+                    format!(
+                        "Failed to verify the backup file at [[{}]], {}",
+                        backup_path, err
+                    ),
+                )
+                .with_path(&backup_path));
+            }
         }
 
         // Apply backup file
         if let Err(e) = fs::rename(backup_path, config_filename) {
-            return Err(Error::Custom(format!(
-                "Failed to replace [[{}]] with backup file. {}",
-                config_filename, e
-            )));
+            return Err(error(
+                ErrorCode::Io,
+                format!(
+                    "Failed to replace [[{}]] with backup file. {}",
+                    config_filename, e
+                ),
+            )
+            .with_path(config_filename)
+            .with_io(&e));
         }
-        return Ok(());
+        Ok(())
     }
 }
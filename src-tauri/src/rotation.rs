@@ -0,0 +1,273 @@
+use crate::actions;
+use crate::backgrounds;
+use crate::config::{self, Config, RotationInterval, RotationSource};
+use crate::custom_backgrounds;
+use crate::helpers::{self, Error};
+use crate::history;
+use std::hash::{BuildHasher, RandomState};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use sysinfo::{ProcessesToUpdate, System};
+
+// Automatic background rotation, driven by a watcher thread that also notices when
+// Overwatch exits and applies the seasonal calendar.
+
+/// Event emitted to the frontend after the watcher applied a new background.
+pub const ROTATED_EVENT: &str = "background-rotated";
+/// Event emitted to the frontend with the error when the watcher failed to apply a
+/// background.
+pub const ROTATION_FAILED_EVENT: &str = "background-rotation-failed";
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// How long the watcher waits after a failure before it tries again.
+const RETRY_DELAY: u64 = 60 * 60;
+const OVERWATCH_PROCESS: &str = "Overwatch.exe";
+const DAY: u64 = 24 * 60 * 60;
+
+/// Ids of the backgrounds the rotation picks from, in catalog order.
+pub fn candidates(config: &Config) -> Vec<String> {
+    let rotation = &config.rotation;
+    let has_tag = |tags: &[String]| tags.iter().any(|tag| rotation.tags.contains(tag));
+
+    match rotation.source {
+        RotationSource::All => backgrounds::get_backgrounds()
+            .into_iter()
+            .map(|background| background.id)
+            .collect(),
+        RotationSource::Favorites => config
+            .favorites
+            .iter()
+            .filter(|id| {
+                backgrounds::find_background_by_id(id).is_some()
+                    || custom_backgrounds::find(config, id).is_some()
+            })
+            .cloned()
+            .collect(),
+        RotationSource::Tags => backgrounds::get_backgrounds()
+            .into_iter()
+            .filter(|background| has_tag(&background.tags))
+            .map(|background| background.id)
+            .chain(
+                config
+                    .custom_backgrounds
+                    .iter()
+                    .filter(|background| has_tag(&background.tags))
+                    .map(|background| background.id.clone()),
+            )
+            .collect(),
+    }
+}
+
+/// Pick a random background that is not applied and was not applied recently.
+///
+/// The no-repeat window shrinks if it would exclude every candidate.
+pub fn pick_next(config: &Config) -> Option<String> {
+    let current = config.shared.background.current.as_deref();
+    let candidates: Vec<String> = candidates(config)
+        .into_iter()
+        .filter(|id| Some(id.as_str()) != current)
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let recent = history::recent(config);
+    let mut window = config.rotation.no_repeat.min(recent.len());
+    let eligible = loop {
+        let recent = &recent[..window];
+        let eligible: Vec<&String> = candidates
+            .iter()
+            .filter(|id| !recent.contains(id))
+            .collect();
+        if !eligible.is_empty() {
+            break eligible;
+        }
+        window -= 1;
+    };
+
+    let random = RandomState::new().hash_one(SystemTime::now()) as usize;
+    Some(eligible[random % eligible.len()].clone())
+}
+
+/// Whether a daily or weekly rotation is due. Rotations on launch are triggered by the
+/// watcher instead.
+pub fn is_due(config: &Config, now: u64) -> bool {
+    let interval = match config.rotation.interval {
+        RotationInterval::Daily => DAY,
+        RotationInterval::Weekly => 7 * DAY,
+        RotationInterval::EveryLaunch => return false,
+    };
+
+    config
+        .rotation
+        .last_rotated
+        .is_none_or(|last_rotated| now.saturating_sub(last_rotated) >= interval)
+}
+
+fn is_overwatch_running(system: &mut System) -> bool {
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    system
+        .processes_by_name(OVERWATCH_PROCESS.as_ref())
+        .next()
+        .is_some()
+}
+
+/// Rotate backgrounds and follow the seasonal calendar while the app is running.
+/// `on_rotate` is called with the saved configuration after every change, and `on_error`
+/// with the error when a change failed.
+///
+/// Nothing is applied while Overwatch is running, a pending change waits until it exits.
+/// Changes are applied like any other, closing the launchers and reopening the ones that
+/// were running. The rotation pauses while a seasonal event is active.
+pub fn watch(data_dir: &Path, on_rotate: impl Fn(&Config), on_error: impl Fn(&Error)) {
+    let mut system = System::new();
+    let mut was_running = false;
+    let mut has_exited = false;
+    let mut retry_at = 0;
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let is_running = is_overwatch_running(&mut system);
+        has_exited |= was_running && !is_running;
+        was_running = is_running;
+        if is_running || helpers::now() < retry_at {
+            continue;
+        }
+
+        let Ok(config) = config::read_config_from(data_dir) else {
+            continue;
+        };
+        if !config.is_setup {
+            continue;
+        }
+
+        let config = match actions::apply_seasonal_calendar(data_dir) {
+            Ok(Some(config)) => {
+                on_rotate(&config);
+                config
+            }
+            Ok(None) => config,
+            Err(error) => {
+                on_error(&error.with_context("Failed to apply the seasonal calendar"));
+                retry_at = helpers::now() + RETRY_DELAY;
+                continue;
+            }
+        };
+        if !config.rotation.enabled || config.seasonal.active.is_some() {
+            has_exited = false;
+            continue;
+        }
+        let is_due = match config.rotation.interval {
+            RotationInterval::EveryLaunch => has_exited,
+            RotationInterval::Daily | RotationInterval::Weekly => is_due(&config, helpers::now()),
+        };
+        has_exited = false;
+        if !is_due {
+            continue;
+        }
+
+        // A failed rotation is not recorded, so it is tried again after the delay
+        match actions::rotate_background(data_dir) {
+            Ok(config) => on_rotate(&config),
+            Err(error) => {
+                on_error(&error.with_context("Failed to rotate the background"));
+                retry_at = helpers::now() + RETRY_DELAY;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A configuration rotating through the given favorites with `current` applied.
+    fn favorites(ids: &[&str], current: &str) -> Config {
+        let mut config = config::get_default_config();
+        config.rotation.source = RotationSource::Favorites;
+        for id in ids {
+            custom_backgrounds::remember(&mut config, id);
+            config.favorites.push(id.to_string());
+        }
+        config.shared.background.current = Some(current.to_string());
+        config
+    }
+
+    /// Pick many times, since the pick is random.
+    fn picks(config: &Config) -> Vec<Option<String>> {
+        let mut picks: Vec<Option<String>> = (0..50).map(|_| pick_next(config)).collect();
+        picks.sort();
+        picks.dedup();
+        picks
+    }
+
+    #[test]
+    fn never_picks_the_current_background() {
+        let config = favorites(&["a", "b", "c"], "a");
+        assert_eq!(picks(&config), [Some("b".into()), Some("c".into())]);
+
+        let config = favorites(&["a"], "a");
+        assert_eq!(picks(&config), [None]);
+    }
+
+    #[test]
+    fn skips_recently_applied_backgrounds() {
+        let mut config = favorites(&["a", "b", "c", "d"], "a");
+        history::record(&mut config, "b", true);
+        history::record(&mut config, "a", true);
+        assert_eq!(picks(&config), [Some("c".into()), Some("d".into())]);
+
+        config.rotation.no_repeat = 0;
+        assert_eq!(
+            picks(&config),
+            [Some("b".into()), Some("c".into()), Some("d".into())]
+        );
+    }
+
+    #[test]
+    fn shrinks_the_no_repeat_window_to_keep_a_candidate() {
+        let mut config = favorites(&["a", "b", "c"], "a");
+        history::record(&mut config, "c", true);
+        history::record(&mut config, "b", true);
+        history::record(&mut config, "a", true);
+
+        // Only the most recent backgrounds stay excluded
+        assert_eq!(picks(&config), [Some("c".into())]);
+    }
+
+    #[test]
+    fn is_due_after_the_interval() {
+        let now = 100 * DAY;
+        let mut config = config::get_default_config();
+
+        config.rotation.interval = RotationInterval::Daily;
+        config.rotation.last_rotated = None;
+        assert!(is_due(&config, now));
+        config.rotation.last_rotated = Some(now - DAY + 1);
+        assert!(!is_due(&config, now));
+        config.rotation.last_rotated = Some(now - DAY);
+        assert!(is_due(&config, now));
+
+        config.rotation.interval = RotationInterval::Weekly;
+        config.rotation.last_rotated = None;
+        assert!(is_due(&config, now));
+        config.rotation.last_rotated = Some(now - 6 * DAY);
+        assert!(!is_due(&config, now));
+        config.rotation.last_rotated = Some(now - 7 * DAY);
+        assert!(is_due(&config, now));
+
+        // A clock that went back does not trigger a rotation
+        config.rotation.last_rotated = Some(now + DAY);
+        assert!(!is_due(&config, now));
+    }
+
+    #[test]
+    fn is_never_due_on_launch_rotations() {
+        let mut config = config::get_default_config();
+        config.rotation.interval = RotationInterval::EveryLaunch;
+        assert!(!is_due(&config, 100 * DAY));
+        config.rotation.last_rotated = Some(0);
+        assert!(!is_due(&config, 100 * DAY));
+    }
+}
@@ -0,0 +1,414 @@
+use crate::backgrounds::Background;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Search, filtering and sorting of the background catalog for `query_backgrounds`.
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct BackgroundQuery {
+    /// Words that must all appear in the name or description.
+    pub text: Option<String>,
+    pub tags: Vec<String>,
+    pub heroes: Vec<String>,
+    pub events: Vec<String>,
+    pub seasons: Vec<u32>,
+    pub new_only: bool,
+    /// Defaults to relevance when searching for text, otherwise to release order.
+    pub sort: Option<SortOrder>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Relevance,
+    /// Catalog order, which is the order backgrounds were released in.
+    Release,
+    Name,
+    RecentlyApplied,
+}
+
+#[derive(Serialize)]
+pub struct QueryResult {
+    pub results: Vec<RankedBackground>,
+    pub facets: Facets,
+}
+
+#[derive(Serialize)]
+pub struct RankedBackground {
+    #[serde(flatten)]
+    pub background: Background,
+    pub score: u32,
+}
+
+/// Number of results for every filter value. The counts of a facet take all other
+/// filters into account, but not the facet's own filter, so that selecting a value
+/// does not hide the alternatives.
+#[derive(Serialize)]
+pub struct Facets {
+    pub tags: Vec<FacetCount<String>>,
+    pub heroes: Vec<FacetCount<String>>,
+    pub events: Vec<FacetCount<String>>,
+    pub seasons: Vec<FacetCount<u32>>,
+    pub new: usize,
+}
+
+#[derive(Serialize)]
+pub struct FacetCount<T> {
+    pub value: T,
+    pub count: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Facet {
+    Tags,
+    Heroes,
+    Events,
+    Seasons,
+    New,
+}
+
+impl BackgroundQuery {
+    fn terms(&self) -> Vec<String> {
+        self.text
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    /// Check every filter except the one of the `skip` facet.
+    fn matches(&self, background: &Background, skip: Option<Facet>) -> bool {
+        let any_of = |facet: Facet, selected: &[String], values: &[String]| {
+            skip == Some(facet)
+                || selected.is_empty()
+                || values.iter().any(|value| selected.contains(value))
+        };
+
+        any_of(Facet::Tags, &self.tags, &background.tags)
+            && any_of(Facet::Heroes, &self.heroes, &background.heroes)
+            && any_of(Facet::Events, &self.events, background.event.as_slice())
+            && (skip == Some(Facet::Seasons)
+                || self.seasons.is_empty()
+                || background
+                    .season
+                    .is_some_and(|season| self.seasons.contains(&season)))
+            && (skip == Some(Facet::New) || !self.new_only || background.new)
+    }
+}
+
+/// Score how well a background matches the search terms, `None` if a term is missing.
+fn score(background: &Background, terms: &[String]) -> Option<u32> {
+    let name = background.name.to_lowercase();
+    let description = background.description.to_lowercase();
+
+    let mut score = 0;
+    for term in terms {
+        score += if name == *term {
+            100
+        } else if name.starts_with(term.as_str()) {
+            50
+        } else if name
+            .split_whitespace()
+            .any(|word| word.starts_with(term.as_str()))
+        {
+            30
+        } else if name.contains(term.as_str()) {
+            20
+        } else if description.contains(term.as_str()) {
+            10
+        } else {
+            return None;
+        };
+    }
+
+    Some(score)
+}
+
+fn count<T: Ord + Clone>(counts: &mut BTreeMap<T, usize>, values: impl IntoIterator<Item = T>) {
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+}
+
+/// Most common values first, selected values are always included.
+fn facet_counts<T: Ord + Clone>(
+    mut counts: BTreeMap<T, usize>,
+    selected: &[T],
+) -> Vec<FacetCount<T>> {
+    for value in selected {
+        counts.entry(value.clone()).or_default();
+    }
+
+    let mut facet: Vec<FacetCount<T>> = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .collect();
+    facet.sort_by_key(|facet| std::cmp::Reverse(facet.count));
+    facet
+}
+
+/// Search the given backgrounds. `recent` are the ids of recently applied backgrounds,
+/// most recent first.
+pub fn query(
+    backgrounds: &[Background],
+    query: &BackgroundQuery,
+    recent: &[String],
+) -> QueryResult {
+    let terms = query.terms();
+    let searchable: Vec<(usize, &Background, u32)> = backgrounds
+        .iter()
+        .enumerate()
+        .filter_map(|(index, background)| {
+            score(background, &terms).map(|score| (index, background, score))
+        })
+        .collect();
+
+    let mut tags = BTreeMap::new();
+    let mut heroes = BTreeMap::new();
+    let mut events = BTreeMap::new();
+    let mut seasons = BTreeMap::new();
+    let mut new = 0;
+    for (_, background, _) in &searchable {
+        if query.matches(background, Some(Facet::Tags)) {
+            count(&mut tags, background.tags.iter().cloned());
+        }
+        if query.matches(background, Some(Facet::Heroes)) {
+            count(&mut heroes, background.heroes.iter().cloned());
+        }
+        if query.matches(background, Some(Facet::Events)) {
+            count(&mut events, background.event.iter().cloned());
+        }
+        if query.matches(background, Some(Facet::Seasons)) {
+            count(&mut seasons, background.season);
+        }
+        if background.new && query.matches(background, Some(Facet::New)) {
+            new += 1;
+        }
+    }
+
+    let mut results: Vec<(usize, &Background, u32)> = searchable
+        .into_iter()
+        .filter(|(_, background, _)| query.matches(background, None))
+        .collect();
+
+    let recent_position = |background: &Background| {
+        recent
+            .iter()
+            .position(|id| *id == background.id)
+            .unwrap_or(usize::MAX)
+    };
+    let sort = query.sort.unwrap_or(if terms.is_empty() {
+        SortOrder::Release
+    } else {
+        SortOrder::Relevance
+    });
+    match sort {
+        SortOrder::Relevance => results.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0))),
+        SortOrder::Release => results.sort_by_key(|(index, _, _)| *index),
+        SortOrder::Name => {
+            results.sort_by_cached_key(|(_, background, _)| background.name.to_lowercase())
+        }
+        SortOrder::RecentlyApplied => {
+            results.sort_by_key(|(index, background, _)| (recent_position(background), *index))
+        }
+    }
+
+    QueryResult {
+        results: results
+            .into_iter()
+            .map(|(_, background, score)| RankedBackground {
+                background: background.clone(),
+                score,
+            })
+            .collect(),
+        facets: Facets {
+            tags: facet_counts(tags, &query.tags),
+            heroes: facet_counts(heroes, &query.heroes),
+            events: facet_counts(events, &query.events),
+            seasons: {
+                // Seasons read better in order
+                let mut seasons = facet_counts(seasons, &query.seasons);
+                seasons.sort_by_key(|facet| facet.value);
+                seasons
+            },
+            new,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn background(id: &str, name: &str, description: &str) -> Background {
+        serde_json::from_value(json!({
+            "id": id,
+            "image": format!("{}.jpg", id),
+            "name": name,
+            "description": description,
+        }))
+        .unwrap()
+    }
+
+    fn catalog() -> Vec<Background> {
+        let mut backgrounds = vec![
+            background("a", "Winter Wonderland", "Snow in Hanamura"),
+            background("b", "Kiriko", "Kiriko in Kanezaka"),
+            background("c", "Kanezaka Night", "Kiriko at the shrine"),
+            background("d", "Busan", "Temple at night"),
+            background("e", "Oasis", "Gardens of Oasis"),
+        ];
+        backgrounds[0].tags = vec!["snow".into()];
+        backgrounds[0].event = Some("winter-wonderland".into());
+        backgrounds[0].season = Some(2);
+        backgrounds[1].heroes = vec!["Kiriko".into()];
+        backgrounds[1].season = Some(1);
+        backgrounds[1].new = true;
+        backgrounds[2].heroes = vec!["Kiriko".into()];
+        backgrounds[2].tags = vec!["night".into()];
+        backgrounds[2].season = Some(1);
+        backgrounds[3].tags = vec!["night".into()];
+        backgrounds[3].season = Some(2);
+        backgrounds
+    }
+
+    fn ids(result: &QueryResult) -> Vec<&str> {
+        result
+            .results
+            .iter()
+            .map(|ranked| ranked.background.id.as_str())
+            .collect()
+    }
+
+    fn search(text: &str) -> QueryResult {
+        let query = BackgroundQuery {
+            text: Some(text.to_string()),
+            ..Default::default()
+        };
+        super::query(&catalog(), &query, &[])
+    }
+
+    #[test]
+    fn scores_matches_by_where_the_term_appears() {
+        let backgrounds = vec![
+            background("description", "Busan", "Night market in kiri"),
+            background("substring", "Akirin", ""),
+            background("word", "Night Kiri Lights", ""),
+            background("prefix", "Kiriko", ""),
+            background("exact", "Kiri", ""),
+        ];
+        let query = BackgroundQuery {
+            text: Some("KIRI".to_string()),
+            ..Default::default()
+        };
+
+        let result = super::query(&backgrounds, &query, &[]);
+        assert_eq!(
+            ids(&result),
+            ["exact", "prefix", "word", "substring", "description"]
+        );
+        let scores: Vec<u32> = result.results.iter().map(|ranked| ranked.score).collect();
+        assert_eq!(scores, [100, 50, 30, 20, 10]);
+    }
+
+    #[test]
+    fn adds_up_scores_of_all_terms() {
+        let result = search("kiriko kanezaka");
+        assert_eq!(ids(&result), ["b", "c"]);
+        // Exact name and description, name prefix and description
+        let scores: Vec<u32> = result.results.iter().map(|ranked| ranked.score).collect();
+        assert_eq!(scores, [110, 60]);
+    }
+
+    #[test]
+    fn drops_results_missing_a_term() {
+        assert_eq!(ids(&search("night")), ["c", "d"]);
+        assert_eq!(ids(&search("night shrine")), ["c"]);
+        assert!(ids(&search("night hanamura")).is_empty());
+    }
+
+    #[test]
+    fn sorts_by_release_without_text() {
+        let result = query(&catalog(), &BackgroundQuery::default(), &[]);
+        assert_eq!(ids(&result), ["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn sorts_by_name() {
+        let query = BackgroundQuery {
+            sort: Some(SortOrder::Name),
+            ..Default::default()
+        };
+        let result = super::query(&catalog(), &query, &[]);
+        assert_eq!(ids(&result), ["d", "c", "b", "e", "a"]);
+    }
+
+    #[test]
+    fn sorts_recently_applied_first() {
+        let query = BackgroundQuery {
+            sort: Some(SortOrder::RecentlyApplied),
+            ..Default::default()
+        };
+        let recent = ["d".to_string(), "missing".to_string(), "b".to_string()];
+        let result = super::query(&catalog(), &query, &recent);
+
+        // The rest keep their release order
+        assert_eq!(ids(&result), ["d", "b", "a", "c", "e"]);
+    }
+
+    #[test]
+    fn counts_facets_without_their_own_filter() {
+        let query = BackgroundQuery {
+            tags: vec!["night".into()],
+            seasons: vec![1],
+            ..Default::default()
+        };
+        let result = super::query(&catalog(), &query, &[]);
+        assert_eq!(ids(&result), ["c"]);
+
+        let counts = |facet: &[FacetCount<String>]| -> Vec<(String, usize)> {
+            facet
+                .iter()
+                .map(|facet| (facet.value.clone(), facet.count))
+                .collect()
+        };
+        // Tags of season 1, regardless of the selected tag
+        assert_eq!(counts(&result.facets.tags), [("night".to_string(), 1)]);
+        // Seasons of night backgrounds, regardless of the selected season
+        let seasons: Vec<(u32, usize)> = result
+            .facets
+            .seasons
+            .iter()
+            .map(|facet| (facet.value, facet.count))
+            .collect();
+        assert_eq!(seasons, [(1, 1), (2, 1)]);
+        // Other facets take both filters into account
+        assert_eq!(counts(&result.facets.heroes), [("Kiriko".to_string(), 1)]);
+        assert!(result.facets.events.is_empty());
+        assert_eq!(result.facets.new, 0);
+    }
+
+    #[test]
+    fn keeps_selected_values_without_results_in_facets() {
+        let query = BackgroundQuery {
+            events: vec!["anniversary".into()],
+            new_only: true,
+            ..Default::default()
+        };
+        let result = super::query(&catalog(), &query, &[]);
+        assert!(ids(&result).is_empty());
+
+        let events: Vec<(&str, usize)> = result
+            .facets
+            .events
+            .iter()
+            .map(|facet| (facet.value.as_str(), facet.count))
+            .collect();
+        // Only new backgrounds are counted, and none of them are from an event
+        assert_eq!(events, [("anniversary", 0)]);
+        assert_eq!(result.facets.new, 0);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::backgrounds;
+use crate::config::{Config, SeasonalEvent};
+use crate::custom_backgrounds;
+use crate::helpers::{self, Error, ErrorCode};
+
+// Calendar of yearly events whose backgrounds are applied while they run.
+
+/// Events of the calendar new users start with.
+pub fn default_calendar() -> Vec<SeasonalEvent> {
+    let event = |name: &str, start: &str, end: &str, background: &str| SeasonalEvent {
+        name: name.to_string(),
+        start: start.to_string(),
+        end: end.to_string(),
+        background: background.to_string(),
+    };
+
+    vec![
+        event("Ultimate Valentine", "02-07", "02-21", "0x0800000000000B6B"),
+        event("Summer Games", "07-15", "08-05", "0x0800000000000BCE"),
+        event(
+            "Overwatch 2 Anniversary",
+            "10-01",
+            "10-13",
+            "0x08000000000013AD",
+        ),
+        event("Halloween Terror", "10-14", "11-04", "0x08000000000013C5"),
+        event("Winter Wonderland", "12-10", "01-06", "0x08000000000013F0"),
+    ]
+}
+
+/// Parse a `MM-DD` date into a month and day.
+fn parse_month_day(date: &str) -> Option<(u32, u32)> {
+    let (month, day) = date.split_once('-')?;
+    if month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let month: u32 = month.parse().ok()?;
+    let day: u32 = day.parse().ok()?;
+
+    ((1..=12).contains(&month) && (1..=31).contains(&day)).then_some((month, day))
+}
+
+/// Get the current month and day in UTC.
+pub fn today() -> (u32, u32) {
+    // Convert days since the Unix epoch to a civil date, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (helpers::now() / (24 * 60 * 60)) as i64 + 719_468;
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+
+    (month as u32, day as u32)
+}
+
+fn is_running(event: &SeasonalEvent, today: (u32, u32)) -> bool {
+    let (Some(start), Some(end)) = (parse_month_day(&event.start), parse_month_day(&event.end))
+    else {
+        return false;
+    };
+
+    if start <= end {
+        start <= today && today <= end
+    } else {
+        // The event spans the new year
+        today >= start || today <= end
+    }
+}
+
+/// Find the event that runs on the given day, the first one if several overlap.
+pub fn current_event(config: &Config, today: (u32, u32)) -> Option<&SeasonalEvent> {
+    if !config.seasonal.enabled {
+        return None;
+    }
+
+    config
+        .seasonal
+        .events
+        .iter()
+        .find(|event| is_running(event, today))
+}
+
+/// Check the events of a calendar and normalize their background ids.
+pub fn validate(config: &Config, events: &mut [SeasonalEvent]) -> Result<(), Error> {
+    for event in events {
+        if event.name.trim().is_empty() {
+            return Err(Error::new(
+                ErrorCode::InvalidArgument,
+                "Every seasonal event needs a name",
+            ));
+        }
+        for date in [&event.start, &event.end] {
+            if parse_month_day(date).is_none() {
+                return Err(Error::new(
+                    ErrorCode::InvalidArgument,
+                    format!(
+                        "[[{}]] of the seasonal event [[{}]] is not a valid date",
+                        date, event.name
+                    ),
+                )
+                .with_hint("Dates are written as MM-DD, e.g. 12-24."));
+            }
+        }
+
+        event.background = backgrounds::normalize_id(&event.background)?;
+        if backgrounds::find_background_by_id(&event.background).is_none()
+            && custom_backgrounds::find(config, &event.background).is_none()
+        {
+            return Err(Error::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "Failed to find the background [[{}]] of the seasonal event [[{}]]",
+                    event.background, event.name
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+
+    fn event(name: &str, start: &str, end: &str) -> SeasonalEvent {
+        SeasonalEvent {
+            name: name.to_string(),
+            start: start.to_string(),
+            end: end.to_string(),
+            background: "0x0800000000000E77".to_string(),
+        }
+    }
+
+    #[test]
+    fn runs_within_its_dates() {
+        let event = event("Summer Games", "07-15", "08-05");
+        for today in [(7, 15), (7, 31), (8, 5)] {
+            assert!(is_running(&event, today), "{:?}", today);
+        }
+        for today in [(7, 14), (8, 6), (1, 1), (12, 31)] {
+            assert!(!is_running(&event, today), "{:?}", today);
+        }
+    }
+
+    #[test]
+    fn runs_across_the_new_year() {
+        let event = event("Winter Wonderland", "12-10", "01-06");
+        for today in [(12, 10), (12, 31), (1, 1), (1, 6)] {
+            assert!(is_running(&event, today), "{:?}", today);
+        }
+        for today in [(12, 9), (1, 7), (7, 1)] {
+            assert!(!is_running(&event, today), "{:?}", today);
+        }
+    }
+
+    #[test]
+    fn never_runs_with_invalid_dates() {
+        for (start, end) in [("13-01", "12-31"), ("01-01", "1-31"), ("", "12-31")] {
+            assert!(!is_running(&event("Broken", start, end), (6, 1)));
+        }
+    }
+
+    #[test]
+    fn picks_the_first_of_overlapping_events() {
+        let mut config = config::get_default_config();
+        config.seasonal.enabled = true;
+        config.seasonal.events = vec![
+            event("Halloween Terror", "10-14", "11-04"),
+            event("Anniversary", "10-01", "10-20"),
+            event("Winter Wonderland", "12-10", "01-06"),
+            event("New Year", "12-31", "01-02"),
+        ];
+
+        let name = |today| current_event(&config, today).map(|event| event.name.as_str());
+        assert_eq!(name((10, 5)), Some("Anniversary"));
+        assert_eq!(name((10, 15)), Some("Halloween Terror"));
+        assert_eq!(name((10, 21)), Some("Halloween Terror"));
+        assert_eq!(name((1, 1)), Some("Winter Wonderland"));
+        assert_eq!(name((6, 1)), None);
+
+        config.seasonal.enabled = false;
+        assert!(current_event(&config, (10, 15)).is_none());
+    }
+
+    #[test]
+    fn default_calendar_is_valid() {
+        let config = config::get_default_config();
+        let mut events = default_calendar();
+        for event in &events {
+            assert!(parse_month_day(&event.start).is_some(), "{}", event.name);
+            assert!(parse_month_day(&event.end).is_some(), "{}", event.name);
+        }
+        assert!(validate(&config, &mut events).is_ok());
+    }
+}
@@ -0,0 +1,214 @@
+use crate::helpers::{Error, ErrorCode};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Snapshots of launcher config files, taken before they are modified so that a failed
+/// apply can restore every file to its original contents.
+#[derive(Default)]
+pub struct Transaction {
+    snapshots: Vec<Snapshot>,
+}
+
+struct Snapshot {
+    path: PathBuf,
+    /// `None` if the file did not exist, so it is removed again on rollback.
+    contents: Option<Vec<u8>>,
+}
+
+pub struct Rollback {
+    /// Files that were modified and have been restored.
+    pub restored: Vec<PathBuf>,
+    /// Files that were modified but could not be restored.
+    pub failed: Vec<(PathBuf, std::io::Error)>,
+}
+
+impl Transaction {
+    /// Take a snapshot of a file. Files that were already snapshotted are skipped.
+    pub fn snapshot(&mut self, path: &Path) -> Result<(), Error> {
+        if self.snapshots.iter().any(|snapshot| snapshot.path == path) {
+            return Ok(());
+        }
+
+        let contents = match fs::read(path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+        .map_err(|err| {
+            Error::new(
+                ErrorCode::Io,
+                format!(
+                    "Failed to create a snapshot of [[{}]]: {}",
+                    path.display(),
+                    err
+                ),
+            )
+            .with_path(path)
+            .with_io(&err)
+        })?;
+        self.snapshots.push(Snapshot {
+            path: path.to_path_buf(),
+            contents,
+        });
+
+        Ok(())
+    }
+
+    /// Keep every change, discarding the snapshots.
+    pub fn commit(self) {}
+
+    /// Restore every file that changed since its snapshot was taken, and remove the files
+    /// that were created.
+    pub fn rollback(self) -> Rollback {
+        let mut rollback = Rollback {
+            restored: vec![],
+            failed: vec![],
+        };
+
+        for snapshot in self.snapshots {
+            let current = fs::read(&snapshot.path).ok();
+            if current == snapshot.contents {
+                continue;
+            }
+
+            let result = match &snapshot.contents {
+                Some(contents) => fs::write(&snapshot.path, contents),
+                None => fs::remove_file(&snapshot.path),
+            };
+            match result {
+                Ok(_) => rollback.restored.push(snapshot.path),
+                Err(err) => rollback.failed.push((snapshot.path, err)),
+            }
+        }
+
+        rollback
+    }
+}
+
+impl Rollback {
+    /// Describe the rollback, to be appended to the error that caused it.
+    pub fn describe(&self) -> String {
+        let mut lines = vec![];
+
+        if !self.restored.is_empty() {
+            lines.push(format!(
+                "Rolled back changes to [[{}]]",
+                self.restored
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join("]], [[")
+            ));
+        }
+        for (path, err) in &self.failed {
+            lines.push(format!(
+                "Failed to roll back changes to [[{}]]: {}",
+                path.display(),
+                err
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty directory for a test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "overbuddy-transaction-{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    /// Write files in order like the launchers do, stopping at the first error.
+    fn write_all(files: &[(&Path, &[u8])]) -> std::io::Result<()> {
+        files
+            .iter()
+            .try_for_each(|(path, contents)| fs::write(path, contents))
+    }
+
+    #[test]
+    fn restores_written_files_when_a_later_write_fails() {
+        let dir = test_dir("rollback");
+        let first = dir.join("Battle.net.config");
+        // Line endings and invalid UTF-8 must survive the rollback
+        let original: &[u8] = b"{\r\n\t\"Games\": {}\r\n}\xff\r\n";
+        fs::write(&first, original).unwrap();
+        let second = dir.join("missing").join("localconfig.vdf");
+
+        let mut transaction = Transaction::default();
+        transaction.snapshot(&first).unwrap();
+        transaction.snapshot(&second).unwrap();
+        assert!(write_all(&[(&first, b"changed"), (&second, b"changed")]).is_err());
+
+        let rollback = transaction.rollback();
+        assert_eq!(fs::read(&first).unwrap(), original);
+        assert_eq!(rollback.restored, std::slice::from_ref(&first));
+        assert!(rollback.failed.is_empty());
+        assert!(rollback.describe().contains(&first.display().to_string()));
+    }
+
+    #[test]
+    fn removes_created_files_on_rollback() {
+        let dir = test_dir("created");
+        let created = dir.join("localconfig.vdf");
+
+        let mut transaction = Transaction::default();
+        transaction.snapshot(&created).unwrap();
+        fs::write(&created, "new").unwrap();
+
+        let rollback = transaction.rollback();
+        assert!(!created.exists());
+        assert_eq!(rollback.restored, [created]);
+    }
+
+    #[test]
+    fn skips_unchanged_files_on_rollback() {
+        let dir = test_dir("unchanged");
+        let file = dir.join("Battle.net.config");
+        fs::write(&file, "same").unwrap();
+
+        let mut transaction = Transaction::default();
+        transaction.snapshot(&file).unwrap();
+        transaction.snapshot(&file).unwrap();
+
+        let rollback = transaction.rollback();
+        assert!(rollback.restored.is_empty());
+        assert!(rollback.describe().is_empty());
+    }
+
+    #[test]
+    fn keeps_new_contents_on_commit() {
+        let dir = test_dir("commit");
+        let first = dir.join("Battle.net.config");
+        let second = dir.join("localconfig.vdf");
+        fs::write(&first, "old").unwrap();
+
+        let mut transaction = Transaction::default();
+        transaction.snapshot(&first).unwrap();
+        transaction.snapshot(&second).unwrap();
+        write_all(&[(&first, b"new"), (&second, b"created")]).unwrap();
+        transaction.commit();
+
+        assert_eq!(fs::read_to_string(&first).unwrap(), "new");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "created");
+    }
+
+    #[test]
+    fn fails_to_snapshot_unreadable_files() {
+        let dir = test_dir("unreadable");
+
+        let mut transaction = Transaction::default();
+        let error = transaction.snapshot(&dir).err().unwrap();
+        assert_eq!(error.code, ErrorCode::Io);
+    }
+}
@@ -0,0 +1,556 @@
+use std::ops::Range;
+
+// Text KeyValues (VDF) documents, as used by Steam for localconfig.vdf and friends.
+//
+// Documents keep the original source text and only splice the regions that are
+// modified, so everything that is not touched is written back byte-for-byte.
+
+#[derive(Debug, thiserror::Error)]
+#[error("{message} at line {line}, column {column}")]
+pub struct ParseError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub key: String,
+    pub value: Value,
+    /// Byte offset of the start of the key token (including its opening quote).
+    start: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    String {
+        value: String,
+        /// Byte range of the raw value, excluding quotes.
+        span: Range<usize>,
+        quoted: bool,
+    },
+    Object {
+        entries: Vec<Entry>,
+        /// Byte offset of the opening brace.
+        open: usize,
+        /// Byte offset of the closing brace.
+        close: usize,
+    },
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String { value, .. } => Some(value),
+            Value::Object { .. } => None,
+        }
+    }
+
+    pub fn entries(&self) -> Option<&[Entry]> {
+        match self {
+            Value::String { .. } => None,
+            Value::Object { entries, .. } => Some(entries),
+        }
+    }
+
+    /// Get a child value by key. Keys are matched case-insensitively, like Steam does.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries()?
+            .iter()
+            .find(|entry| entry.key.eq_ignore_ascii_case(key))
+            .map(|entry| &entry.value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Document {
+    source: String,
+    root: Value,
+}
+
+impl Document {
+    /// Parse a text KeyValues document.
+    pub fn parse(source: impl Into<String>) -> Result<Document, ParseError> {
+        let source = source.into();
+        let entries = Parser::new(&source).parse_document()?;
+        let root = Value::Object {
+            entries,
+            open: 0,
+            close: source.len(),
+        };
+
+        Ok(Document { source, root })
+    }
+
+    pub fn root(&self) -> &Value {
+        &self.root
+    }
+
+    /// Get a value by its key path.
+    pub fn get(&self, path: &[&str]) -> Option<&Value> {
+        path.iter()
+            .try_fold(&self.root, |value, key| value.get(key))
+    }
+
+    /// Get a string value by its key path.
+    pub fn get_str(&self, path: &[&str]) -> Option<&str> {
+        self.get(path).and_then(Value::as_str)
+    }
+
+    /// Set a string value by its key path.
+    ///
+    /// The parent object must already exist. If the key exists its value is
+    /// replaced in place, otherwise a new line is appended to the parent object
+    /// using the indentation of its siblings.
+    pub fn set_str(&mut self, path: &[&str], value: &str) -> Result<(), String> {
+        let (key, parent_path) = path
+            .split_last()
+            .ok_or_else(|| "Cannot set a value without a key".to_string())?;
+        let parent = self.get(parent_path).ok_or_else(|| {
+            format!(
+                "Failed to find the [[{}]] key",
+                parent_path.last().unwrap_or(&"root")
+            )
+        })?;
+        let Value::Object {
+            entries,
+            open,
+            close,
+        } = parent
+        else {
+            return Err(format!(
+                "Expected the [[{}]] key to be an object",
+                parent_path.last().unwrap_or(&"root")
+            ));
+        };
+
+        let escaped = escape(value);
+        let existing = entries
+            .iter()
+            .find(|entry| entry.key.eq_ignore_ascii_case(key));
+
+        let (range, replacement) = match existing.map(|entry| &entry.value) {
+            Some(Value::String { span, quoted, .. }) => {
+                let replacement = if *quoted {
+                    escaped
+                } else {
+                    format!("\"{}\"", escaped)
+                };
+                (span.clone(), replacement)
+            }
+            Some(Value::Object { .. }) => {
+                return Err(format!("Expected the [[{}]] key to be a string", key));
+            }
+            None => {
+                let (insert_at, line) = self.new_entry_line(entries, *open, *close, key, &escaped);
+                (insert_at..insert_at, line)
+            }
+        };
+        self.source.replace_range(range, &replacement);
+
+        // Re-parse so spans stay in sync with the modified source
+        let entries = Parser::new(&self.source)
+            .parse_document()
+            .map_err(|err| err.to_string())?;
+        self.root = Value::Object {
+            entries,
+            open: 0,
+            close: self.source.len(),
+        };
+
+        Ok(())
+    }
+
+    /// Build the line for a new entry and find where to insert it.
+    fn new_entry_line(
+        &self,
+        entries: &[Entry],
+        open: usize,
+        close: usize,
+        key: &str,
+        escaped_value: &str,
+    ) -> (usize, String) {
+        let line_start = |offset: usize| {
+            self.source[..offset]
+                .rfind('\n')
+                .map(|index| index + 1)
+                .unwrap_or(0)
+        };
+        let indent_of = |offset: usize| -> String {
+            self.source[line_start(offset)..offset]
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .collect()
+        };
+
+        let is_root = open == 0 && close == self.source.len();
+        let indent = match entries.first() {
+            Some(entry) => indent_of(entry.start),
+            None if is_root => String::new(),
+            None => format!("{}\t", indent_of(open)),
+        };
+        let newline = if self.source.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let entry = format!("{}\"{}\"\t\t\"{}\"", indent, escape(key), escaped_value);
+
+        // Insert on its own line right before the closing brace
+        let close_line_start = line_start(close);
+        if close_line_start > open && self.source[close_line_start..close].trim().is_empty() {
+            (close_line_start, format!("{}{}", entry, newline))
+        } else {
+            let parent_indent = indent_of(open);
+            (
+                close,
+                format!("{}{}{}{}", newline, entry, newline, parent_indent),
+            )
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl std::fmt::Display for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Escape a string for use inside a quoted KeyValues token.
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(raw: &str) -> String {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => value.push('\\'),
+            Some('"') => value.push('"'),
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some(other) => {
+                value.push('\\');
+                value.push(other);
+            }
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    OpenBrace,
+    CloseBrace,
+    String { span: Range<usize>, quoted: bool },
+    Eof,
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser {
+            source,
+            position: 0,
+        }
+    }
+
+    fn error(&self, offset: usize, message: impl Into<String>) -> ParseError {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+        ParseError {
+            message: message.into(),
+            line,
+            column,
+        }
+    }
+
+    fn skip_trivia(&mut self) {
+        let bytes = self.source.as_bytes();
+        loop {
+            while self.position < bytes.len() && bytes[self.position].is_ascii_whitespace() {
+                self.position += 1;
+            }
+            if self.source[self.position..].starts_with("//") {
+                self.position = self.source[self.position..]
+                    .find('\n')
+                    .map(|i| self.position + i)
+                    .unwrap_or(bytes.len());
+                continue;
+            }
+            // Platform conditionals such as [$WIN32] are accepted and ignored
+            if bytes.get(self.position) == Some(&b'[') {
+                self.position = self.source[self.position..]
+                    .find(']')
+                    .map(|i| self.position + i + 1)
+                    .unwrap_or(bytes.len());
+                continue;
+            }
+            break;
+        }
+    }
+
+    /// Returns the next token and the byte offset where it starts.
+    fn next_token(&mut self) -> Result<(Token, usize), ParseError> {
+        self.skip_trivia();
+        let start = self.position;
+        let bytes = self.source.as_bytes();
+
+        match bytes.get(start) {
+            None => Ok((Token::Eof, start)),
+            Some(b'{') => {
+                self.position += 1;
+                Ok((Token::OpenBrace, start))
+            }
+            Some(b'}') => {
+                self.position += 1;
+                Ok((Token::CloseBrace, start))
+            }
+            Some(b'"') => {
+                let mut index = start + 1;
+                while index < bytes.len() {
+                    match bytes[index] {
+                        b'\\' => index += 2,
+                        b'"' => {
+                            self.position = index + 1;
+                            return Ok((
+                                Token::String {
+                                    span: start + 1..index,
+                                    quoted: true,
+                                },
+                                start,
+                            ));
+                        }
+                        _ => index += 1,
+                    }
+                }
+                Err(self.error(start, "Unterminated string"))
+            }
+            Some(_) => {
+                let mut index = start;
+                while index < bytes.len()
+                    && !bytes[index].is_ascii_whitespace()
+                    && !matches!(bytes[index], b'{' | b'}' | b'"')
+                {
+                    index += 1;
+                }
+                self.position = index;
+                Ok((
+                    Token::String {
+                        span: start..index,
+                        quoted: false,
+                    },
+                    start,
+                ))
+            }
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Vec<Entry>, ParseError> {
+        let (entries, close) = self.parse_entries(true)?;
+        debug_assert_eq!(close, self.source.len());
+        Ok(entries)
+    }
+
+    /// Parse entries until the closing brace (or end of file for the root).
+    /// Returns the entries and the offset of the terminating token.
+    fn parse_entries(&mut self, is_root: bool) -> Result<(Vec<Entry>, usize), ParseError> {
+        let mut entries = vec![];
+
+        loop {
+            let (token, start) = self.next_token()?;
+            let key_span = match token {
+                Token::String { span, .. } => span,
+                Token::CloseBrace if !is_root => return Ok((entries, start)),
+                Token::Eof if is_root => return Ok((entries, start)),
+                Token::Eof => return Err(self.error(start, "Unexpected end of file")),
+                Token::CloseBrace => return Err(self.error(start, "Unexpected closing brace")),
+                Token::OpenBrace => return Err(self.error(start, "Expected a key")),
+            };
+            let key = unescape(&self.source[key_span]);
+
+            let (token, value_start) = self.next_token()?;
+            let value = match token {
+                Token::String { span, quoted } => Value::String {
+                    value: unescape(&self.source[span.clone()]),
+                    span,
+                    quoted,
+                },
+                Token::OpenBrace => {
+                    let (children, close) = self.parse_entries(false)?;
+                    Value::Object {
+                        entries: children,
+                        open: value_start,
+                        close,
+                    }
+                }
+                _ => {
+                    return Err(self.error(
+                        value_start,
+                        format!("Expected a value for the [[{}]] key", key),
+                    ));
+                }
+            };
+
+            entries.push(Entry { key, value, start });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCALCONFIG: &str = "\"UserLocalConfigStore\"\n{\n\t\"Software\"\n\t{\n\t\t\"Valve\"\n\t\t{\n\t\t\t\"Steam\"\n\t\t\t{\n\t\t\t\t\"apps\"\n\t\t\t\t{\n\t\t\t\t\t\"2357570\"\n\t\t\t\t\t{\n\t\t\t\t\t\t\"LastPlayed\"\t\t\"1718000000\"\n\t\t\t\t\t\t\"LaunchOptions\"\t\t\"-windowed --lobbyMap=0x0800000000000E77\"\n\t\t\t\t\t}\n\t\t\t\t\t\"570\"\n\t\t\t\t\t{\n\t\t\t\t\t\t\"LastPlayed\"\t\t\"1700000000\"\n\t\t\t\t\t}\n\t\t\t\t}\n\t\t\t}\n\t\t}\n\t}\n\t\"friends\"\n\t{\n\t\t\"PersonaName\"\t\t\"Alice\"\n\t}\n}\n";
+    const APP: &[&str] = &[
+        "UserLocalConfigStore",
+        "Software",
+        "Valve",
+        "Steam",
+        "apps",
+        "2357570",
+    ];
+
+    fn launch_options_path() -> Vec<&'static str> {
+        [APP, &["LaunchOptions"]].concat()
+    }
+
+    #[test]
+    fn round_trips_unmodified() {
+        let document = Document::parse(LOCALCONFIG).unwrap();
+        assert_eq!(document.to_string(), LOCALCONFIG);
+        assert_eq!(
+            document.get_str(&launch_options_path()),
+            Some("-windowed --lobbyMap=0x0800000000000E77")
+        );
+    }
+
+    #[test]
+    fn replaces_value_in_place() {
+        let mut document = Document::parse(LOCALCONFIG).unwrap();
+        document
+            .set_str(&launch_options_path(), "--tank_Console")
+            .unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            LOCALCONFIG.replace("-windowed --lobbyMap=0x0800000000000E77", "--tank_Console")
+        );
+    }
+
+    #[test]
+    fn keeps_comments_and_conditionals() {
+        let source = "// Written by Steam\n\"root\"\n{\n\t\"a\"\t\t\"1\" [$WIN32]\n\t// comment\n\t\"b\"\t\t\"2\"\t[$OSX]\n}\n";
+        let mut document = Document::parse(source).unwrap();
+        assert_eq!(document.to_string(), source);
+        assert_eq!(document.get_str(&["root", "a"]), Some("1"));
+        assert_eq!(document.get_str(&["root", "b"]), Some("2"));
+
+        document.set_str(&["root", "b"], "3").unwrap();
+        assert_eq!(document.to_string(), source.replace("\"2\"", "\"3\""));
+    }
+
+    #[test]
+    fn unescapes_and_escapes_quotes() {
+        let source = "\"root\"\n{\n\t\"a\"\t\t\"say \\\"hi\\\" \\\\o/\"\n}\n";
+        let mut document = Document::parse(source).unwrap();
+        assert_eq!(document.get_str(&["root", "a"]), Some("say \"hi\" \\o/"));
+        assert_eq!(document.to_string(), source);
+
+        document
+            .set_str(&["root", "a"], "-config \"C:\\x.cfg\"")
+            .unwrap();
+        assert_eq!(
+            document.get_str(&["root", "a"]),
+            Some("-config \"C:\\x.cfg\"")
+        );
+        assert!(
+            document
+                .to_string()
+                .contains("\"-config \\\"C:\\\\x.cfg\\\"\"")
+        );
+    }
+
+    #[test]
+    fn inserts_missing_key() {
+        let source = LOCALCONFIG.replace(
+            "\t\t\t\t\t\t\"LaunchOptions\"\t\t\"-windowed --lobbyMap=0x0800000000000E77\"\n",
+            "",
+        );
+        let mut document = Document::parse(source.as_str()).unwrap();
+        document
+            .set_str(&launch_options_path(), "--tank_Console")
+            .unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            LOCALCONFIG.replace("-windowed --lobbyMap=0x0800000000000E77", "--tank_Console")
+        );
+        assert_eq!(
+            document.get_str(&launch_options_path()),
+            Some("--tank_Console")
+        );
+    }
+
+    #[test]
+    fn inserts_into_empty_object() {
+        let source = "\"root\"\n{\n\t\"apps\"\n\t{\n\t}\n}\n";
+        let mut document = Document::parse(source).unwrap();
+        document.set_str(&["root", "apps", "a"], "1").unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            "\"root\"\n{\n\t\"apps\"\n\t{\n\t\t\"a\"\t\t\"1\"\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn fails_to_set_missing_parent() {
+        let mut document = Document::parse(LOCALCONFIG).unwrap();
+        assert!(
+            document
+                .set_str(&["UserLocalConfigStore", "x", "y"], "1")
+                .is_err()
+        );
+        assert_eq!(document.to_string(), LOCALCONFIG);
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        let error = Document::parse("\"root\"\n{\n\t\"a\"\t\t\"1\"\n").unwrap_err();
+        assert_eq!(error.message, "Unexpected end of file");
+        assert_eq!(error.line, 4);
+
+        let error = Document::parse("\"root\"\n{\n\t\"a\"\t\t\"1\n}\n").unwrap_err();
+        assert_eq!(error.message, "Unterminated string");
+        assert_eq!((error.line, error.column), (3, 7));
+
+        assert!(Document::parse("\"root\"\n{\n}\n}\n").is_err());
+        assert!(Document::parse("\"root\"\n{\n\t\"a\"\n}\n").is_err());
+    }
+}
@@ -1,21 +1,39 @@
 import clsx from 'clsx'
 import { LoaderPinwheel } from 'lucide-react'
 import { AnimatePresence, motion } from 'motion/react'
-import { useRef } from 'react'
+import { useEffect, useRef } from 'react'
 
 import SteamProfileComponent from '@/components/SteamProfile'
+import {
+  useIncludeNewSteamAccountsMutation,
+  useMarkSteamAccountsSeenMutation,
+  useSteamAccountMutation
+} from '@/lib/data'
 import type { SteamProfile } from '@/lib/schemas'
 import { useIsOverflow } from '@/lib/useIsOverflow'
 
 export default function SteamProfileList({
   steam_profiles,
+  include_new_accounts,
   isFetching
 }: {
   steam_profiles: SteamProfile[]
+  include_new_accounts: boolean
   isFetching: boolean
 }) {
   const ref = useRef<HTMLDivElement>(null)
   const isOverflow = useIsOverflow(ref)
+  const { mutate: setIncluded, status } = useSteamAccountMutation()
+  const { mutate: setIncludeNewAccounts, status: newAccountsStatus } =
+    useIncludeNewSteamAccountsMutation()
+  const { mutate: markSeen } = useMarkSteamAccountsSeenMutation()
+
+  // New accounts stay marked while the list is shown, and are seen once it is closed
+  const hasNew = steam_profiles.some((profile) => profile.is_new)
+  useEffect(() => {
+    if (!hasNew) return
+    return () => markSeen()
+  }, [hasNew, markSeen])
 
   return (
     <motion.div
@@ -38,8 +56,41 @@ export default function SteamProfileList({
       {steam_profiles.length > 0 ? (
         <>
           {steam_profiles.map((profile) => (
-            <SteamProfileComponent key={profile.id} account={profile} />
+            <div
+              key={profile.id}
+              className="flex shrink-0 flex-col items-start gap-1.5"
+            >
+              <div className={clsx(!profile.included && 'opacity-50')}>
+                <SteamProfileComponent account={profile} />
+              </div>
+              <button
+                className="text-sm text-zinc-400 underline-offset-2 transition hover:text-zinc-200 hover:underline focus-visible:text-zinc-200 focus-visible:underline focus-visible:outline-none disabled:opacity-50"
+                onClick={() =>
+                  setIncluded({ id: profile.id, included: !profile.included })
+                }
+                disabled={status === 'pending'}
+                title={
+                  profile.included
+                    ? 'OverBuddy changes the launch options of this account'
+                    : 'OverBuddy leaves this account alone'
+                }
+              >
+                {profile.included ? 'Included' : 'Excluded'}
+                {profile.is_new && (
+                  <span className="ml-1.5 rounded bg-orange-500 px-1 text-xs font-medium uppercase text-orange-50">
+                    New
+                  </span>
+                )}
+              </button>
+            </div>
           ))}
+          <button
+            className="shrink-0 self-end text-sm text-zinc-400 underline-offset-2 transition hover:text-zinc-200 hover:underline focus-visible:text-zinc-200 focus-visible:underline focus-visible:outline-none disabled:opacity-50"
+            onClick={() => setIncludeNewAccounts(!include_new_accounts)}
+            disabled={newAccountsStatus === 'pending'}
+          >
+            New accounts are {include_new_accounts ? 'included' : 'excluded'}
+          </button>
           {/* {steam_profiles.map((profile) => (
             <SteamProfileComponent key={profile.id} account={profile} />
           ))} */}
@@ -1,5 +1,5 @@
 import { queryOptions, useMutation } from '@tanstack/react-query'
-import { invoke } from '@tauri-apps/api/core'
+import { invoke as invokeCommand } from '@tauri-apps/api/core'
 import { check } from '@tauri-apps/plugin-updater'
 import { toast } from 'sonner'
 import * as z from 'zod'
@@ -11,19 +11,36 @@ import {
   SetupError,
   SetupPathResponse,
   SteamSetupError,
-  handleError
+  handleError,
+  parseCommandError
 } from '@/lib/errors'
 import {
   Background,
   BackgroundArray,
+  BackgroundHistory,
+  BackgroundQueryResult,
+  BattleNetCandidate,
   LaunchConfig,
+  LaunchOptions,
+  type CustomBackground,
+  type RotationConfig,
+  type SeasonalEvent,
+  StatusReport,
   SteamProfile,
-  type Platform
+  type BackgroundQuery,
+  type Platform,
+  type Target
 } from '@/lib/schemas'
 import { queryClient } from '@/main'
 import { useState } from 'react'
 import { isDev } from './dev'
 
+/* Invoke a command, converting the errors it returns into Error instances */
+const invoke = (...args: Parameters<typeof invokeCommand>) =>
+  invokeCommand(...args).catch((error) => {
+    throw parseCommandError(error)
+  })
+
 const launchQueryKey = ['launch']
 
 const updateLaunchConfig = async (config: LaunchConfig) => {
@@ -33,10 +50,7 @@ const updateLaunchConfig = async (config: LaunchConfig) => {
 export const launchQueryOptions = queryOptions({
   queryKey: launchQueryKey,
   queryFn: async () => {
-    const data = await invoke('get_launch_config').catch((error) => {
-      if (typeof error !== 'string') throw error
-      throw new Error(error)
-    })
+    const data = await invoke('get_launch_config')
     const config = LaunchConfig.safeParse(JSON.parse(data as string))
     if (!config.success) {
       throw new Error(config.error.message)
@@ -61,27 +75,7 @@ const setupMutation = async ({
   platforms: Platform[]
   isInitialized?: boolean
 }): Promise<SetupResponse> => {
-  const data = await invoke('setup', { platforms, isInitialized }).catch(
-    (error) => {
-      if (typeof error !== 'string') throw error
-
-      let parsed
-      try {
-        parsed = JSON.parse(error as string)
-      } catch (_) {
-        throw new Error(error)
-      }
-
-      const configError = ConfigErrorSchema.safeParse(parsed)
-      if (configError.success) {
-        throw new ConfigError(configError.data)
-      }
-
-      throw new Error(
-        `Failed to setup. Received: [[${JSON.stringify(parsed)}]], Error: [[${configError.error.message}]]`
-      )
-    }
-  )
+  const data = await invoke('setup', { platforms, isInitialized })
 
   const config = LaunchConfig.safeParse(JSON.parse(data as string))
   if (!config.success) {
@@ -129,7 +123,7 @@ export const getSetupPath = (key: ConfigErrors) =>
       } catch (error) {
         handleError(error)
 
-        if (typeof error === 'string') throw new Error(error)
+        throw parseCommandError(error)
       }
       throw new Error('Failed to get setup paths.')
     }
@@ -148,7 +142,7 @@ export const useSetupErrorMutation = ({
       path,
       platforms
     }: {
-      key: ConfigErrorSchema['error_key']
+      key: ConfigErrorSchema['code']
       path: string | undefined
       platforms: Platform[]
     }) => {
@@ -162,22 +156,54 @@ export const useSetupErrorMutation = ({
         key,
         path,
         platforms
-      }).catch((error) => {
-        if (typeof error !== 'string') throw error
-
-        let parsed
-        try {
-          parsed = JSON.parse(error as string)
-        } catch (_) {
-          throw new Error(error)
-        }
+      })
 
-        const configError = ConfigErrorSchema.safeParse(parsed)
-        if (configError.success) {
-          throw new ConfigError(configError.data)
-        }
+      const config = LaunchConfig.safeParse(JSON.parse(data as string))
+      if (!config.success) {
+        throw new Error(`Failed to setup. ${config.error.message}`)
+      }
+
+      updateLaunchConfig(config.data)
+      return { platforms, config: config.data }
+    },
+    onError,
+    onSuccess
+  })
 
-        throw new Error(configError.error.message)
+export const battleNetCandidatesQueryOptions = queryOptions({
+  queryKey: ['battle_net_candidates'],
+  queryFn: async () => {
+    const data = await invoke('get_battle_net_candidates')
+    const candidates = z
+      .array(BattleNetCandidate)
+      .safeParse(JSON.parse(data as string))
+    if (!candidates.success) {
+      throw new Error(
+        `Failed to get Battle.net installations. ${candidates.error.message}`
+      )
+    }
+    return candidates.data
+  }
+})
+
+export const useBattleNetCandidateMutation = ({
+  onError,
+  onSuccess
+}: {
+  onError?: (error: Error | ConfigError) => void
+  onSuccess?: (data: SetupResponse) => void
+} = {}) =>
+  useMutation({
+    mutationFn: async ({
+      prefix,
+      platforms
+    }: {
+      prefix: string | null
+      platforms: Platform[]
+    }) => {
+      const data = await invoke('select_battle_net_candidate', {
+        prefix,
+        platforms
       })
 
       const config = LaunchConfig.safeParse(JSON.parse(data as string))
@@ -294,6 +320,62 @@ export const backgroundsQueryOptions = queryOptions({
   staleTime: isDev() ? 0 : Infinity
 })
 
+export const backgroundSearchQueryOptions = (query: BackgroundQuery) =>
+  queryOptions({
+    queryKey: ['backgrounds', 'search', query],
+    queryFn: async () => {
+      const data = await invoke('query_backgrounds', { query })
+      const result = BackgroundQueryResult.safeParse(JSON.parse(data as string))
+      if (!result.success) {
+        throw new Error(`Failed to search backgrounds. ${result.error.message}`)
+      }
+
+      return result.data
+    }
+  })
+
+export const historyQueryOptions = queryOptions({
+  queryKey: ['history'],
+  queryFn: async () => {
+    const data = await invoke('get_history')
+    const history = BackgroundHistory.safeParse(JSON.parse(data as string))
+    if (!history.success) {
+      throw new Error(`Failed to get history. ${history.error.message}`)
+    }
+
+    return history.data
+  },
+  staleTime: 0
+})
+
+export const statusQueryOptions = queryOptions({
+  queryKey: ['status'],
+  queryFn: async () => {
+    const data = await invoke('get_status')
+    const status = StatusReport.safeParse(JSON.parse(data as string))
+    if (!status.success) {
+      throw new Error(`Failed to get launcher status. ${status.error.message}`)
+    }
+
+    return status.data
+  },
+  staleTime: 0
+})
+
+export const launchOptionsQueryOptions = queryOptions({
+  queryKey: ['launch_options'],
+  queryFn: async () => {
+    const data = await invoke('get_launch_options')
+    const options = LaunchOptions.safeParse(JSON.parse(data as string))
+    if (!options.success) {
+      throw new Error(`Failed to get launch options. ${options.error.message}`)
+    }
+
+    return options.data
+  },
+  staleTime: 0
+})
+
 /**
  * Query for the active background
  *
@@ -356,7 +438,11 @@ export const useBackgroundMutation = ({
   const [toastIndex, setToastIndex] = useState(0)
 
   return useMutation({
-    mutationFn: async (background: { id: string; isCustom?: boolean }) => {
+    mutationFn: async (background: {
+      id: string
+      isCustom?: boolean
+      targets?: Target[]
+    }) => {
       const data = (await invoke('set_background', background)) as string
       const config = LaunchConfig.safeParse(JSON.parse(data))
       if (!config.success) {
@@ -398,6 +484,214 @@ export const useBackgroundMutation = ({
   })
 }
 
+export const useSaveCustomBackgroundMutation = ({
+  onSuccess
+}: {
+  onSuccess?: () => void
+} = {}) =>
+  useMutation({
+    mutationFn: async ({
+      background,
+      image
+    }: {
+      background: CustomBackground
+      image?: string
+    }) => {
+      const data = (await invoke('save_custom_background', {
+        background,
+        image
+      })) as string
+      const config = LaunchConfig.safeParse(JSON.parse(data))
+      if (!config.success) {
+        throw new Error(
+          `Failed to save custom background. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error),
+    onSuccess
+  })
+
+export const useDeleteCustomBackgroundMutation = () =>
+  useMutation({
+    mutationFn: async (id: string) => {
+      const data = (await invoke('delete_custom_background', { id })) as string
+      const config = LaunchConfig.safeParse(JSON.parse(data))
+      if (!config.success) {
+        throw new Error(
+          `Failed to delete custom background. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error)
+  })
+
+export const useFavoriteMutation = () =>
+  useMutation({
+    mutationFn: async (data: { id: string; favorite: boolean }) => {
+      const query = (await invoke('set_favorite', data)) as string
+      const config = LaunchConfig.safeParse(JSON.parse(query))
+      if (!config.success) {
+        throw new Error(`Failed to save favorites. ${config.error.message}`)
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error)
+  })
+
+export const useSteamAccountMutation = () =>
+  useMutation({
+    mutationFn: async (data: { id: string; included: boolean }) => {
+      const query = (await invoke('set_steam_account_included', data)) as string
+      const config = LaunchConfig.safeParse(JSON.parse(query))
+      if (!config.success) {
+        throw new Error(
+          `Failed to save Steam account settings. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error)
+  })
+
+export const useMarkSteamAccountsSeenMutation = () =>
+  useMutation({
+    mutationFn: async () => {
+      const query = (await invoke('mark_steam_accounts_seen')) as string
+      const config = LaunchConfig.safeParse(JSON.parse(query))
+      if (!config.success) {
+        throw new Error(
+          `Failed to save Steam account settings. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error)
+  })
+
+export const useIncludeNewSteamAccountsMutation = () =>
+  useMutation({
+    mutationFn: async (include: boolean) => {
+      const query = (await invoke('set_include_new_steam_accounts', {
+        include
+      })) as string
+      const config = LaunchConfig.safeParse(JSON.parse(query))
+      if (!config.success) {
+        throw new Error(
+          `Failed to save Steam account settings. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error)
+  })
+
+export const useLaunchOptionMutation = () =>
+  useMutation({
+    mutationFn: async (data: {
+      flag: string
+      value?: string
+      targets?: Target[]
+      remove?: boolean
+    }) => {
+      const query = (await invoke(
+        data.remove ? 'remove_launch_option' : 'set_launch_option',
+        { flag: data.flag, value: data.value, targets: data.targets }
+      )) as string
+      const config = LaunchConfig.safeParse(JSON.parse(query))
+      if (!config.success) {
+        throw new Error(`Failed to save launch option. ${config.error.message}`)
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error),
+    onSettled: () => {
+      queryClient.invalidateQueries(launchOptionsQueryOptions)
+      queryClient.invalidateQueries(statusQueryOptions)
+    }
+  })
+
+export const useRotationMutation = () =>
+  useMutation({
+    mutationFn: async (rotation: RotationConfig) => {
+      const data = (await invoke('set_rotation', { rotation })) as string
+      const config = LaunchConfig.safeParse(JSON.parse(data))
+      if (!config.success) {
+        throw new Error(
+          `Failed to save rotation settings. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error)
+  })
+
+export const useRotateBackgroundMutation = () =>
+  useMutation({
+    mutationFn: async () => {
+      const data = (await invoke('rotate_background')) as string
+      const config = LaunchConfig.safeParse(JSON.parse(data))
+      if (!config.success) {
+        throw new Error(`Failed to rotate background. ${config.error.message}`)
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error),
+    onSuccess: () => {
+      backgroundToastIds.forEach((id) => toast.dismiss(id))
+      toast.success('Successfully applied the next background.', {
+        id: backgroundToastIds[0]
+      })
+    }
+  })
+
+export const useSeasonalCalendarMutation = () =>
+  useMutation({
+    mutationFn: async (data: {
+      enabled: boolean
+      events?: SeasonalEvent[]
+    }) => {
+      const query = (await invoke('set_seasonal_calendar', data)) as string
+      const config = LaunchConfig.safeParse(JSON.parse(query))
+      if (!config.success) {
+        throw new Error(
+          `Failed to save seasonal calendar. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+    },
+    onError: (error) => handleError(error)
+  })
+
+export const useRevertBackgroundMutation = () =>
+  useMutation({
+    mutationFn: async () => {
+      const data = (await invoke('revert_background')) as string
+      const config = LaunchConfig.safeParse(JSON.parse(data))
+      if (!config.success) {
+        throw new Error(`Failed to revert background. ${config.error.message}`)
+      }
+      updateLaunchConfig(config.data)
+      queryClient.invalidateQueries(historyQueryOptions)
+    },
+    onError: (error) => handleError(error),
+    onSuccess: () => {
+      backgroundToastIds.forEach((id) => toast.dismiss(id))
+      toast.success('Successfully reverted to the previous background.', {
+        id: backgroundToastIds[0]
+      })
+    }
+  })
+
+/** Emitted by the backend when the rotation or the seasonal calendar applied a new background. */
+export const ROTATED_EVENT = 'background-rotated'
+/** Emitted by the backend with the error when the rotation or the seasonal calendar failed. */
+export const ROTATION_FAILED_EVENT = 'background-rotation-failed'
+export const invalidateLaunchConfig = () =>
+  queryClient.invalidateQueries(launchQueryOptions)
+
 export const useResetBackgroundMutation = ({
   onSuccess,
   onSettled
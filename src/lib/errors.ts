@@ -13,26 +13,77 @@ export const ConfigErrors = z.enum([
 ])
 export type ConfigErrors = z.infer<typeof ConfigErrors>
 
-/* ConfigError Response Schema */
-export const ConfigErrorSchema = z.object({
-  error_key: ConfigErrors.or(z.enum(['NoOverwatch'])),
+/* Error Response Schema */
+export const ErrorSegment = z.object({
+  text: z.string(),
+  highlight: z.boolean()
+})
+export type ErrorSegment = z.infer<typeof ErrorSegment>
+
+export const ErrorResponseSchema = z.object({
+  code: z.string(),
   message: z.string(),
-  platforms: z.array(Platform)
+  segments: z.array(ErrorSegment),
+  platform: Platform.nullable(),
+  path: z.string().nullable(),
+  io_kind: z.string().nullable(),
+  hints: z.array(z.string()),
+  platforms: z.array(Platform).nullable()
+})
+export type ErrorResponseSchema = z.infer<typeof ErrorResponseSchema>
+
+/* Render error segments, marking highlighted values with [[ ]] */
+function formatSegments(segments: ErrorSegment[]) {
+  return segments
+    .map((segment) =>
+      segment.highlight ? `[[${segment.text}]]` : segment.text
+    )
+    .join('')
+}
+
+/* BackendError Class */
+export class BackendError extends Error {
+  code: string
+  hints: string[]
+
+  constructor(public error: ErrorResponseSchema) {
+    super(formatSegments(error.segments))
+    this.code = error.code
+    this.hints = error.hints
+  }
+}
+
+/* ConfigError Response Schema */
+export const ConfigErrorSchema = ErrorResponseSchema.extend({
+  code: ConfigErrors.or(z.enum(['NoOverwatch', 'BattleNetPrefix']))
 })
 export type ConfigErrorSchema = z.infer<typeof ConfigErrorSchema>
 
 /* ConfigError Class */
-export class ConfigError extends Error {
-  error_key: ConfigErrorSchema['error_key']
-  platforms: ConfigErrorSchema['platforms']
-
-  constructor(public error: ConfigErrorSchema) {
-    super(error.message)
-    this.error_key = error.error_key
-    this.platforms = error.platforms
+export class ConfigError extends BackendError {
+  declare code: ConfigErrorSchema['code']
+  platforms: Platform[]
+
+  constructor(error: ConfigErrorSchema) {
+    super(error)
+    this.platforms = error.platforms ?? []
   }
 }
 
+/* Convert an error returned by a command into an Error */
+export function parseCommandError(error: unknown): Error {
+  if (error instanceof Error) return error
+  if (typeof error === 'string') return new Error(error)
+
+  const configError = ConfigErrorSchema.safeParse(error)
+  if (configError.success) return new ConfigError(configError.data)
+
+  const backendError = ErrorResponseSchema.safeParse(error)
+  if (backendError.success) return new BackendError(backendError.data)
+
+  return new Error(`Received an unexpected error: [[${JSON.stringify(error)}]]`)
+}
+
 /* SetupError Class */
 export class SetupError extends Error {
   constructor() {
@@ -56,12 +107,18 @@ export class SteamSetupError extends Error {
 /* Handle non-critical errors */
 // , reportable = true
 export function handleError(error: unknown) {
+  if (!(error instanceof Error) && typeof error === 'object' && error !== null)
+    error = parseCommandError(error)
+  const hints = error instanceof BackendError ? error.hints : []
   if (error instanceof Error) error = error.message
   else if (typeof error !== 'string') error = 'An unknown error occurred.'
   toast.error(
     (error as string).replaceAll(/\[\[|\]\]/g, '"') +
       (/[.!?]$/.test(error as string) ? '' : '.'),
     {
+      description: hints.length
+        ? hints.join(' ').replaceAll(/\[\[|\]\]/g, '"')
+        : undefined,
       classNames: {
         toast: '!max-w-[28rem] !select-auto'
       }
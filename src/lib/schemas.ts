@@ -10,44 +10,211 @@ const SteamLocalconfig = z.object({
 
 export const SteamProfile = z.object({
   id: z.string(),
+  steam_id: z.string().nullable(),
   name: z.string(),
+  account_name: z.string().nullable(),
   avatar: z.url().nullable(),
-  has_overwatch: z.boolean()
+  most_recent: z.boolean(),
+  has_overwatch: z.boolean(),
+  is_last_owner: z.boolean(),
+  included: z.boolean(),
+  is_new: z.boolean()
 })
 export type SteamProfile = z.infer<typeof SteamProfile>
 
+export const BattleNetCandidate = z.object({
+  source: z.enum(['Native', 'Wine', 'Lutris', 'Bottles', 'Heroic']),
+  prefix: z.string().nullable(),
+  install: z.string().nullable(),
+  config: z.string().nullable()
+})
+export type BattleNetCandidate = z.infer<typeof BattleNetCandidate>
+
+const SteamAppInstall = z.object({
+  library: z.string(),
+  install_dir: z.string(),
+  build_id: z.string().nullable(),
+  branch: z.string(),
+  last_owner: z.string().nullable()
+})
+
+export const RetiredBackground = z.object({
+  id: z.string(),
+  name: z.string(),
+  status: z.enum(['removed', 'broken']),
+  season: z.number().nullable(),
+  replacement: z.string().nullable()
+})
+export type RetiredBackground = z.infer<typeof RetiredBackground>
+
+export const CustomBackground = z.object({
+  id: z.string(),
+  name: z.string(),
+  notes: z.string().nullable(),
+  image: z.url().nullable(),
+  tags: z.array(z.string())
+})
+export type CustomBackground = z.infer<typeof CustomBackground>
+
+export const RotationConfig = z.object({
+  enabled: z.boolean(),
+  interval: z.enum(['daily', 'weekly', 'every_launch']),
+  source: z.enum(['all', 'favorites', 'tags']),
+  tags: z.array(z.string()),
+  no_repeat: z.number(),
+  last_rotated: z.number().nullable()
+})
+export type RotationConfig = z.infer<typeof RotationConfig>
+
+const BackgroundConfig = z.object({
+  current: z.string().nullable(),
+  is_outdated: z.boolean(),
+  custom: z.string().nullable(),
+  retired: RetiredBackground.nullable()
+})
+
+const AdditionalConfig = z.object({
+  console_enabled: z.boolean()
+})
+
+export const Target = z.discriminatedUnion('platform', [
+  z.object({ platform: z.literal('BattleNet') }),
+  z.object({ platform: z.literal('Steam'), account: z.string() })
+])
+export type Target = z.infer<typeof Target>
+
+export const TargetConfig = z.object({
+  target: Target,
+  name: z.string(),
+  background: BackgroundConfig,
+  additional: AdditionalConfig
+})
+export type TargetConfig = z.infer<typeof TargetConfig>
+
+const TargetError = z.object({
+  code: z.string(),
+  segments: z.array(z.object({ text: z.string(), highlight: z.boolean() }))
+})
+
+export const TargetStatus = TargetConfig.extend({
+  path: z.string(),
+  launch_args: z.string().nullable(),
+  modified: z.number().nullable(),
+  error: TargetError.nullable()
+})
+export type TargetStatus = z.infer<typeof TargetStatus>
+
+export const StatusReport = z.object({
+  targets: z.array(TargetStatus),
+  summary: z.object({
+    background: BackgroundConfig,
+    additional: AdditionalConfig
+  })
+})
+export type StatusReport = z.infer<typeof StatusReport>
+
+export const KnownFlag = z.object({
+  flag: z.string(),
+  description: z.string(),
+  value: z.enum(['none', 'integer', 'background']),
+  managed: z.boolean()
+})
+export type KnownFlag = z.infer<typeof KnownFlag>
+
+export const LaunchOptions = z.object({
+  targets: z.array(
+    z.object({
+      target: Target,
+      name: z.string(),
+      launch_args: z.string().nullable(),
+      args: z.array(z.string()),
+      warnings: z.array(z.object({ flag: z.string(), message: z.string() })),
+      error: TargetError.nullable()
+    })
+  ),
+  catalog: z.array(KnownFlag)
+})
+export type LaunchOptions = z.infer<typeof LaunchOptions>
+
+export const SeasonalEvent = z.object({
+  name: z.string(),
+  start: z.string(),
+  end: z.string(),
+  background: z.string()
+})
+export type SeasonalEvent = z.infer<typeof SeasonalEvent>
+
 export const LaunchConfig = z.object({
   is_setup: z.boolean(),
   battle_net: z.object({
     enabled: z.boolean(),
     config: z.string().nullable(),
-    install: z.string().nullable()
+    install: z.string().nullable(),
+    prefix: z.string().nullable()
   }),
   steam: z.object({
     enabled: z.boolean(),
     in_setup: z.boolean(),
     install: z.string().nullable(),
     configs: z.array(SteamLocalconfig).nullable(),
-    profiles: z.array(SteamProfile).nullable()
+    profiles: z.array(SteamProfile).nullable(),
+    overwatch: SteamAppInstall.nullable(),
+    include_new_accounts: z.boolean()
   }),
   shared: z.object({
-    background: z.object({
-      current: z.string().nullable(),
-      is_outdated: z.boolean(),
-      custom: z.string().nullable()
-    }),
-    additional: z.object({
-      console_enabled: z.boolean()
-    })
+    background: BackgroundConfig,
+    additional: AdditionalConfig
+  }),
+  targets: z.array(TargetConfig),
+  custom_backgrounds: z.array(CustomBackground),
+  favorites: z.array(z.string()),
+  rotation: RotationConfig,
+  seasonal: z.object({
+    enabled: z.boolean(),
+    events: z.array(SeasonalEvent),
+    active: z
+      .object({
+        name: z.string(),
+        background: z.string(),
+        previous: z.array(
+          z.object({
+            target: Target,
+            background: z.string().nullable(),
+            is_custom: z.boolean()
+          })
+        )
+      })
+      .nullable()
   })
 })
 export type LaunchConfig = z.infer<typeof LaunchConfig>
 
+export const FilePreview = z.object({
+  platform: Platform,
+  path: z.string(),
+  diff: z.string()
+})
+export type FilePreview = z.infer<typeof FilePreview>
+
+export const Preview = z.object({
+  config: LaunchConfig,
+  files: z.array(FilePreview)
+})
+export type Preview = z.infer<typeof Preview>
+
 export const Background = z.object({
   id: z.string(),
   image: z.string(),
   name: z.string(),
   description: z.string(),
+  heroes: z.array(z.string()),
+  event: z.string().nullable(),
+  season: z.number().nullable(),
+  release_date: z.string().nullable(),
+  animated: z.boolean(),
+  music: z.string().nullable(),
+  silent: z.boolean(),
+  collaboration: z.string().nullable(),
   tags: z.array(z.string()),
   new: z.boolean()
 })
@@ -56,6 +223,54 @@ export type Background = z.infer<typeof Background>
 export const BackgroundArray = z.array(Background)
 export type BackgroundArray = z.infer<typeof BackgroundArray>
 
+export type BackgroundQuery = {
+  text?: string
+  tags?: string[]
+  heroes?: string[]
+  events?: string[]
+  seasons?: number[]
+  new_only?: boolean
+  sort?: 'relevance' | 'release' | 'name' | 'recently_applied'
+}
+
+const FacetCount = <T extends z.ZodType>(value: T) =>
+  z.object({ value, count: z.number() })
+
+export const BackgroundQueryResult = z.object({
+  results: z.array(Background.extend({ score: z.number() })),
+  facets: z.object({
+    tags: z.array(FacetCount(z.string())),
+    heroes: z.array(FacetCount(z.string())),
+    events: z.array(FacetCount(z.string())),
+    seasons: z.array(FacetCount(z.number())),
+    new: z.number()
+  })
+})
+export type BackgroundQueryResult = z.infer<typeof BackgroundQueryResult>
+
+const BackgroundStats = z.object({
+  id: z.string(),
+  name: z.string(),
+  apply_count: z.number(),
+  last_applied: z.number().nullable(),
+  available: z.boolean()
+})
+
+export const BackgroundHistory = z.object({
+  favorites: z.array(BackgroundStats),
+  backgrounds: z.array(BackgroundStats),
+  entries: z.array(
+    z.object({
+      id: z.string(),
+      name: z.string(),
+      applied_at: z.number(),
+      is_custom: z.boolean(),
+      available: z.boolean()
+    })
+  )
+})
+export type BackgroundHistory = z.infer<typeof BackgroundHistory>
+
 export const RedirectSearchParam = z.object({
   redirect: z.string().optional()
 })
@@ -1,5 +1,14 @@
 import { QueryClient } from '@tanstack/react-query'
 import { Outlet, createRootRouteWithContext } from '@tanstack/react-router'
+import { listen } from '@tauri-apps/api/event'
+import { useEffect } from 'react'
+
+import {
+  ROTATED_EVENT,
+  ROTATION_FAILED_EVENT,
+  invalidateLaunchConfig
+} from '@/lib/data'
+import { handleError } from '@/lib/errors'
 
 export const Route = createRootRouteWithContext<{
   queryClient: QueryClient
@@ -8,6 +17,16 @@ export const Route = createRootRouteWithContext<{
 })
 
 function RootComponent() {
+  useEffect(() => {
+    const unlisten = [
+      listen(ROTATED_EVENT, () => invalidateLaunchConfig()),
+      listen(ROTATION_FAILED_EVENT, (event) => handleError(event.payload))
+    ]
+    return () => {
+      unlisten.forEach((unlisten) => unlisten.then((unlisten) => unlisten()))
+    }
+  }, [])
+
   return (
     <div className="h-screen min-h-screen">
       <Outlet />
@@ -4,11 +4,19 @@ import clsx from 'clsx'
 import {
   ChevronLeft,
   ChevronRight,
+  HeartIcon,
   LoaderPinwheel,
   SettingsIcon
 } from 'lucide-react'
 import { AnimatePresence, motion, useAnimation } from 'motion/react'
-import { useCallback, useEffect, useLayoutEffect, useMemo, useRef } from 'react'
+import {
+  useCallback,
+  useEffect,
+  useLayoutEffect,
+  useMemo,
+  useRef,
+  useState
+} from 'react'
 import { toast } from 'sonner'
 
 import placeholder from '@/assets/placeholder.svg'
@@ -21,12 +29,19 @@ import {
   updateQueryOptions,
   useActiveBackgroundMutation,
   useBackgroundMutation,
+  useFavoriteMutation,
   useResetBackgroundMutation
 } from '@/lib/data'
 import { linkFix } from '@/lib/linkFix'
+import type { Background, Target } from '@/lib/schemas'
 import useKeyPress from '@/lib/useKeyPress'
 import { cn } from '@/lib/utils'
 
+const targetKey = (target: Target) =>
+  target.platform === 'Steam'
+    ? `${target.platform}-${target.account}`
+    : target.platform
+
 const buttonTapAnimation = {
   scale: 0.9
 }
@@ -78,6 +93,16 @@ const handleKeyDown = (
 const prevKeys = ['ArrowLeft', 'a']
 const nextKeys = ['ArrowRight', 'd']
 
+function getBackgroundLabels(background: Background) {
+  return [
+    ...(background.collaboration ? ['Collaboration'] : []),
+    ...background.tags,
+    ...(background.animated ? ['Animated'] : []),
+    ...(background.silent ? ['Silent'] : []),
+    ...(background.music ? [`Song: ${background.music}`] : [])
+  ]
+}
+
 function Menu() {
   const navigate = useNavigate()
   const { data: backgrounds } = useSuspenseQuery(backgroundsQueryOptions)
@@ -101,6 +126,15 @@ function Menu() {
   })
   const backgroundRefs = useRef<HTMLButtonElement[]>([])
   const { mutate: setActiveBackground } = useActiveBackgroundMutation()
+  const { mutate: setFavorite } = useFavoriteMutation()
+  const isFavorite = config.favorites.includes(activeBackground.id)
+  const [selectedTarget, setSelectedTarget] = useState<string>('all')
+  const target = config.targets.find(
+    (state) => targetKey(state.target) === selectedTarget
+  )
+  const currentBackground = target
+    ? target.background.current
+    : config.shared.background.current
 
   const backgroundIndex = useMemo(
     () => backgrounds.findIndex((bg) => bg.id === activeBackground.id) || 0,
@@ -209,9 +243,48 @@ function Menu() {
       toast.dismiss('update-available')
     }
   }, [updateAvailable, navigate])
+  // Retired background toast
+  const retired = config.shared.background.retired
+  const replacement = useMemo(
+    () => backgrounds.find((bg) => bg.id === retired?.replacement),
+    [backgrounds, retired?.replacement]
+  )
+  useEffect(() => {
+    if (retired) {
+      const season = retired.season ? ` in Season ${retired.season}` : ''
+      toast.warning(
+        retired.status === 'broken'
+          ? `Your background ${retired.name} no longer works since a patch${season}.`
+          : `Your background ${retired.name} was removed from Overwatch${season}.`,
+        {
+          id: 'retired-background',
+          description: replacement
+            ? `Try ${replacement.name} instead, or revert to the default background.`
+            : 'For the best experience, please revert to the default background.',
+          action: replacement
+            ? {
+                label: `Apply ${replacement.name}`,
+                onClick: () => setBackground({ id: replacement.id })
+              }
+            : {
+                label: 'Revert to Default',
+                onClick: () => resetBackground()
+              },
+          classNames: {
+            toast: 'max-w-xl'
+          },
+          duration: Infinity
+        }
+      )
+    }
+
+    return () => {
+      toast.dismiss('retired-background')
+    }
+  }, [retired, replacement, setBackground, resetBackground])
   // Outdated background toast
   useEffect(() => {
-    if (config.shared.background.is_outdated) {
+    if (config.shared.background.is_outdated && !retired) {
       toast.warning(
         'Your background is outdated or unknown to OverBuddy. For the best experience, please revert to the default background.',
         {
@@ -231,7 +304,7 @@ function Menu() {
     return () => {
       toast.dismiss('outdated-background')
     }
-  }, [config.shared.background.is_outdated, resetBackground])
+  }, [config.shared.background.is_outdated, retired, resetBackground])
 
   useLayoutEffect(() => {
     const index = backgrounds.findIndex((bg) => bg.id === activeBackground.id)
@@ -440,7 +513,7 @@ function Menu() {
             className="scrollbar-hide flex h-fit w-fit flex-wrap gap-2 text-sm"
             key={activeBackground.id}
           >
-            {activeBackground.tags.map((tag) => (
+            {getBackgroundLabels(activeBackground).map((tag) => (
               <motion.p
                 key={tag}
                 className="flex-shrink-0 rounded-md border border-zinc-800/80 bg-zinc-700/80 px-2 py-1 font-medium text-zinc-100 backdrop-blur"
@@ -552,12 +625,46 @@ function Menu() {
             </button>
           )}
 
-          {/* <button className="group rounded-full border-2 border-orange-900/50 bg-orange-950 p-3.5 text-orange-100 shadow-md ring-white transition will-change-transform hover:scale-105 hover:border-white focus-visible:scale-105 focus-visible:border-white focus-visible:outline-none focus-visible:ring-2 active:scale-95 active:border-orange-200 active:ring-orange-200">
-              <HeartIcon
-                size={24}
-                className="fill-transparent transition-colors group-hover:fill-current group-focus-visible:fill-current group-active:fill-orange-200 group-active:stroke-orange-200"
-              />
-            </button> */}
+          <button
+            className="group rounded-full border-2 border-orange-900/50 bg-orange-950 p-3.5 text-orange-100 shadow-md ring-white transition will-change-transform hover:scale-105 hover:border-white focus-visible:scale-105 focus-visible:border-white focus-visible:outline-none focus-visible:ring-2 active:scale-95 active:border-orange-200 active:ring-orange-200"
+            onClick={() =>
+              setFavorite({ id: activeBackground.id, favorite: !isFavorite })
+            }
+            title={isFavorite ? 'Remove from Favorites' : 'Add to Favorites'}
+          >
+            <HeartIcon
+              size={24}
+              className={clsx(
+                'transition-colors group-active:fill-orange-200 group-active:stroke-orange-200',
+                isFavorite
+                  ? 'fill-current'
+                  : 'fill-transparent group-hover:fill-current group-focus-visible:fill-current'
+              )}
+            />
+          </button>
+          {config.targets.length > 1 && (
+            <select
+              className="h-14 max-w-48 rounded-[0.2rem] border-2 border-zinc-700 bg-zinc-900 px-3 text-zinc-50 outline-none ring-white transition focus-visible:border-white focus-visible:ring-1"
+              value={selectedTarget}
+              onChange={(e) => {
+                setSelectedTarget(e.target.value)
+                resetSetBackground()
+              }}
+              title="Apply to"
+            >
+              <option value="all">All Launchers</option>
+              {config.targets.map((state) => (
+                <option
+                  key={targetKey(state.target)}
+                  value={targetKey(state.target)}
+                >
+                  {state.target.platform === 'Steam'
+                    ? `Steam: ${state.name}`
+                    : state.name}
+                </option>
+              ))}
+            </select>
+          )}
           <button
             className={clsx(
               'h-14 w-40 select-none rounded-[0.2rem] border-2 border-orange-800/40 bg-orange-500 px-10 text-center text-lg font-medium uppercase tracking-wider text-orange-50 shadow-md ring-white transition will-change-transform hover:scale-105 hover:rounded hover:border-orange-50 focus-visible:scale-105 focus-visible:border-white focus-visible:outline-none focus-visible:ring-1 active:scale-95 disabled:!scale-100 disabled:!border-orange-800/40',
@@ -565,16 +672,19 @@ function Menu() {
             )}
             onClick={() => {
               if (setStatus === 'pending') return
-              setBackground({ id: activeBackground.id })
+              setBackground({
+                id: activeBackground.id,
+                targets: target ? [target.target] : undefined
+              })
             }}
             disabled={
-              config.shared.background.current === activeBackground.id ||
+              currentBackground === activeBackground.id ||
               setStatus === 'success'
             }
             key={activeBackground.id}
           >
             <AnimatePresence mode="wait" initial={false}>
-              {config.shared.background.current === activeBackground.id ||
+              {currentBackground === activeBackground.id ||
               setStatus === 'success' ? (
                 <motion.span
                   className="text-orange-100"
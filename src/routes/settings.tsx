@@ -1,5 +1,6 @@
 import { useQuery, useSuspenseQuery } from '@tanstack/react-query'
 import { createFileRoute, useRouter } from '@tanstack/react-router'
+import { open } from '@tauri-apps/plugin-dialog'
 import { relaunch } from '@tauri-apps/plugin-process'
 import clsx from 'clsx'
 import {
@@ -39,20 +40,35 @@ import {
 } from '@/lib/animations'
 import {
   backgroundToastIds,
+  backgroundsQueryOptions,
+  historyQueryOptions,
   invalidateActiveBackground,
+  launchOptionsQueryOptions,
   launchQueryOptions,
+  statusQueryOptions,
   updateQueryOptions,
   useBackgroundMutation,
   useCheckUpdates,
   useDebugConsoleMutation,
+  useDeleteCustomBackgroundMutation,
+  useLaunchOptionMutation,
   useResetBackgroundMutation,
   useResetMutation,
+  useRevertBackgroundMutation,
+  useRotateBackgroundMutation,
+  useRotationMutation,
+  useSaveCustomBackgroundMutation,
+  useSeasonalCalendarMutation,
   useSetupMutation,
   useUpdateMutation
 } from '@/lib/data'
 import { ConfigError, ConfigErrors, SetupError } from '@/lib/errors'
 import preventReload from '@/lib/preventReload'
-import type { Platform } from '@/lib/schemas'
+import type {
+  CustomBackground,
+  Platform,
+  RotationConfig
+} from '@/lib/schemas'
 import useKeyPress from '@/lib/useKeyPress'
 
 export const Route = createFileRoute('/settings')({
@@ -240,6 +256,63 @@ function Settings() {
             </div>
             <ToggleConsole />
             <CustomBackgroundSetter />
+            <CustomBackgroundLibrary />
+          </motion.div>
+          <motion.div
+            className="flex flex-col gap-2"
+            variants={moveInLessVariants}
+          >
+            <div className="flex items-baseline gap-2.5 text-zinc-400">
+              <h2 className="select-none text-lg font-bold text-white">
+                Rotation
+              </h2>
+              <p className="select-none">
+                Apply a new background automatically while OverBuddy is
+                running.
+              </p>
+            </div>
+            <RotationSettings />
+            <SeasonalCalendar />
+          </motion.div>
+          <motion.div
+            className="flex flex-col gap-2"
+            variants={moveInLessVariants}
+          >
+            <div className="flex items-baseline gap-2.5 text-zinc-400">
+              <h2 className="select-none text-lg font-bold text-white">
+                History
+              </h2>
+              <p className="select-none">Backgrounds you applied recently.</p>
+            </div>
+            <BackgroundHistoryList />
+          </motion.div>
+          <motion.div
+            className="flex flex-col gap-2"
+            variants={moveInLessVariants}
+          >
+            <div className="flex items-baseline gap-2.5 text-zinc-400">
+              <h2 className="select-none text-lg font-bold text-white">
+                Status
+              </h2>
+              <p className="select-none">
+                What each launcher and Steam account is set to.
+              </p>
+            </div>
+            <TargetStatusList />
+          </motion.div>
+          <motion.div
+            className="flex flex-col gap-2"
+            variants={moveInLessVariants}
+          >
+            <div className="flex items-baseline gap-2.5 text-zinc-400">
+              <h2 className="select-none text-lg font-bold text-white">
+                Launch Options
+              </h2>
+              <p className="select-none">
+                Other arguments Overwatch is launched with.
+              </p>
+            </div>
+            <LaunchOptionsEditor />
           </motion.div>
           <motion.div
             className="flex flex-col gap-1.5"
@@ -279,12 +352,12 @@ function Platforms() {
         return
       } else if (
         error instanceof ConfigError &&
-        ConfigErrors.safeParse(error.error_key).success
+        ConfigErrors.safeParse(error.code).success
       ) {
         router.navigate({
           to: '/setup/$key',
           params: {
-            key: error.error_key
+            key: error.code
           },
           search: {
             message: error.message,
@@ -527,6 +600,7 @@ function Platforms() {
               {data.steam.enabled && data.steam.profiles && (
                 <SteamProfileList
                   steam_profiles={data.steam.profiles}
+                  include_new_accounts={data.steam.include_new_accounts}
                   isFetching={isFetching}
                 />
               )}
@@ -910,13 +984,14 @@ function ToggleConsole() {
 }
 
 function formatCustomBackgroundId(input: string) {
-  if (input.startsWith('0x08') && input.length === 18) {
-    return input
+  const hex = input.startsWith('0x') ? input.slice(2) : input
+  // Only 16 digits are a full id, anything shorter is the index
+  if (hex.length === 16) {
+    return `0x${hex}`
   }
-  const hex = input.startsWith('0x08') ? input.slice(4) : input
   const cleaned = hex.replace(/^0+/, '')
 
-  return `0x080000000000${cleaned.padStart(4, '0')}`
+  return `0x08${cleaned.padStart(14, '0')}`
 }
 
 const patternString = '^(0x)?[0-9A-F]{1,16}$'
@@ -1076,3 +1151,570 @@ function CustomBackgroundSetter() {
     </AlertDialog>
   )
 }
+
+function CustomBackgroundLibrary() {
+  const { data: config } = useSuspenseQuery(launchQueryOptions)
+  const { mutate: applyBackground, status } = useBackgroundMutation()
+  const { mutate: deleteBackground } = useDeleteCustomBackgroundMutation()
+
+  if (config.custom_backgrounds.length === 0) {
+    return null
+  }
+
+  return (
+    <motion.ul
+      className="flex flex-col gap-2"
+      layout
+      transition={{ duration: 0.15 }}
+    >
+      {config.custom_backgrounds.map((background) => (
+        <li
+          key={background.id}
+          className="flex items-center gap-3 rounded-lg bg-zinc-800/50 p-2"
+        >
+          {background.image ? (
+            <img
+              src={background.image}
+              alt={background.name}
+              className="h-12 w-20 shrink-0 rounded object-cover"
+            />
+          ) : (
+            <div className="h-12 w-20 shrink-0 rounded bg-zinc-800" />
+          )}
+          <div className="flex min-w-0 flex-1 flex-col">
+            <div className="flex items-baseline gap-2">
+              <p className="truncate font-medium text-white">
+                {background.name}
+              </p>
+              <code className="select-all text-sm text-zinc-400">
+                {background.id}
+              </code>
+            </div>
+            {(background.notes || background.tags.length > 0) && (
+              <p className="truncate text-sm text-zinc-400">
+                {[background.tags.join(', '), background.notes]
+                  .filter(Boolean)
+                  .join(' · ')}
+              </p>
+            )}
+          </div>
+          <MotionButton
+            onClick={() =>
+              applyBackground({ id: background.id, isCustom: true })
+            }
+            disabled={
+              status === 'pending' ||
+              config.shared.background.current === background.id
+            }
+          >
+            {config.shared.background.current === background.id
+              ? 'Applied'
+              : 'Apply'}
+          </MotionButton>
+          <CustomBackgroundEditor background={background} />
+          <MotionButton
+            destructive
+            onClick={() => deleteBackground(background.id)}
+          >
+            Delete
+          </MotionButton>
+        </li>
+      ))}
+    </motion.ul>
+  )
+}
+
+function CustomBackgroundEditor({
+  background
+}: {
+  background: CustomBackground
+}) {
+  const [isOpen, setIsOpen] = useState(false)
+  const [name, setName] = useState(background.name)
+  const [notes, setNotes] = useState(background.notes ?? '')
+  const [tags, setTags] = useState(background.tags.join(', '))
+  const [image, setImage] = useState<string | null>(null)
+
+  const { mutate, status } = useSaveCustomBackgroundMutation({
+    onSuccess: () => setIsOpen(false)
+  })
+
+  const handleOpenChange = (open: boolean) => {
+    if (open) {
+      setName(background.name)
+      setNotes(background.notes ?? '')
+      setTags(background.tags.join(', '))
+      setImage(null)
+    }
+    setIsOpen(open)
+  }
+
+  const handleSave = (e: React.MouseEvent<HTMLButtonElement>) => {
+    e.preventDefault()
+    mutate({
+      background: {
+        ...background,
+        name,
+        notes: notes.trim() || null,
+        tags: tags
+          .split(',')
+          .map((tag) => tag.trim())
+          .filter(Boolean)
+      },
+      image: image ?? undefined
+    })
+  }
+
+  const inputClassName =
+    'w-full rounded border border-zinc-700 bg-zinc-800 p-2 text-zinc-50 outline-none ring-zinc-600 transition selection:bg-zinc-500 selection:text-zinc-50 placeholder:text-zinc-500 focus-visible:border-zinc-600 focus-visible:ring-2'
+
+  return (
+    <AlertDialog open={isOpen} onOpenChange={handleOpenChange}>
+      <AlertDialogTrigger asChild>
+        <MotionButton>Edit</MotionButton>
+      </AlertDialogTrigger>
+      <AlertDialogContent>
+        <AlertDialogHeader>
+          <AlertDialogTitle>Edit Custom Background</AlertDialogTitle>
+          <AlertDialogDescription>
+            Details of <code className="select-all">{background.id}</code>{' '}
+            are only stored in OverBuddy.
+          </AlertDialogDescription>
+        </AlertDialogHeader>
+        <div className="flex flex-col gap-2">
+          <input
+            type="text"
+            value={name}
+            onChange={(e) => setName(e.target.value)}
+            placeholder="Name"
+            className={inputClassName}
+            autoFocus
+          />
+          <input
+            type="text"
+            value={tags}
+            onChange={(e) => setTags(e.target.value)}
+            placeholder="Tags, separated by commas"
+            className={inputClassName}
+          />
+          <textarea
+            value={notes}
+            onChange={(e) => setNotes(e.target.value)}
+            placeholder="Notes"
+            className={clsx(inputClassName, 'resize-none')}
+            rows={3}
+          />
+          <div className="flex items-center gap-2 text-zinc-400">
+            <MotionButton
+              onClick={async () => {
+                const selected = await open({
+                  filters: [
+                    {
+                      name: 'Image',
+                      extensions: ['png', 'jpg', 'jpeg', 'webp', 'gif']
+                    }
+                  ]
+                })
+                if (selected) setImage(selected)
+              }}
+            >
+              Choose Preview Image
+            </MotionButton>
+            <p className="truncate">
+              {image ??
+                (background.image ? 'Keeping the current image.' : 'No image.')}
+            </p>
+          </div>
+        </div>
+        <AlertDialogFooter>
+          <AlertDialogCancel>Cancel</AlertDialogCancel>
+          <AlertDialogAction
+            onClick={handleSave}
+            disabled={status === 'pending'}
+          >
+            Save
+          </AlertDialogAction>
+        </AlertDialogFooter>
+      </AlertDialogContent>
+    </AlertDialog>
+  )
+}
+
+const rotationIntervals: {
+  value: RotationConfig['interval']
+  label: string
+}[] = [
+  { value: 'daily', label: 'Every Day' },
+  { value: 'weekly', label: 'Every Week' },
+  { value: 'every_launch', label: 'Every Launch' }
+]
+
+const rotationSources: { value: RotationConfig['source']; label: string }[] = [
+  { value: 'all', label: 'All Backgrounds' },
+  { value: 'favorites', label: 'Favorites' },
+  { value: 'tags', label: 'Tags' }
+]
+
+function RotationSettings() {
+  const { data: config } = useSuspenseQuery(launchQueryOptions)
+  const { mutate, status } = useRotationMutation()
+  const { mutate: rotate, status: rotateStatus } =
+    useRotateBackgroundMutation()
+  const [tags, setTags] = useState(config.rotation.tags.join(', '))
+
+  const rotation = config.rotation
+  const update = (changes: Partial<RotationConfig>) =>
+    mutate({ ...rotation, ...changes })
+
+  const selectClassName =
+    'rounded border border-zinc-700 bg-zinc-800 p-2 text-zinc-50 outline-none ring-zinc-600 transition focus-visible:border-zinc-600 focus-visible:ring-2 disabled:opacity-50'
+
+  return (
+    <div className="flex flex-col gap-2">
+      <div className="flex w-full items-center gap-4">
+        <MotionButton
+          className="w-fit min-w-[12.5625rem]"
+          onClick={() => update({ enabled: !rotation.enabled })}
+          disabled={status === 'pending'}
+        >
+          {rotation.enabled ? 'Disable Rotation' : 'Enable Rotation'}
+        </MotionButton>
+        <MotionButton
+          onClick={() => rotate()}
+          disabled={rotateStatus === 'pending'}
+        >
+          Next Background
+        </MotionButton>
+        <p className="select-none text-zinc-400">
+          Never applied while Overwatch is running.
+        </p>
+      </div>
+      <div className="flex items-center gap-2 text-zinc-400">
+        <select
+          className={selectClassName}
+          value={rotation.interval}
+          onChange={(e) =>
+            update({
+              interval: e.target.value as RotationConfig['interval']
+            })
+          }
+          disabled={status === 'pending'}
+        >
+          {rotationIntervals.map(({ value, label }) => (
+            <option key={value} value={value}>
+              {label}
+            </option>
+          ))}
+        </select>
+        <span className="select-none">from</span>
+        <select
+          className={selectClassName}
+          value={rotation.source}
+          onChange={(e) =>
+            update({ source: e.target.value as RotationConfig['source'] })
+          }
+          disabled={status === 'pending'}
+        >
+          {rotationSources.map(({ value, label }) => (
+            <option key={value} value={value}>
+              {label}
+            </option>
+          ))}
+        </select>
+        {rotation.source === 'tags' && (
+          <input
+            type="text"
+            value={tags}
+            onChange={(e) => setTags(e.target.value)}
+            onBlur={() =>
+              update({
+                tags: tags
+                  .split(',')
+                  .map((tag) => tag.trim())
+                  .filter(Boolean)
+              })
+            }
+            placeholder="Tags, separated by commas"
+            className={clsx(selectClassName, 'flex-1')}
+          />
+        )}
+        <span className="select-none">skipping the last</span>
+        <input
+          type="number"
+          min={0}
+          max={20}
+          value={rotation.no_repeat}
+          onChange={(e) =>
+            update({ no_repeat: Math.max(0, Number(e.target.value) || 0) })
+          }
+          className={clsx(selectClassName, 'w-16')}
+          disabled={status === 'pending'}
+        />
+        <span className="select-none">backgrounds.</span>
+      </div>
+    </div>
+  )
+}
+
+function SeasonalCalendar() {
+  const { data: config } = useSuspenseQuery(launchQueryOptions)
+  const { data: backgrounds } = useQuery(backgroundsQueryOptions)
+  const { mutate, status } = useSeasonalCalendarMutation()
+
+  const seasonal = config.seasonal
+  const backgroundName = (id: string) =>
+    backgrounds?.find((background) => background.id === id)?.name ?? id
+
+  return (
+    <div className="flex flex-col gap-2">
+      <div className="flex w-full items-center gap-4">
+        <MotionButton
+          className="w-fit min-w-[12.5625rem]"
+          onClick={() => mutate({ enabled: !seasonal.enabled })}
+          disabled={status === 'pending'}
+        >
+          {seasonal.enabled
+            ? 'Disable Seasonal Calendar'
+            : 'Enable Seasonal Calendar'}
+        </MotionButton>
+        <p className="select-none text-zinc-400">
+          {seasonal.active
+            ? `Showing ${backgroundName(seasonal.active.background)} for ${seasonal.active.name}.`
+            : 'Switch to event backgrounds while events run, then switch back.'}
+        </p>
+      </div>
+      <ul
+        className={clsx(
+          'grid grid-cols-2 gap-x-4 gap-y-1 text-sm text-zinc-400 transition-colors',
+          !seasonal.enabled && 'text-zinc-600'
+        )}
+      >
+        {seasonal.events.map((event) => (
+          <li key={event.name} className="flex items-baseline gap-2">
+            <span className="font-medium text-zinc-300">{event.name}</span>
+            <span>
+              {event.start} to {event.end}
+            </span>
+            <span className="truncate">{backgroundName(event.background)}</span>
+          </li>
+        ))}
+      </ul>
+    </div>
+  )
+}
+
+function BackgroundHistoryList() {
+  const { data: history } = useQuery(historyQueryOptions)
+  const { mutate: revert, status } = useRevertBackgroundMutation()
+
+  const entries = history?.entries.slice(0, 8) ?? []
+
+  return (
+    <div className="flex flex-col gap-2">
+      <div className="flex w-full items-center gap-4">
+        <MotionButton
+          className="w-fit min-w-[12.5625rem]"
+          onClick={() => revert()}
+          disabled={status === 'pending' || entries.length === 0}
+        >
+          Revert to Previous Background
+        </MotionButton>
+        {history && history.backgrounds.length > 0 && (
+          <p className="select-none text-zinc-400">
+            Most applied: {history.backgrounds[0].name} (
+            {history.backgrounds[0].apply_count} times).
+          </p>
+        )}
+      </div>
+      <ul className="flex flex-col gap-1 text-sm text-zinc-400">
+        {entries.map((entry, index) => (
+          <li
+            key={`${entry.applied_at}-${index}`}
+            className={clsx(
+              'flex items-baseline gap-2',
+              !entry.available && 'text-zinc-600'
+            )}
+          >
+            <span className="w-36 shrink-0">
+              {new Date(entry.applied_at * 1000).toLocaleString()}
+            </span>
+            <span className="font-medium text-zinc-300">{entry.name}</span>
+            <code className="select-all">{entry.id}</code>
+            {!entry.available && <span>No longer available</span>}
+          </li>
+        ))}
+      </ul>
+    </div>
+  )
+}
+
+function TargetStatusList() {
+  const { data: status } = useQuery(statusQueryOptions)
+
+  if (!status) return null
+
+  return (
+    <ul className="flex flex-col gap-2 text-sm text-zinc-400">
+      {status.targets.map((target) => (
+        <li key={target.path} className="flex flex-col gap-0.5">
+          <span className="font-medium text-zinc-300">{target.name}</span>
+          <code className="select-all break-all">{target.path}</code>
+          {target.error ? (
+            <span className="text-red-400">
+              {target.error.segments.map((segment) => segment.text).join('')}
+            </span>
+          ) : (
+            <>
+              <span>
+                Background:{' '}
+                {target.background.current ? (
+                  <code className="select-all">
+                    {target.background.current}
+                  </code>
+                ) : (
+                  'Default'
+                )}
+                , debug console{' '}
+                {target.additional.console_enabled ? 'enabled' : 'disabled'}
+              </span>
+              {target.launch_args && (
+                <code className="select-all break-all">
+                  {target.launch_args}
+                </code>
+              )}
+            </>
+          )}
+          {target.modified !== null && (
+            <span>
+              Modified {new Date(target.modified * 1000).toLocaleString()}
+            </span>
+          )}
+        </li>
+      ))}
+    </ul>
+  )
+}
+
+function LaunchOptionsEditor() {
+  const { data: options } = useQuery(launchOptionsQueryOptions)
+  const { mutate, status } = useLaunchOptionMutation()
+  const [flag, setFlag] = useState('')
+  const [value, setValue] = useState('')
+
+  if (!options) return null
+
+  const inputClassName =
+    'rounded border border-zinc-700 bg-zinc-800 p-2 text-zinc-50 outline-none ring-zinc-600 transition focus-visible:border-zinc-600 focus-visible:ring-2 disabled:opacity-50'
+  const known = options.catalog.find((known) => known.flag === flag.trim())
+  const isManaged = (arg: string) =>
+    options.catalog.some(
+      (known) =>
+        known.managed &&
+        (arg === known.flag || arg.startsWith(`${known.flag}=`))
+    )
+
+  return (
+    <div className="flex flex-col gap-3">
+      <form
+        className="flex flex-wrap items-center gap-2 text-zinc-400"
+        onSubmit={(e) => {
+          e.preventDefault()
+          if (!flag.trim()) return
+          mutate(
+            { flag: flag.trim(), value: value.trim() || undefined },
+            {
+              onSuccess: () => {
+                setFlag('')
+                setValue('')
+              }
+            }
+          )
+        }}
+      >
+        <input
+          type="text"
+          list="known-flags"
+          value={flag}
+          onChange={(e) => setFlag(e.target.value)}
+          placeholder="Flag, e.g. --tank_WorkerThreadCount"
+          className={clsx(inputClassName, 'w-72')}
+        />
+        <datalist id="known-flags">
+          {options.catalog
+            .filter((known) => !known.managed)
+            .map((known) => (
+              <option key={known.flag} value={known.flag}>
+                {known.description}
+              </option>
+            ))}
+        </datalist>
+        <input
+          type={known?.value === 'integer' ? 'number' : 'text'}
+          value={value}
+          onChange={(e) => setValue(e.target.value)}
+          placeholder="Value"
+          className={clsx(inputClassName, 'w-32')}
+          disabled={known?.value === 'none'}
+        />
+        <MotionButton
+          className="w-fit"
+          type="submit"
+          disabled={status === 'pending' || !flag.trim()}
+        >
+          Add to All Launchers
+        </MotionButton>
+        {known && <span className="select-none">{known.description}.</span>}
+      </form>
+      <p className="text-sm text-zinc-400">
+        Only flags that start with - can be edited here. Other arguments, such
+        as environment variables, are kept and can be changed in the launcher.
+      </p>
+      <ul className="flex flex-col gap-2 text-sm text-zinc-400">
+        {options.targets.map((target) => (
+          <li
+            key={`${target.target.platform}-${target.name}`}
+            className="flex flex-col gap-1"
+          >
+            <span className="font-medium text-zinc-300">{target.name}</span>
+            {target.error && (
+              <span className="text-red-400">
+                {target.error.segments.map((segment) => segment.text).join('')}
+              </span>
+            )}
+            <div className="flex flex-wrap gap-1.5">
+              {target.args.map((arg, index) => (
+                <span
+                  key={`${arg}-${index}`}
+                  className="flex items-center gap-1 rounded bg-zinc-800 px-2 py-0.5"
+                >
+                  <code className="select-all">{arg}</code>
+                  {arg.startsWith('-') && !isManaged(arg) && (
+                    <button
+                      className="text-zinc-500 transition-colors hover:text-zinc-50 disabled:opacity-50"
+                      title={`Remove ${arg}`}
+                      onClick={() =>
+                        mutate({
+                          flag: arg.split('=')[0],
+                          targets: [target.target],
+                          remove: true
+                        })
+                      }
+                      disabled={status === 'pending'}
+                    >
+                      <XIcon size={14} />
+                    </button>
+                  )}
+                </span>
+              ))}
+            </div>
+            {target.warnings.map((warning) => (
+              <span key={warning.message} className="text-amber-400">
+                {warning.message}
+              </span>
+            ))}
+          </li>
+        ))}
+      </ul>
+    </div>
+  )
+}
@@ -60,12 +60,12 @@ function ConfigureComponent() {
     onError: (error) => {
       if (
         error instanceof ConfigError &&
-        ConfigErrors.safeParse(error.error_key).success
+        ConfigErrors.safeParse(error.code).success
       ) {
         navigate({
           to: '/setup/$key',
           params: {
-            key: error.error_key
+            key: error.code
           },
           search: {
             message: error.message,
@@ -36,12 +36,12 @@ function SetupSelect() {
         reset()
       } else if (
         error instanceof ConfigError &&
-        ConfigErrors.safeParse(error.error_key).success
+        ConfigErrors.safeParse(error.code).success
       ) {
         navigate({
           to: '/setup/$key',
           params: {
-            key: error.error_key
+            key: error.code
           },
           search: {
             message: error.message,